@@ -0,0 +1,338 @@
+use crate::{RadioError, RadioResult};
+
+use super::{ctr, BlockCipher, BLOCK_LENGTH};
+
+/// Nonce length, leaving 2 bytes for the payload length (up to 65535 bytes)
+pub const NONCE_LENGTH: usize = 13;
+
+/// `L - 1` with `L` the size of the length field
+const LENGTH_FIELD: u8 = 1;
+
+/// CCM nonce. It must never be used twice with the same key
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub struct Nonce(pub [u8; NONCE_LENGTH]);
+
+impl Nonce {
+    /// Nonce of a packet sent by `source`, made of the source address, the 2 bit STack
+    /// sequence number (`TX_PCKT_INFO` on the transmitter, `RxPacket::sequence_number` on
+    /// the receiver) and `frame_counter`.
+    ///
+    /// As the sequence number wraps every 4 packets, `frame_counter` must be incremented for
+    /// every packet sent with the same key
+    pub fn for_packet(source: u8, sequence_number: u8, frame_counter: u32) -> Self {
+        let mut nonce = [0; NONCE_LENGTH];
+        nonce[0] = source;
+        nonce[1] = sequence_number & 0b11;
+        nonce[2..6].copy_from_slice(&frame_counter.to_be_bytes());
+
+        Self(nonce)
+    }
+}
+
+/// Counter with CBC-MAC authenticated encryption (NIST SP 800-38C, RFC 3610)
+pub struct Ccm<C: BlockCipher> {
+    cipher: C,
+    tag_length: usize,
+}
+
+impl<C: BlockCipher> Ccm<C> {
+    /// CCM with `cipher` and a tag of `tag_length` bytes (4, 6, 8, 10, 12, 14 or 16)
+    pub fn new(cipher: C, tag_length: usize) -> RadioResult<Self> {
+        if !(4..=16).contains(&tag_length) || !tag_length.is_multiple_of(2) {
+            return Err(RadioError::ParameterError);
+        }
+
+        Ok(Self { cipher, tag_length })
+    }
+
+    /// Length of the authentication tag
+    pub fn tag_length(&self) -> usize {
+        self.tag_length
+    }
+
+    /// Releases the block cipher
+    pub fn into_inner(self) -> C {
+        self.cipher
+    }
+
+    /// Encrypts `data` in place and writes its authentication tag, covering `data` and the
+    /// additional data `aad` (sent in clear), to `tag`
+    pub fn encrypt(
+        &mut self,
+        nonce: &Nonce,
+        aad: &[u8],
+        data: &mut [u8],
+        tag: &mut [u8],
+    ) -> RadioResult<()> {
+        if tag.len() != self.tag_length {
+            return Err(RadioError::ParameterError);
+        }
+
+        let mac = self.mac(nonce, aad, data)?;
+        let s0 = self.cipher.encrypt_block(&counter_block(nonce, 0))?;
+        for (i, byte) in tag.iter_mut().enumerate() {
+            *byte = mac[i] ^ s0[i];
+        }
+
+        ctr(&mut self.cipher, &counter_block(nonce, 1), data)
+    }
+
+    /// Decrypts `data` in place, checking `tag`. The lengths are checked before anything is
+    /// decrypted, and `data` is zeroed on every error, [`RadioError::AuthenticationFailed`]
+    /// included
+    pub fn decrypt(
+        &mut self,
+        nonce: &Nonce,
+        aad: &[u8],
+        data: &mut [u8],
+        tag: &[u8],
+    ) -> RadioResult<()> {
+        let result = self.decrypt_in_place(nonce, aad, data, tag);
+        if result.is_err() {
+            data.fill(0);
+        }
+
+        result
+    }
+
+    fn decrypt_in_place(
+        &mut self,
+        nonce: &Nonce,
+        aad: &[u8],
+        data: &mut [u8],
+        tag: &[u8],
+    ) -> RadioResult<()> {
+        if tag.len() != self.tag_length {
+            return Err(RadioError::ParameterError);
+        }
+        check_lengths(aad, data)?;
+
+        ctr(&mut self.cipher, &counter_block(nonce, 1), data)?;
+
+        let mac = self.mac(nonce, aad, data)?;
+        let s0 = self.cipher.encrypt_block(&counter_block(nonce, 0))?;
+
+        // Compare the whole tag whatever the first difference
+        let difference = tag.iter().enumerate().fold(0, |difference, (i, byte)| {
+            difference | (byte ^ mac[i] ^ s0[i])
+        });
+        if difference != 0 {
+            return Err(RadioError::AuthenticationFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Encrypts the first `len` bytes of `buffer` in place and appends the tag, returning
+    /// the length of the sealed payload
+    pub fn seal(
+        &mut self,
+        nonce: &Nonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+        len: usize,
+    ) -> RadioResult<usize> {
+        let sealed = len + self.tag_length;
+        if buffer.len() < sealed {
+            return Err(RadioError::BufferTooSmall);
+        }
+
+        let (data, tag) = buffer[..sealed].split_at_mut(len);
+        self.encrypt(nonce, aad, data, tag)?;
+
+        Ok(sealed)
+    }
+
+    /// Decrypts a payload sealed by [`Ccm::seal`] in place, returning the length of the
+    /// plaintext at the beginning of `sealed`
+    pub fn open(&mut self, nonce: &Nonce, aad: &[u8], sealed: &mut [u8]) -> RadioResult<usize> {
+        let len = sealed
+            .len()
+            .checked_sub(self.tag_length)
+            .ok_or(RadioError::InvalidFrame)?;
+
+        let (data, tag) = sealed.split_at_mut(len);
+        self.decrypt(nonce, aad, data, tag)?;
+
+        Ok(len)
+    }
+
+    /// CBC-MAC of the `B0` block, the additional data and the payload
+    fn mac(&mut self, nonce: &Nonce, aad: &[u8], data: &[u8]) -> RadioResult<[u8; BLOCK_LENGTH]> {
+        let (aad_length, data_length) = check_lengths(aad, data)?;
+
+        let mut b0 = [0; BLOCK_LENGTH];
+        b0[0] =
+            (!aad.is_empty() as u8) << 6 | ((self.tag_length as u8 - 2) / 2) << 3 | LENGTH_FIELD;
+        b0[1..=NONCE_LENGTH].copy_from_slice(&nonce.0);
+        b0[14..].copy_from_slice(&data_length.to_be_bytes());
+
+        let mut mac = self.cipher.encrypt_block(&b0)?;
+        if !aad.is_empty() {
+            self.absorb(&mut mac, &[&aad_length.to_be_bytes(), aad])?;
+        }
+        self.absorb(&mut mac, &[data])?;
+
+        Ok(mac)
+    }
+
+    /// Chains the concatenation of `parts`, zero padded to a whole number of blocks, into
+    /// the CBC-MAC
+    fn absorb(&mut self, mac: &mut [u8; BLOCK_LENGTH], parts: &[&[u8]]) -> RadioResult<()> {
+        let mut filled = 0;
+        for byte in parts.iter().flat_map(|part| part.iter()) {
+            mac[filled] ^= byte;
+            filled += 1;
+
+            if filled == BLOCK_LENGTH {
+                *mac = self.cipher.encrypt_block(mac)?;
+                filled = 0;
+            }
+        }
+
+        if filled > 0 {
+            *mac = self.cipher.encrypt_block(mac)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encoded lengths of `aad` and `data`. Payloads longer than 65535 bytes fail with
+/// [`RadioError::PacketTooLong`], and additional data of `0xFF00` bytes or more (needing a
+/// longer length encoding) with [`RadioError::ParameterError`]
+fn check_lengths(aad: &[u8], data: &[u8]) -> RadioResult<(u16, u16)> {
+    let data_length = u16::try_from(data.len()).map_err(|_| RadioError::PacketTooLong)?;
+    let aad_length = u16::try_from(aad.len())
+        .ok()
+        .filter(|len| *len < 0xFF00)
+        .ok_or(RadioError::ParameterError)?;
+
+    Ok((aad_length, data_length))
+}
+
+/// Counter block `A_i`
+fn counter_block(nonce: &Nonce, i: u16) -> [u8; BLOCK_LENGTH] {
+    let mut block = [0; BLOCK_LENGTH];
+    block[0] = LENGTH_FIELD;
+    block[1..=NONCE_LENGTH].copy_from_slice(&nonce.0);
+    block[14..].copy_from_slice(&i.to_be_bytes());
+
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::SoftAes128;
+
+    /// RFC 3610 packet vectors use the key `C0 C1 ... CF`, 8 bytes of additional data and
+    /// an 8 byte tag
+    fn rfc_3610_ccm() -> Ccm<SoftAes128> {
+        let key: [u8; BLOCK_LENGTH] = core::array::from_fn(|i| 0xC0 + i as u8);
+        Ccm::new(SoftAes128::new(&key), 8).unwrap()
+    }
+
+    /// Nonce `00 00 00 xx xx xx xx A0 A1 A2 A3 A4 A5`
+    fn rfc_3610_nonce(varying: [u8; 4]) -> Nonce {
+        let mut nonce = [0; NONCE_LENGTH];
+        nonce[3..7].copy_from_slice(&varying);
+        nonce[7..].copy_from_slice(&[0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5]);
+
+        Nonce(nonce)
+    }
+
+    fn check_vector(nonce: Nonce, plaintext_length: usize, expected: &[u8]) {
+        let aad: [u8; 8] = core::array::from_fn(|i| i as u8);
+        let plaintext: [u8; 24] = core::array::from_fn(|i| 8 + i as u8);
+
+        let mut buffer = [0; 32];
+        buffer[..plaintext_length].copy_from_slice(&plaintext[..plaintext_length]);
+
+        let sealed = rfc_3610_ccm()
+            .seal(&nonce, &aad, &mut buffer, plaintext_length)
+            .unwrap();
+        assert_eq!(&buffer[..sealed], expected);
+
+        let len = rfc_3610_ccm()
+            .open(&nonce, &aad, &mut buffer[..sealed])
+            .unwrap();
+        assert_eq!(&buffer[..len], &plaintext[..plaintext_length]);
+    }
+
+    /// RFC 3610 Packet Vector #1
+    #[test]
+    fn rfc_3610_packet_vector_1() {
+        check_vector(
+            rfc_3610_nonce([0x03, 0x02, 0x01, 0x00]),
+            23,
+            &[
+                0x58, 0x8C, 0x97, 0x9A, 0x61, 0xC6, 0x63, 0xD2, 0xF0, 0x66, 0xD0, 0xC2, 0xC0, 0xF9,
+                0x89, 0x80, 0x6D, 0x5F, 0x6B, 0x61, 0xDA, 0xC3, 0x84, 0x17, 0xE8, 0xD1, 0x2C, 0xFD,
+                0xF9, 0x26, 0xE0,
+            ],
+        );
+    }
+
+    /// RFC 3610 Packet Vector #2
+    #[test]
+    fn rfc_3610_packet_vector_2() {
+        check_vector(
+            rfc_3610_nonce([0x04, 0x03, 0x02, 0x01]),
+            24,
+            &[
+                0x72, 0xC9, 0x1A, 0x36, 0xE1, 0x35, 0xF8, 0xCF, 0x29, 0x1C, 0xA8, 0x94, 0x08, 0x5C,
+                0x87, 0xE3, 0xCC, 0x15, 0xC4, 0x39, 0xC9, 0xE4, 0x3A, 0x3B, 0xA0, 0x91, 0xD5, 0x6E,
+                0x10, 0x40, 0x09, 0x16,
+            ],
+        );
+    }
+
+    #[test]
+    fn decrypt_zeroes_data_on_authentication_failure() {
+        let nonce = rfc_3610_nonce([0x03, 0x02, 0x01, 0x00]);
+        let mut buffer = [0x55; 20];
+        let sealed = rfc_3610_ccm().seal(&nonce, &[], &mut buffer, 12).unwrap();
+
+        buffer[sealed - 1] ^= 0x01;
+        assert!(matches!(
+            rfc_3610_ccm().open(&nonce, &[], &mut buffer[..sealed]),
+            Err(RadioError::AuthenticationFailed)
+        ));
+        assert!(buffer[..12].iter().all(|byte| *byte == 0));
+    }
+
+    /// Block cipher counting the blocks it encrypts
+    struct CountingCipher(usize);
+
+    impl BlockCipher for CountingCipher {
+        fn encrypt_block(&mut self, block: &[u8; BLOCK_LENGTH]) -> RadioResult<[u8; BLOCK_LENGTH]> {
+            self.0 += 1;
+            Ok(*block)
+        }
+    }
+
+    #[test]
+    fn decrypt_checks_lengths_first() {
+        let nonce = Nonce([0; NONCE_LENGTH]);
+        let tag = [0; 8];
+
+        let mut ccm = Ccm::new(CountingCipher(0), 8).unwrap();
+        let mut data = [0xAA; 65_536];
+        assert!(matches!(
+            ccm.decrypt(&nonce, &[], &mut data, &tag),
+            Err(RadioError::PacketTooLong)
+        ));
+        assert!(data.iter().all(|byte| *byte == 0));
+
+        let aad = [0; 0xFF00];
+        let mut data = [0xAA; 16];
+        assert!(matches!(
+            ccm.decrypt(&nonce, &aad, &mut data, &tag),
+            Err(RadioError::ParameterError)
+        ));
+        assert!(data.iter().all(|byte| *byte == 0));
+
+        assert_eq!(ccm.into_inner().0, 0);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::RadioResult;
+
+use super::{BlockCipher, BLOCK_LENGTH};
+
+/// Encrypts (or decrypts) `data` in place in counter mode, starting with the `counter`
+/// block. The counter block is incremented as a 128 bit big endian integer, a counter
+/// value must never be reused with the same key
+pub fn ctr<C: BlockCipher>(
+    cipher: &mut C,
+    counter: &[u8; BLOCK_LENGTH],
+    data: &mut [u8],
+) -> RadioResult<()> {
+    let mut counter = u128::from_be_bytes(*counter);
+
+    for chunk in data.chunks_mut(BLOCK_LENGTH) {
+        let keystream = cipher.encrypt_block(&counter.to_be_bytes())?;
+        for (byte, key) in chunk.iter_mut().zip(keystream) {
+            *byte ^= key;
+        }
+
+        counter = counter.wrapping_add(1);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use crate::{RadioResult, SpiritAes, AES_BLOCK_LENGTH};
+
+use super::{BlockCipher, BLOCK_LENGTH};
+
+/// [`BlockCipher`] running on the SPIRIT1 AES co-processor, see [`SpiritAes`]
+pub struct HardwareAes<'a, R: SpiritAes> {
+    radio: &'a mut R,
+}
+
+impl<'a, R: SpiritAes> HardwareAes<'a, R> {
+    /// Loads `key` in the co-processor. The AES engine must be enabled
+    /// ([`SpiritAes::aes_enable`])
+    pub fn new(radio: &'a mut R, key: &[u8; AES_BLOCK_LENGTH]) -> RadioResult<Self> {
+        radio.aes_set_key(key)?;
+
+        Ok(Self { radio })
+    }
+}
+
+impl<R: SpiritAes> BlockCipher for HardwareAes<'_, R> {
+    fn encrypt_block(&mut self, block: &[u8; BLOCK_LENGTH]) -> RadioResult<[u8; BLOCK_LENGTH]> {
+        self.radio.aes_encrypt_block(block)
+    }
+}
@@ -0,0 +1,55 @@
+//! # Payload Encryption
+//! Authenticated encryption of whole payloads on top of a 128 bit block cipher, either
+//! the SPIRIT1 co-processor ([`HardwareAes`]) or the software implementation
+//! ([`SoftAes128`]) which also runs on the host to cross-check the results.
+//!
+//! - [`ctr`] encrypts without authentication (NIST SP 800-38A)
+//! - [`Ccm`] encrypts and authenticates the payload and additional data
+//!   (NIST SP 800-38C / RFC 3610), with a 13 byte [`Nonce`]
+//!
+//! Both modes only use the forward (encryption) direction of the cipher.
+//!
+//! ## Example
+//! ```no_run
+//! # #![allow(incomplete_features)]
+//! # #![feature(generic_const_exprs)]
+//! # use spirit1_rs::{crypto::*, prelude::*};
+//! # fn seal(
+//! #     mut radio: impl SpiritAes,
+//! #     key: [u8; 16],
+//! #     header: [u8; 4],
+//! #     mut buffer: [u8; 64],
+//! #     payload_len: usize,
+//! #     (my_address, sequence_number, frame_counter): (u8, u8, u32),
+//! # ) -> RadioResult<()> {
+//! radio.aes_enable(true)?;
+//! let mut ccm = Ccm::new(HardwareAes::new(&mut radio, &key)?, 8)?;
+//!
+//! let nonce = Nonce::for_packet(my_address, sequence_number, frame_counter);
+//! let len = ccm.seal(&nonce, &header, &mut buffer, payload_len)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::RadioResult;
+
+mod soft;
+pub use soft::*;
+
+mod hardware;
+pub use hardware::*;
+
+mod ctr;
+pub use ctr::*;
+
+mod ccm;
+pub use ccm::*;
+
+/// Cipher block length
+pub const BLOCK_LENGTH: usize = 16;
+
+/// 128 bit block cipher, encryption direction
+pub trait BlockCipher {
+    /// Encrypts one block with the cipher key
+    fn encrypt_block(&mut self, block: &[u8; BLOCK_LENGTH]) -> RadioResult<[u8; BLOCK_LENGTH]>;
+}
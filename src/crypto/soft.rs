@@ -0,0 +1,278 @@
+use crate::RadioResult;
+
+use super::{BlockCipher, BLOCK_LENGTH};
+
+/// Number of rounds of AES-128
+const ROUNDS: usize = 10;
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7C, 0x77, 0x7B, 0xF2, 0x6B, 0x6F, 0xC5, 0x30, 0x01, 0x67, 0x2B, 0xFE, 0xD7, 0xAB, 0x76,
+    0xCA, 0x82, 0xC9, 0x7D, 0xFA, 0x59, 0x47, 0xF0, 0xAD, 0xD4, 0xA2, 0xAF, 0x9C, 0xA4, 0x72, 0xC0,
+    0xB7, 0xFD, 0x93, 0x26, 0x36, 0x3F, 0xF7, 0xCC, 0x34, 0xA5, 0xE5, 0xF1, 0x71, 0xD8, 0x31, 0x15,
+    0x04, 0xC7, 0x23, 0xC3, 0x18, 0x96, 0x05, 0x9A, 0x07, 0x12, 0x80, 0xE2, 0xEB, 0x27, 0xB2, 0x75,
+    0x09, 0x83, 0x2C, 0x1A, 0x1B, 0x6E, 0x5A, 0xA0, 0x52, 0x3B, 0xD6, 0xB3, 0x29, 0xE3, 0x2F, 0x84,
+    0x53, 0xD1, 0x00, 0xED, 0x20, 0xFC, 0xB1, 0x5B, 0x6A, 0xCB, 0xBE, 0x39, 0x4A, 0x4C, 0x58, 0xCF,
+    0xD0, 0xEF, 0xAA, 0xFB, 0x43, 0x4D, 0x33, 0x85, 0x45, 0xF9, 0x02, 0x7F, 0x50, 0x3C, 0x9F, 0xA8,
+    0x51, 0xA3, 0x40, 0x8F, 0x92, 0x9D, 0x38, 0xF5, 0xBC, 0xB6, 0xDA, 0x21, 0x10, 0xFF, 0xF3, 0xD2,
+    0xCD, 0x0C, 0x13, 0xEC, 0x5F, 0x97, 0x44, 0x17, 0xC4, 0xA7, 0x7E, 0x3D, 0x64, 0x5D, 0x19, 0x73,
+    0x60, 0x81, 0x4F, 0xDC, 0x22, 0x2A, 0x90, 0x88, 0x46, 0xEE, 0xB8, 0x14, 0xDE, 0x5E, 0x0B, 0xDB,
+    0xE0, 0x32, 0x3A, 0x0A, 0x49, 0x06, 0x24, 0x5C, 0xC2, 0xD3, 0xAC, 0x62, 0x91, 0x95, 0xE4, 0x79,
+    0xE7, 0xC8, 0x37, 0x6D, 0x8D, 0xD5, 0x4E, 0xA9, 0x6C, 0x56, 0xF4, 0xEA, 0x65, 0x7A, 0xAE, 0x08,
+    0xBA, 0x78, 0x25, 0x2E, 0x1C, 0xA6, 0xB4, 0xC6, 0xE8, 0xDD, 0x74, 0x1F, 0x4B, 0xBD, 0x8B, 0x8A,
+    0x70, 0x3E, 0xB5, 0x66, 0x48, 0x03, 0xF6, 0x0E, 0x61, 0x35, 0x57, 0xB9, 0x86, 0xC1, 0x1D, 0x9E,
+    0xE1, 0xF8, 0x98, 0x11, 0x69, 0xD9, 0x8E, 0x94, 0x9B, 0x1E, 0x87, 0xE9, 0xCE, 0x55, 0x28, 0xDF,
+    0x8C, 0xA1, 0x89, 0x0D, 0xBF, 0xE6, 0x42, 0x68, 0x41, 0x99, 0x2D, 0x0F, 0xB0, 0x54, 0xBB, 0x16,
+];
+
+const INV_SBOX: [u8; 256] = invert(&SBOX);
+
+/// Round constants of the key expansion
+const RCON: [u8; ROUNDS] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1B, 0x36];
+
+/// Software AES-128 (FIPS-197), giving the same results as the SPIRIT1 co-processor
+pub struct SoftAes128 {
+    round_keys: [[u8; BLOCK_LENGTH]; ROUNDS + 1],
+}
+
+impl SoftAes128 {
+    /// Expands `key`
+    pub fn new(key: &[u8; BLOCK_LENGTH]) -> Self {
+        let mut round_keys = [[0; BLOCK_LENGTH]; ROUNDS + 1];
+        round_keys[0] = *key;
+
+        for round in 1..=ROUNDS {
+            let previous = round_keys[round - 1];
+
+            // RotWord, SubWord and Rcon on the last word of the previous round key
+            let mut word = [
+                SBOX[previous[13] as usize] ^ RCON[round - 1],
+                SBOX[previous[14] as usize],
+                SBOX[previous[15] as usize],
+                SBOX[previous[12] as usize],
+            ];
+
+            for i in 0..BLOCK_LENGTH {
+                word[i % 4] ^= previous[i];
+                round_keys[round][i] = word[i % 4];
+            }
+        }
+
+        Self { round_keys }
+    }
+
+    /// Cipher of the decryption key derived from the encryption key (the last round key),
+    /// as given by the SPIRIT1 co-processor
+    pub fn from_decryption_key(key: &[u8; BLOCK_LENGTH]) -> Self {
+        let mut round_keys = [[0; BLOCK_LENGTH]; ROUNDS + 1];
+        round_keys[ROUNDS] = *key;
+
+        for round in (1..=ROUNDS).rev() {
+            let next = round_keys[round];
+
+            for i in (4..BLOCK_LENGTH).rev() {
+                round_keys[round - 1][i] = next[i] ^ next[i - 4];
+            }
+
+            let previous = round_keys[round - 1];
+            let word = [
+                SBOX[previous[13] as usize] ^ RCON[round - 1],
+                SBOX[previous[14] as usize],
+                SBOX[previous[15] as usize],
+                SBOX[previous[12] as usize],
+            ];
+            for i in 0..4 {
+                round_keys[round - 1][i] = next[i] ^ word[i];
+            }
+        }
+
+        Self { round_keys }
+    }
+
+    /// Decryption key derived from the encryption key, see [`SoftAes128::from_decryption_key`]
+    pub fn decryption_key(&self) -> [u8; BLOCK_LENGTH] {
+        self.round_keys[ROUNDS]
+    }
+
+    /// Encrypts one block
+    pub fn encrypt(&self, block: &[u8; BLOCK_LENGTH]) -> [u8; BLOCK_LENGTH] {
+        let mut state = *block;
+        add_round_key(&mut state, &self.round_keys[0]);
+
+        for round in 1..=ROUNDS {
+            for byte in state.iter_mut() {
+                *byte = SBOX[*byte as usize];
+            }
+            shift_rows(&mut state);
+            if round != ROUNDS {
+                mix_columns(&mut state);
+            }
+            add_round_key(&mut state, &self.round_keys[round]);
+        }
+
+        state
+    }
+
+    /// Decrypts one block
+    pub fn decrypt(&self, block: &[u8; BLOCK_LENGTH]) -> [u8; BLOCK_LENGTH] {
+        let mut state = *block;
+        add_round_key(&mut state, &self.round_keys[ROUNDS]);
+
+        for round in (0..ROUNDS).rev() {
+            inv_shift_rows(&mut state);
+            for byte in state.iter_mut() {
+                *byte = INV_SBOX[*byte as usize];
+            }
+            add_round_key(&mut state, &self.round_keys[round]);
+            if round != 0 {
+                inv_mix_columns(&mut state);
+            }
+        }
+
+        state
+    }
+}
+
+impl BlockCipher for SoftAes128 {
+    fn encrypt_block(&mut self, block: &[u8; BLOCK_LENGTH]) -> RadioResult<[u8; BLOCK_LENGTH]> {
+        Ok(self.encrypt(block))
+    }
+}
+
+fn add_round_key(state: &mut [u8; BLOCK_LENGTH], key: &[u8; BLOCK_LENGTH]) {
+    for (byte, key) in state.iter_mut().zip(key) {
+        *byte ^= key;
+    }
+}
+
+/// The state is stored column by column, row `r` being bytes `r`, `r + 4`, `r + 8`, `r + 12`
+fn shift_rows(state: &mut [u8; BLOCK_LENGTH]) {
+    let previous = *state;
+    for column in 0..4 {
+        for row in 1..4 {
+            state[column * 4 + row] = previous[((column + row) % 4) * 4 + row];
+        }
+    }
+}
+
+fn inv_shift_rows(state: &mut [u8; BLOCK_LENGTH]) {
+    let previous = *state;
+    for column in 0..4 {
+        for row in 1..4 {
+            state[((column + row) % 4) * 4 + row] = previous[column * 4 + row];
+        }
+    }
+}
+
+fn mix_columns(state: &mut [u8; BLOCK_LENGTH]) {
+    for column in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        column[0] = mul(a0, 2) ^ mul(a1, 3) ^ a2 ^ a3;
+        column[1] = a0 ^ mul(a1, 2) ^ mul(a2, 3) ^ a3;
+        column[2] = a0 ^ a1 ^ mul(a2, 2) ^ mul(a3, 3);
+        column[3] = mul(a0, 3) ^ a1 ^ a2 ^ mul(a3, 2);
+    }
+}
+
+fn inv_mix_columns(state: &mut [u8; BLOCK_LENGTH]) {
+    for column in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        column[0] = mul(a0, 14) ^ mul(a1, 11) ^ mul(a2, 13) ^ mul(a3, 9);
+        column[1] = mul(a0, 9) ^ mul(a1, 14) ^ mul(a2, 11) ^ mul(a3, 13);
+        column[2] = mul(a0, 13) ^ mul(a1, 9) ^ mul(a2, 14) ^ mul(a3, 11);
+        column[3] = mul(a0, 11) ^ mul(a1, 13) ^ mul(a2, 9) ^ mul(a3, 14);
+    }
+}
+
+/// Multiplication in GF(2^8) modulo `x^8 + x^4 + x^3 + x + 1`
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1B } else { 0 };
+        b >>= 1;
+    }
+
+    product
+}
+
+/// Inverse of the permutation `sbox`
+const fn invert(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut inverse = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        inverse[sbox[i] as usize] = i as u8;
+        i += 1;
+    }
+
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// FIPS-197 Appendix B
+    #[test]
+    fn fips_197_cipher_example() {
+        let key = [
+            0x2B, 0x7E, 0x15, 0x16, 0x28, 0xAE, 0xD2, 0xA6, 0xAB, 0xF7, 0x15, 0x88, 0x09, 0xCF,
+            0x4F, 0x3C,
+        ];
+        let plaintext = [
+            0x32, 0x43, 0xF6, 0xA8, 0x88, 0x5A, 0x30, 0x8D, 0x31, 0x31, 0x98, 0xA2, 0xE0, 0x37,
+            0x07, 0x34,
+        ];
+        let ciphertext = [
+            0x39, 0x25, 0x84, 0x1D, 0x02, 0xDC, 0x09, 0xFB, 0xDC, 0x11, 0x85, 0x97, 0x19, 0x6A,
+            0x0B, 0x32,
+        ];
+
+        let aes = SoftAes128::new(&key);
+        assert_eq!(aes.encrypt(&plaintext), ciphertext);
+        assert_eq!(aes.decrypt(&ciphertext), plaintext);
+    }
+
+    /// FIPS-197 Appendix C.1
+    #[test]
+    fn fips_197_aes_128() {
+        let key: [u8; BLOCK_LENGTH] = core::array::from_fn(|i| i as u8);
+        let plaintext: [u8; BLOCK_LENGTH] = core::array::from_fn(|i| (i as u8) * 0x11);
+        let ciphertext = [
+            0x69, 0xC4, 0xE0, 0xD8, 0x6A, 0x7B, 0x04, 0x30, 0xD8, 0xCD, 0xB7, 0x80, 0x70, 0xB4,
+            0xC5, 0x5A,
+        ];
+
+        let aes = SoftAes128::new(&key);
+        assert_eq!(aes.encrypt(&plaintext), ciphertext);
+        assert_eq!(aes.decrypt(&ciphertext), plaintext);
+    }
+
+    #[test]
+    fn decryption_key_round_trip() {
+        // FIPS-197 Appendix A.1
+        let key = [
+            0x2B, 0x7E, 0x15, 0x16, 0x28, 0xAE, 0xD2, 0xA6, 0xAB, 0xF7, 0x15, 0x88, 0x09, 0xCF,
+            0x4F, 0x3C,
+        ];
+        let last_round_key = [
+            0xD0, 0x14, 0xF9, 0xA8, 0xC9, 0xEE, 0x25, 0x89, 0xE1, 0x3F, 0x0C, 0xC8, 0xB6, 0x63,
+            0x0C, 0xA6,
+        ];
+
+        let aes = SoftAes128::new(&key);
+        assert_eq!(aes.decryption_key(), last_round_key);
+        assert_eq!(
+            SoftAes128::from_decryption_key(&aes.decryption_key()).round_keys,
+            aes.round_keys
+        );
+    }
+
+    #[test]
+    fn inverse_sbox() {
+        for byte in 0..=255u8 {
+            assert_eq!(INV_SBOX[SBOX[byte as usize] as usize], byte);
+        }
+    }
+}
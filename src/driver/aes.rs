@@ -0,0 +1,185 @@
+//! # AES-128 Co-processor
+//! *See 9.13 AES encryption co-processor*
+//!
+//! The co-processor encrypts or decrypts one 16 byte block at a time with the key loaded in
+//! `AES_KEY_IN`. Decryption needs the decryption key, derived from the encryption key with
+//! [`SpiritAes::aes_derive_decryption_key`] or on the fly by
+//! [`SpiritAes::aes_key_decrypt_block`].
+//!
+//! The key and data registers hold the most significant byte at the lowest address
+//! (`AES_KEY_IN[15]`, `AES_DATA_IN[15]`), the blocks taken and returned here are in the
+//! usual byte order.
+//!
+//! Every operation polls `IRQ_STATUS` for the `AES_END` event, which like every event is
+//! latched whether or not it is enabled in `IRQ_MASK`. The other events read meanwhile are
+//! kept for the next [`SpiritIrq::irq_take`].
+//!
+//! ## Example
+//! ```no_run
+//! # #![allow(incomplete_features)]
+//! # #![feature(generic_const_exprs)]
+//! # use spirit1_rs::prelude::*;
+//! # fn round_trip(
+//! #     radio: &mut impl SpiritAes,
+//! #     key: [u8; 16],
+//! #     plaintext: [u8; 16],
+//! # ) -> RadioResult<()> {
+//! radio.aes_enable(true)?;
+//! radio.aes_set_key(&key)?;
+//! let ciphertext = radio.aes_encrypt_block(&plaintext)?;
+//!
+//! let decryption_key = radio.aes_derive_decryption_key(&key)?;
+//! radio.aes_set_key(&decryption_key)?;
+//! assert_eq!(radio.aes_decrypt_block(&ciphertext)?, plaintext);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::prelude::*;
+
+use crate::{registers::*, RadioError, RadioResult};
+
+/// AES block and key length
+pub const AES_BLOCK_LENGTH: usize = 16;
+
+/// Time allowed for an operation of the co-processor, which takes a few µs
+pub const AES_END_TIMEOUT_MS: u32 = 10;
+
+pub trait SpiritAes: SpiritIrq {
+    /// Powers the AES engine on or off (`ANA_FUNC_CONF.AES_ON`)
+    fn aes_enable(&mut self, enable: bool) -> RadioResult<()> {
+        let mut conf: AnaFuncConf = self.read_register()?;
+        conf.aes_on = enable;
+        self.write_register(conf)
+    }
+
+    /// Loads `key` in `AES_KEY_IN`
+    fn aes_set_key(&mut self, key: &[u8; AES_BLOCK_LENGTH]) -> RadioResult<()> {
+        self.write_register(AesKeyIn { key: reversed(key) })
+    }
+
+    /// Encrypts `block` with the key loaded in `AES_KEY_IN`
+    fn aes_encrypt_block(
+        &mut self,
+        block: &[u8; AES_BLOCK_LENGTH],
+    ) -> RadioResult<[u8; AES_BLOCK_LENGTH]> {
+        self.aes_execute(SpiritCommand::AES_ENCRYPT, block)
+    }
+
+    /// Decrypts `block` with the decryption key loaded in `AES_KEY_IN`
+    fn aes_decrypt_block(
+        &mut self,
+        block: &[u8; AES_BLOCK_LENGTH],
+    ) -> RadioResult<[u8; AES_BLOCK_LENGTH]> {
+        self.aes_execute(SpiritCommand::AES_DECRYPT, block)
+    }
+
+    /// Decrypts `block` with the encryption key loaded in `AES_KEY_IN`, deriving the
+    /// decryption key first
+    fn aes_key_decrypt_block(
+        &mut self,
+        block: &[u8; AES_BLOCK_LENGTH],
+    ) -> RadioResult<[u8; AES_BLOCK_LENGTH]> {
+        self.aes_execute(SpiritCommand::AES_KEY_DECRYPT, block)
+    }
+
+    /// Derives the decryption key of the encryption `key`. `key` is left in `AES_KEY_IN`
+    fn aes_derive_decryption_key(
+        &mut self,
+        key: &[u8; AES_BLOCK_LENGTH],
+    ) -> RadioResult<[u8; AES_BLOCK_LENGTH]> {
+        self.aes_set_key(key)?;
+        self.write_command(SpiritCommand::AES_KEY)?;
+        self.wait_for_aes_end()?;
+
+        let out: AesDataOut = self.read_register()?;
+
+        Ok(reversed(&out.data))
+    }
+
+    /// Loads `block` in `AES_DATA_IN`, runs `command` and reads the result out of
+    /// `AES_DATA_OUT`
+    fn aes_execute(
+        &mut self,
+        command: SpiritCommand,
+        block: &[u8; AES_BLOCK_LENGTH],
+    ) -> RadioResult<[u8; AES_BLOCK_LENGTH]> {
+        self.write_register(AesDataIn {
+            data: reversed(block),
+        })?;
+        self.write_command(command)?;
+        self.wait_for_aes_end()?;
+
+        let out: AesDataOut = self.read_register()?;
+
+        Ok(reversed(&out.data))
+    }
+
+    /// Blocking wait for the `AES_END` event, polling every millisecond (see
+    /// [`SpiritIrq::irq_poll`]).
+    ///
+    /// Fails with [`RadioError::Timeout`] if the operation does not end within
+    /// [`AES_END_TIMEOUT_MS`]
+    fn wait_for_aes_end(&mut self) -> RadioResult<()> {
+        for _ in 0..=AES_END_TIMEOUT_MS {
+            if !self
+                .irq_poll(InterruptEvent::OthersAesEndOfOperation)?
+                .is_empty()
+            {
+                return Ok(());
+            }
+
+            self.delay_ms(1);
+        }
+
+        Err(RadioError::Timeout(self.read_register()?))
+    }
+}
+
+fn reversed(block: &[u8; AES_BLOCK_LENGTH]) -> [u8; AES_BLOCK_LENGTH] {
+    let mut reversed = *block;
+    reversed.reverse();
+    reversed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    /// FIPS-197 Appendix C.1 through the co-processor registers
+    #[test]
+    fn aes_co_processor() {
+        let key: [u8; AES_BLOCK_LENGTH] = core::array::from_fn(|i| i as u8);
+        let plaintext: [u8; AES_BLOCK_LENGTH] = core::array::from_fn(|i| (i as u8) * 0x11);
+        let ciphertext = [
+            0x69, 0xC4, 0xE0, 0xD8, 0x6A, 0x7B, 0x04, 0x30, 0xD8, 0xCD, 0xB7, 0x80, 0x70, 0xB4,
+            0xC5, 0x5A,
+        ];
+
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        radio.aes_enable(true).unwrap();
+        radio.aes_set_key(&key).unwrap();
+
+        assert_eq!(radio.aes_encrypt_block(&plaintext).unwrap(), ciphertext);
+        assert_eq!(radio.aes_key_decrypt_block(&ciphertext).unwrap(), plaintext);
+
+        let decryption_key = radio.aes_derive_decryption_key(&key).unwrap();
+        radio.aes_set_key(&decryption_key).unwrap();
+        assert_eq!(radio.aes_decrypt_block(&ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn aes_powered_off_times_out() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        radio.aes_set_key(&[0; AES_BLOCK_LENGTH]).unwrap();
+
+        assert!(matches!(
+            radio.aes_encrypt_block(&[0; AES_BLOCK_LENGTH]),
+            Err(RadioError::Timeout(_))
+        ));
+    }
+}
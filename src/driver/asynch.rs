@@ -0,0 +1,857 @@
+//! # Async Driver
+//! Async counterparts of [`Spirit1Driver`], [`SpiritPacketFormats`] and [`SpiritIrq`].
+//!
+//! Rather than busy looping on `IRQ_STATUS` the async driver awaits the nIRQ GPIO, so the
+//! relevant events must be enabled in `IRQ_MASK` and one GPIO must be configured as
+//! `GpioMode::OutputLowPower(DigitalOutputMode::nIRQ)`.
+
+use crate::prelude::*;
+use crate::{registers::*, RadioError, RadioResult};
+
+use super::{
+    calibration_refdiv, compute_rssi_threshold, rx_fifo_length, rx_timeout_ms, synth_registers,
+    tx_load_capacitors, vco_lock_state, xtal_offset_ppm, WMBusFormat, IQC_CORRECTION_WRITES,
+    RX_EVENTS, RX_PACKET_STATUS_ADDRESS, RX_PACKET_STATUS_LENGTH, VCO_GEN_CURR_CALIBRATION,
+    VCO_GEN_CURR_NOMINAL, WA_STROBE_RX_WRITE, WA_STROBE_TX_WRITES,
+};
+
+impl<T> Spirit1DriverAsync for T where T: Spirit1HalAsync {}
+impl<T> SpiritPacketFormatsAsync for T where T: Spirit1HalAsync {}
+impl<T> SpiritIrqAsync for T where T: Spirit1HalAsync {}
+impl<T> Spirit1Async for T where T: Spirit1HalAsync {}
+
+#[allow(
+    async_fn_in_trait,
+    reason = "the driver futures are awaited in place, no `Send` bound is needed"
+)]
+pub trait Spirit1DriverAsync: SpiritIrqAsync {
+    /// In the SPIRIT1 there are two data FIFOs, a TX FIFO for data to be transmitted and an RX.
+    /// FIFO for the received data. The length of both FIFOs is 96 bytes.
+    const MAX_FIFO_LENGTH: usize = 96;
+
+    const LINEAR_FIFO_ADDRESS: u8 = 0xFF;
+
+    /// Wait for `MC_STATE` to be `READY`
+    async fn wait_for_ready(&mut self) -> RadioResult<()> {
+        self.wait_for_state(SpiritState::READY).await
+    }
+
+    /// Wait for `MC_STATE` to enter specified state
+    async fn wait_for_state(&mut self, state: SpiritState) -> RadioResult<()> {
+        trace!("waiting for SpiritState::{}", state);
+        while self.read_register::<McState>().await?.state != state {
+            self.delay_ms(1).await;
+        }
+
+        Ok(())
+    }
+
+    /// See [`Spirit1Driver::wait_for_ready_timeout`]
+    async fn wait_for_ready_timeout(
+        &mut self,
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<()> {
+        self.wait_for_state_timeout(SpiritState::READY, timeout_ms, recovery)
+            .await
+    }
+
+    /// See [`Spirit1Driver::wait_for_state_timeout`]
+    async fn wait_for_state_timeout(
+        &mut self,
+        state: SpiritState,
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<()> {
+        trace!("waiting for SpiritState::{} ({} ms)", state, timeout_ms);
+
+        let mut elapsed_ms = 0;
+        while self.read_register::<McState>().await?.state != state {
+            if elapsed_ms >= timeout_ms {
+                return self.timed_out(recovery).await;
+            }
+
+            self.delay_ms(1).await;
+            elapsed_ms += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the RSSI threshold from its dBm value
+    async fn set_rssi_threshold(&mut self, dbm: i32) -> RadioResult<()> {
+        self.write_register(RssiTh::new(compute_rssi_threshold(dbm)))
+            .await
+    }
+
+    /// See [`Spirit1Driver::set_rx_timeout`]
+    async fn set_rx_timeout(
+        &mut self,
+        timeout_counter: u8,
+        timeout_prescaler: u8,
+    ) -> RadioResult<()> {
+        let mut timers: Timers = self.read_register().await?;
+        (timers.rx_timeout_counter, timers.rx_timeout_prescaler) =
+            (timeout_counter, timeout_prescaler);
+
+        self.write_register(timers).await
+    }
+
+    /// See [`Spirit1Driver::set_rx_timeout_ms`]
+    async fn set_rx_timeout_ms(&mut self, ms: f32) -> RadioResult<f32> {
+        let xtal_frequency = self.get_xtal_frequency();
+        if !(ms > 0.0 && ms <= rx_timeout_ms(0xFF, 0xFF, xtal_frequency)) {
+            return Err(RadioError::ParameterError);
+        }
+
+        let (counter, prescaler) = compute_rx_timeout_values(ms, xtal_frequency);
+        self.set_rx_timeout(counter, prescaler).await?;
+
+        Ok(rx_timeout_ms(counter, prescaler, xtal_frequency))
+    }
+
+    /// See [`Spirit1Driver::set_rx_timeout_stop_condition`]
+    async fn set_rx_timeout_stop_condition(
+        &mut self,
+        stop_condition: RxTimeoutStopCondition,
+    ) -> RadioResult<()> {
+        let mut pkt_opts: PcktFltOptions = self.read_register().await?;
+        let mut protocol: Protocol = self.read_register().await?;
+        stop_condition.apply(&mut pkt_opts, &mut protocol)?;
+
+        self.write_register(pkt_opts).await?;
+        self.write_register(protocol).await
+    }
+
+    /// See [`Spirit1Driver::get_rx_timeout_stop_condition`]
+    async fn get_rx_timeout_stop_condition(&mut self) -> RadioResult<RxTimeoutStopCondition> {
+        let pkt_opts: PcktFltOptions = self.read_register().await?;
+        let protocol: Protocol = self.read_register().await?;
+
+        RxTimeoutStopCondition::from_registers(&pkt_opts, &protocol)
+    }
+
+    /// See [`Spirit1Driver::init`]
+    async fn init(&mut self, opts: RadioInitOpts) -> RadioResult<()> {
+        let config = opts.init_config(self.get_xtal_frequency(), self.get_base_frequency())?;
+
+        // Workaround for V_tune - Set `SEL_TSPLIT` to `1`
+        let mut synth_config = SynthConfig::reset_value();
+        synth_config.sel_tsplit = true;
+        self.write_register(synth_config).await?;
+
+        // Disable the digital, ADC, SMPS reference clock divider if fXO > 24MHz or fXO< 26 MHz
+        let mut rco_test_base: XoRcoTest = self.read_register().await?;
+        rco_test_base.pd_clkdiv = config.pd_clkdiv;
+        self.write_register(rco_test_base).await?;
+
+        self.wait_for_ready().await?;
+
+        // 2nd order DEM algorithm enabling
+        let mut if_offset_dig: IfOffsetDig = self.read_register().await?;
+        if_offset_dig.if_offset_dig &= !0x02;
+        self.write_register(if_offset_dig).await?;
+
+        self.write_register(config.if_offset_ana).await?;
+
+        let mut conf: AnaFuncConf = self.read_register().await?;
+        conf.frequency_select = config.frequency_select;
+        self.write_register(conf).await?;
+
+        self.write_register(config.ch_num).await?;
+        self.write_register(config.fc_offset).await?;
+        self.write_register(config.if_offset_dig).await?;
+
+        self.write_modem_config(config.modem).await?;
+
+        // Enable the freeze option of the AFC on the SYNC word
+        let mut afc2: Afc2 = self.read_register().await?;
+        afc2.afc_freeze_on_sync = true;
+        self.write_register(afc2).await?;
+
+        // Set the IQC correction optimal value
+        for (address, value) in IQC_CORRECTION_WRITES {
+            self.write_raw(address, value).await?;
+        }
+
+        self.set_base_frequency(self.get_base_frequency()).await
+    }
+
+    /// See [`Spirit1Driver::write_modem_config`]
+    async fn write_modem_config(&mut self, config: ModemConfig) -> RadioResult<()> {
+        self.write_register(config.ch_space).await?;
+        self.write_register(config.modulation).await?;
+
+        let mut fdev: FreqDev0 = self.read_register().await?;
+        (fdev.fdev_e, fdev.fdev_m) = config.fdev;
+        self.write_register(fdev).await?;
+
+        self.write_register(config.flt).await
+    }
+
+    /// See [`Spirit1Driver::set_base_frequency`]
+    async fn set_base_frequency(&mut self, frequency: u32) -> RadioResult<()> {
+        self.write_base_frequency(frequency).await?;
+        self.store_base_frequency(frequency);
+        self.management_wa_vco_calibration().await
+    }
+
+    /// See [`Spirit1Driver::get_base_frequency_from_device`]
+    async fn get_base_frequency_from_device(&mut self) -> RadioResult<u32> {
+        let synt: Synt = self.read_register().await?;
+        let synth_config: SynthConfig = self.read_register().await?;
+
+        Ok(synt.frequency(self.get_xtal_frequency(), synth_config.refdiv))
+    }
+
+    /// See [`Spirit1Driver::write_base_frequency`]
+    async fn write_base_frequency(&mut self, frequency: u32) -> RadioResult<()> {
+        let (synth_config, synt) = synth_registers(
+            frequency,
+            self.get_xtal_frequency(),
+            self.read_register().await?,
+        )?;
+
+        self.write_register(synth_config).await?;
+        self.write_register(synt).await
+    }
+
+    /// See [`Spirit1Driver::management_wa_vco_calibration`]
+    async fn management_wa_vco_calibration(&mut self) -> RadioResult<()> {
+        let frequency = self.get_base_frequency_from_device().await?;
+
+        // Enable the reference divider if the XTAL is between 48 and 52 MHz
+        let mut synth_config: SynthConfig = self.read_register().await?;
+        let restore_refdiv = calibration_refdiv(self.get_xtal_frequency(), &synth_config);
+        if restore_refdiv {
+            synth_config.refdiv = true;
+            self.write_register(synth_config).await?;
+            self.write_base_frequency(frequency).await?;
+        }
+
+        // Increase the VCO current
+        let mut vco_config: VcoConfig = self.read_register().await?;
+        vco_config.vco_gen_curr = VCO_GEN_CURR_CALIBRATION;
+        self.write_register(vco_config).await?;
+
+        let mut protocol: Protocol = self.read_register().await?;
+        protocol.vco_calibration = true;
+        self.write_register(protocol).await?;
+
+        let standby = self.read_register::<McState>().await?.state == SpiritState::STANDBY;
+        if standby {
+            self.write_command(SpiritCommand::READY).await?;
+            self.wait_for_vco_lock(SpiritState::READY).await?;
+        }
+
+        self.write_command(SpiritCommand::LOCK_TX).await?;
+        self.wait_for_vco_lock(SpiritState::LOCK).await?;
+        let vco_calibr_tx = self
+            .read_register::<RcoVcoCalibrOut>()
+            .await?
+            .vco_calibr_data;
+
+        self.write_command(SpiritCommand::READY).await?;
+        self.wait_for_vco_lock(SpiritState::READY).await?;
+
+        self.write_command(SpiritCommand::LOCK_RX).await?;
+        self.wait_for_vco_lock(SpiritState::LOCK).await?;
+        let vco_calibr_rx = self
+            .read_register::<RcoVcoCalibrOut>()
+            .await?
+            .vco_calibr_data;
+
+        self.write_command(SpiritCommand::READY).await?;
+        self.wait_for_vco_lock(SpiritState::READY).await?;
+
+        if standby {
+            self.write_command(SpiritCommand::STANDBY).await?;
+        }
+
+        let mut protocol: Protocol = self.read_register().await?;
+        protocol.vco_calibration = false;
+        self.write_register(protocol).await?;
+
+        // Disable the reference divider again
+        if restore_refdiv {
+            let mut synth_config: SynthConfig = self.read_register().await?;
+            synth_config.refdiv = false;
+            self.write_register(synth_config).await?;
+            self.write_base_frequency(frequency).await?;
+        }
+
+        // Restore the VCO current
+        let mut vco_config: VcoConfig = self.read_register().await?;
+        vco_config.vco_gen_curr = VCO_GEN_CURR_NOMINAL;
+        self.write_register(vco_config).await?;
+
+        let mut calibr_in: RcoVcoCalibrIn = self.read_register().await?;
+        calibr_in.vco_calibr_tx = vco_calibr_tx;
+        calibr_in.vco_calibr_rx = vco_calibr_rx;
+        self.write_register(calibr_in).await
+    }
+
+    /// See [`Spirit1Driver::wait_for_vco_lock`]
+    async fn wait_for_vco_lock(&mut self, state: SpiritState) -> RadioResult<()> {
+        for _ in 0..=VCO_LOCK_TIMEOUT_MS {
+            let mut mc_state = [0; McState::LENGTH];
+            self.read_raw(McState::ADDRESS, McState::LENGTH, &mut mc_state)
+                .await?;
+
+            if vco_lock_state(&mc_state)? == state {
+                return Ok(());
+            }
+
+            self.delay_ms(1).await;
+        }
+
+        error!("VCO calibration: SpiritState::{} not reached", state);
+        Err(RadioError::VcoCalibration)
+    }
+
+    /// See [`Spirit1Driver::get_channel`]
+    async fn get_channel(&mut self) -> RadioResult<u8> {
+        Ok(self.read_register::<ChNum>().await?.ch_num)
+    }
+
+    /// See [`Spirit1Driver::read_radio_config`]
+    async fn read_radio_config(&mut self) -> RadioResult<RadioInitOpts> {
+        let xtal_frequency = self.get_xtal_frequency();
+        let pd_clkdiv = self.read_register::<XoRcoTest>().await?.pd_clkdiv;
+
+        let base_frequency = self.get_base_frequency_from_device().await?;
+        let offset = self
+            .read_register::<FcOffset>()
+            .await?
+            .offset_hz(xtal_frequency);
+
+        let modulation: Modulation = self.read_register().await?;
+        let fdev: FreqDev0 = self.read_register().await?;
+        let flt: ChFlt = self.read_register().await?;
+
+        Ok(RadioInitOpts {
+            xtal_offset_ppm: xtal_offset_ppm(offset, base_frequency),
+            channel_space: self
+                .read_register::<ChSpace>()
+                .await?
+                .spacing_hz(xtal_frequency),
+            channel_number: self.get_channel().await?,
+            modulation_select: modulation.mod_type.clone(),
+            data_rate: modulation.data_rate_hz(xtal_frequency, pd_clkdiv),
+            frequency_deviation: fdev.deviation_hz(xtal_frequency),
+            bandwidth: flt.bandwidth_hz(xtal_frequency, pd_clkdiv),
+        })
+    }
+
+    /// Transmit `buf`, returning once `TX_DATA_SENT` is raised.
+    ///
+    /// Requires [`InterruptEvent::TxDataSent`] to be enabled in `IRQ_MASK`. The packet must
+    /// fit in the TX FIFO, [`RadioError::PacketTooLong`] is returned otherwise
+    async fn tx(&mut self, buf: &[u8]) -> RadioResult<usize> {
+        let tx_len = self.start_tx(buf).await?;
+
+        loop {
+            self.wait_for_irq().await?;
+
+            let irq_status = self.irq_poll(InterruptEvent::TxDataSent).await?;
+            if irq_status.contains(InterruptEvent::TxDataSent) {
+                break;
+            }
+        }
+
+        Ok(tx_len)
+    }
+
+    /// [`Spirit1DriverAsync::tx`] failing with [`RadioError::Timeout`] if `TX_DATA_SENT` is
+    /// not raised within `timeout_ms`. `recovery` is applied before returning the error.
+    ///
+    /// `IRQ_STATUS` is polled every millisecond instead of awaiting the nIRQ line, so that the
+    /// wait is bounded without a timer future
+    async fn tx_timeout(
+        &mut self,
+        buf: &[u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<usize> {
+        let tx_len = self.start_tx(buf).await?;
+
+        let mut elapsed_ms = 0;
+        loop {
+            let irq_status = self.irq_poll(InterruptEvent::TxDataSent).await?;
+            if irq_status.contains(InterruptEvent::TxDataSent) {
+                return Ok(tx_len);
+            }
+
+            if elapsed_ms >= timeout_ms {
+                return self.timed_out(recovery).await;
+            }
+
+            self.delay_ms(1).await;
+            elapsed_ms += 1;
+        }
+    }
+
+    /// Load `buf` in the TX FIFO and start the transmission, returning the number of bytes
+    /// to be sent.
+    ///
+    /// Fails with [`RadioError::PacketTooLong`] if `buf` does not fit in the TX FIFO
+    async fn start_tx(&mut self, buf: &[u8]) -> RadioResult<usize> {
+        if buf.len() > Self::MAX_FIFO_LENGTH {
+            return Err(RadioError::PacketTooLong);
+        }
+
+        self.write_command(SpiritCommand::FLUSH_TX_FIFO).await?;
+
+        self.write_raw(Self::LINEAR_FIFO_ADDRESS, buf).await?;
+        self.write_register(PcktLen::new(buf.len() as u16)).await?;
+
+        self.strobe_tx().await?;
+
+        Ok(buf.len())
+    }
+
+    /// Receive a packet into `buffer`, re-entering RX whenever the packet is discarded or
+    /// the RX timeout expires.
+    ///
+    /// Requires [`InterruptEvent::RxDataReady`], [`InterruptEvent::RxDataDiscarded`] and
+    /// [`InterruptEvent::TimerRxTimeout`] to be enabled in `IRQ_MASK`. Fails with
+    /// [`RadioError::BufferTooSmall`] (flushing the RX FIFO) if the packet does not fit in
+    /// `buffer`
+    async fn rx<'a>(&mut self, buffer: &'a mut [u8]) -> RadioResult<RxPacket<'a>> {
+        self.strobe_rx().await?;
+
+        loop {
+            self.wait_for_irq().await?;
+
+            let irq_status = self.irq_poll(RX_EVENTS).await?;
+            if irq_status.contains(InterruptEvent::RxDataReady) {
+                break;
+            }
+
+            if irq_status.contains(InterruptEvent::RxDataDiscarded)
+                || irq_status.contains(InterruptEvent::TimerRxTimeout)
+            {
+                self.strobe_rx().await?;
+            }
+        }
+
+        self.read_rx_packet(buffer).await
+    }
+
+    /// [`Spirit1DriverAsync::rx`] failing with [`RadioError::Timeout`] if no packet is
+    /// received within `timeout_ms`. `recovery` is applied before returning the error.
+    ///
+    /// As [`Spirit1DriverAsync::tx_timeout`], `IRQ_STATUS` is polled every millisecond
+    async fn rx_timeout<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<RxPacket<'a>> {
+        self.strobe_rx().await?;
+
+        let mut elapsed_ms = 0;
+        loop {
+            let irq_status = self.irq_poll(RX_EVENTS).await?;
+            if irq_status.contains(InterruptEvent::RxDataReady) {
+                return self.read_rx_packet(buffer).await;
+            }
+
+            if elapsed_ms >= timeout_ms {
+                return self.timed_out(recovery).await;
+            }
+
+            if irq_status.contains(InterruptEvent::RxDataDiscarded)
+                || irq_status.contains(InterruptEvent::TimerRxTimeout)
+            {
+                self.strobe_rx().await?;
+            }
+
+            self.delay_ms(1).await;
+            elapsed_ms += 1;
+        }
+    }
+
+    /// See [`Spirit1Driver::read_rx_packet`]
+    async fn read_rx_packet<'a>(&mut self, buffer: &'a mut [u8]) -> RadioResult<RxPacket<'a>> {
+        let mut status = [0; RX_PACKET_STATUS_LENGTH];
+        self.read_raw(
+            RX_PACKET_STATUS_ADDRESS,
+            RX_PACKET_STATUS_LENGTH,
+            &mut status,
+        )
+        .await?;
+
+        let len = self.read_rx_fifo(buffer).await?;
+
+        RxPacket::from_status(&buffer[..len], &status, self.get_xtal_frequency())
+    }
+
+    /// See [`Spirit1Driver::read_rx_fifo`]
+    async fn read_rx_fifo(&mut self, buffer: &mut [u8]) -> RadioResult<usize> {
+        let Some(rx_fifo_len) = rx_fifo_length(self.read_register().await?, buffer) else {
+            self.write_command(SpiritCommand::FLUSH_RX_FIFO).await?;
+            return Err(RadioError::BufferTooSmall);
+        };
+
+        self.read_raw(Self::LINEAR_FIFO_ADDRESS, rx_fifo_len, buffer)
+            .await?;
+
+        Ok(rx_fifo_len)
+    }
+
+    /// `SpiritManagementWaCmdStrobeTx` followed by the `TX` command
+    async fn strobe_tx(&mut self) -> RadioResult<()> {
+        let band = self.read_register::<Synt>().await?.band_select;
+
+        let mut pa_power: PaPower = self.read_register().await?;
+        pa_power.additional_load_capacitors = tx_load_capacitors(band);
+        self.write_register(pa_power).await?;
+
+        for (address, value) in WA_STROBE_TX_WRITES {
+            self.write_raw(address, &[value]).await?;
+        }
+
+        self.write_command(SpiritCommand::TX).await?;
+
+        Ok(())
+    }
+
+    /// `SpiritManagementWaCmdStrobeRx` followed by the `RX` command
+    async fn strobe_rx(&mut self) -> RadioResult<()> {
+        let (address, value) = WA_STROBE_RX_WRITE;
+        self.write_raw(address, &[value]).await?;
+
+        let mut pa_power: PaPower = self.read_register().await?;
+        pa_power.additional_load_capacitors = AdditionalLoadCapacitors::Cap0;
+        self.write_register(pa_power).await?;
+
+        self.write_command(SpiritCommand::RX).await?;
+
+        Ok(())
+    }
+
+    /// See [`Spirit1Driver::timed_out`]
+    async fn timed_out<T>(&mut self, recovery: TimeoutRecovery) -> RadioResult<T> {
+        let mc_state: McState = self.read_register().await?;
+        debug!("timeout in SpiritState::{}", mc_state.state);
+
+        if let Some(command) = recovery.command() {
+            self.write_command(command).await?;
+        }
+
+        Err(RadioError::Timeout(mc_state))
+    }
+}
+
+#[allow(
+    async_fn_in_trait,
+    reason = "the driver futures are awaited in place, no `Send` bound is needed"
+)]
+pub trait SpiritPacketFormatsAsync: Spirit1DriverAsync {
+    async fn configure_packet_protocol(
+        &mut self,
+        configuration: PacketConfiguration,
+    ) -> RadioResult<()> {
+        match configuration {
+            PacketConfiguration::Basic(config) => self.configure_basic(config).await,
+            PacketConfiguration::STack(config) => self.configure_stack(config).await,
+            PacketConfiguration::WMBus(config) => self.configure_wmbus(config).await,
+        }
+    }
+
+    /// See [`SpiritPacketFormats::configure_basic_filter`]
+    async fn configure_basic_filter(&mut self, opts: BasicAddressOpts) -> RadioResult<()> {
+        let mut flt_opts: PcktFltOptions = self.read_register().await?;
+        let mut goals: PcktFltGoals = self.read_register().await?;
+        opts.apply(&mut flt_opts, &mut goals);
+
+        self.write_register(flt_opts).await?;
+        self.write_register(goals).await
+    }
+
+    /// See [`SpiritPacketFormats::configure_basic`]
+    async fn configure_basic(&mut self, opts: BasicProtocolOpts) -> RadioResult<()> {
+        let registers = opts.registers()?;
+
+        let mut protocol: Protocol = self.read_register().await?;
+        let mut flt_opts: PcktFltOptions = self.read_register().await?;
+        registers.apply(&mut protocol, &mut flt_opts);
+
+        self.write_register(protocol).await?;
+        self.write_register(flt_opts).await?;
+
+        self.write_register(registers.pkt_ctrl_4).await?;
+        self.write_register(registers.pkt_ctrl_3).await?;
+        self.write_register(registers.pkt_ctrl_2).await?;
+        self.write_register(registers.pkt_ctrl_1).await?;
+
+        let (sync_1, sync_2, sync_3, sync_4) = registers.sync;
+        self.write_register(sync_1).await?;
+        self.write_register(sync_2).await?;
+        self.write_register(sync_3).await?;
+        self.write_register(sync_4).await
+    }
+
+    /// See [`SpiritPacketFormats::configure_stack_filter`]
+    async fn configure_stack_filter(&mut self, opts: STackAddressOpts) -> RadioResult<()> {
+        let mut flt_opts: PcktFltOptions = self.read_register().await?;
+        let mut goals: PcktFltGoals = self.read_register().await?;
+        opts.apply(&mut flt_opts, &mut goals);
+
+        self.write_register(flt_opts).await?;
+        self.write_register(goals).await
+    }
+
+    /// See [`SpiritPacketFormats::configure_stack`]
+    async fn configure_stack(&mut self, opts: STackProtocolOpts) -> RadioResult<()> {
+        let registers = opts.registers()?;
+
+        let mut protocol: Protocol = self.read_register().await?;
+        let mut flt_opts: PcktFltOptions = self.read_register().await?;
+        registers.apply(&mut protocol, &mut flt_opts);
+
+        self.write_register(protocol).await?;
+        self.write_register(flt_opts).await?;
+
+        self.write_register(registers.pkt_ctrl_4).await?;
+        self.write_register(registers.pkt_ctrl_3).await?;
+        self.write_register(registers.pkt_ctrl_2).await?;
+        self.write_register(registers.pkt_ctrl_1).await?;
+
+        let (sync_1, sync_2, sync_3, sync_4) = registers.sync;
+        self.write_register(sync_1).await?;
+        self.write_register(sync_2).await?;
+        self.write_register(sync_3).await?;
+        self.write_register(sync_4).await
+    }
+
+    /// See [`SpiritPacketFormats::configure_wmbus`]
+    async fn configure_wmbus(&mut self, opts: WMBusProtocolOpts) -> RadioResult<()> {
+        let preset = WMBusPreset::from_submode(&opts.submode);
+        let pd_clkdiv = self.read_register::<XoRcoTest>().await?.pd_clkdiv;
+        let current = self.read_radio_config().await?;
+        let modem = preset.modem_config(&current, self.get_xtal_frequency(), pd_clkdiv)?;
+
+        self.write_modem_config(modem).await?;
+        self.set_base_frequency(preset.frequency).await?;
+
+        let mut format = WMBusFormat {
+            pkt_ctrl_3: self.read_register().await?,
+            pkt_ctrl_2: self.read_register().await?,
+            pkt_ctrl_1: self.read_register().await?,
+            flt_opts: self.read_register().await?,
+            ant_select: self.read_register().await?,
+        };
+        format.apply();
+
+        self.write_register(format.pkt_ctrl_3).await?;
+        self.write_register(format.pkt_ctrl_2).await?;
+        self.write_register(format.pkt_ctrl_1).await?;
+        self.write_register(format.flt_opts).await?;
+        self.write_register(format.ant_select).await?;
+
+        let (preamble, postamble, ctrl) = opts.registers();
+        self.write_register(preamble).await?;
+        self.write_register(postamble).await?;
+        self.write_register(ctrl).await
+    }
+}
+
+#[allow(
+    async_fn_in_trait,
+    reason = "the driver futures are awaited in place, no `Send` bound is needed"
+)]
+pub trait SpiritIrqAsync: Spirit1HalAsync {
+    /// De-initialize & disable all IRQs
+    async fn irq_silence(&mut self) -> RadioResult<()> {
+        self.write_register(IrqMask::new(0)).await
+    }
+
+    async fn irq_clear(&mut self) -> RadioResult<()> {
+        self.irq_take().await?;
+        Ok(())
+    }
+
+    /// See [`SpiritIrq::irq_take`]
+    async fn irq_take(&mut self) -> RadioResult<IrqSet> {
+        // Register is a Read-Reset type
+        let status: IrqStatus = self.read_register().await?;
+        let latched = self.irq_latched().map(core::mem::take).unwrap_or_default();
+
+        Ok(latched | status.events())
+    }
+
+    /// See [`SpiritIrq::irq_poll`]
+    async fn irq_poll(&mut self, events: impl Into<IrqSet>) -> RadioResult<IrqSet> {
+        // Register is a Read-Reset type
+        let status = self.read_register::<IrqStatus>().await?.events();
+        let events = events.into();
+        if let Some(latched) = self.irq_latched() {
+            *latched |= status.difference(events);
+        }
+
+        Ok(status.intersection(events))
+    }
+
+    /// See [`SpiritIrq::irq_get_mask`]
+    async fn irq_get_mask(&mut self) -> RadioResult<IrqSet> {
+        let mask: IrqMask = self.read_register().await?;
+        Ok(mask.events())
+    }
+
+    /// See [`SpiritIrq::irq_set_mask`]
+    async fn irq_set_mask(&mut self, events: IrqSet) -> RadioResult<()> {
+        self.write_register(IrqMask::from(events)).await
+    }
+
+    /// See [`SpiritIrq::irq_enable`]
+    async fn irq_enable(&mut self, events: impl Into<IrqSet>) -> RadioResult<()> {
+        let mask = self.irq_get_mask().await? | events.into();
+        self.irq_set_mask(mask).await
+    }
+
+    /// See [`SpiritIrq::irq_disable`]
+    async fn irq_disable(&mut self, events: impl Into<IrqSet>) -> RadioResult<()> {
+        let mask = self.irq_get_mask().await?.difference(events.into());
+        self.irq_set_mask(mask).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::mock::{MockSpirit1, MockSpirit1Async};
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    /// Runs `future` to completion, the mock futures never being pending
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    fn basic_opts() -> BasicProtocolOpts {
+        BasicProtocolOpts {
+            preamble_length: PreambleLength::Bytes04,
+            sync_length: PacketSyncLength::Bytes04,
+            sync_words: (0x1A, 0x2B, 0x3C, 0x4D),
+            fix_var_length: PacketLengthMode::Variable,
+            packet_length_width: 7,
+            crc_mode: CrcMode::Crc0x1021,
+            control_length: PacketControlLength::Bytes0,
+            address_field: false,
+            fec: false,
+            data_whitening: true,
+        }
+    }
+
+    fn configured() -> MockSpirit1Async<'static> {
+        let mut radio = MockSpirit1Async(MockSpirit1::new(XTAL, BASE));
+        block_on(radio.init(RadioInitOpts::default())).unwrap();
+        block_on(radio.configure_basic(basic_opts())).unwrap();
+
+        radio
+    }
+
+    #[test]
+    fn programs_the_same_registers_as_the_blocking_driver() {
+        let radio = configured();
+
+        let mut blocking = MockSpirit1::new(XTAL, BASE);
+        Spirit1Driver::init(&mut blocking, RadioInitOpts::default()).unwrap();
+        SpiritPacketFormats::configure_basic(&mut blocking, basic_opts()).unwrap();
+
+        for address in 0..=0xFE {
+            assert_eq!(
+                radio.0.register(address),
+                blocking.register(address),
+                "register {:#04x}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn configure_wmbus_matches_the_blocking_driver() {
+        let opts = || WMBusProtocolOpts {
+            submode: MBusSubMode::T1T2MeterToOther,
+            preamble_length: 4,
+            postamble_length: 2,
+        };
+
+        let mut radio = configured();
+        block_on(radio.configure_wmbus(opts())).unwrap();
+
+        let mut blocking = MockSpirit1::new(XTAL, BASE);
+        Spirit1Driver::init(&mut blocking, RadioInitOpts::default()).unwrap();
+        SpiritPacketFormats::configure_basic(&mut blocking, basic_opts()).unwrap();
+        SpiritPacketFormats::configure_wmbus(&mut blocking, opts()).unwrap();
+
+        for address in 0..=0xFE {
+            assert_eq!(
+                radio.0.register(address),
+                blocking.register(address),
+                "register {:#04x}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn tx_rejects_packets_longer_than_the_fifo() {
+        let mut radio = configured();
+
+        assert!(matches!(
+            block_on(radio.tx(&[0x55; 97])),
+            Err(RadioError::PacketTooLong)
+        ));
+        assert!(radio.0.take_transmitted().is_none());
+
+        assert_eq!(block_on(radio.tx(&[0x55; 96])).unwrap(), 96);
+        assert_eq!(radio.0.take_transmitted().unwrap().as_slice(), &[0x55; 96]);
+    }
+
+    #[test]
+    fn rx_timeout_times_out() {
+        let mut radio = configured();
+        let mut buffer = [0; 16];
+
+        let result = block_on(radio.rx_timeout(&mut buffer, 5, TimeoutRecovery::Abort));
+
+        assert!(matches!(result, Err(RadioError::Timeout(_))));
+        assert_eq!(radio.0.state(), SpiritState::READY);
+        assert_eq!(radio.0.elapsed_ms(), 5);
+    }
+
+    #[test]
+    fn rx_timeout_reads_the_packet() {
+        let mut radio = configured();
+        radio.0.inject_rx_packet(b"ping").unwrap();
+
+        let mut buffer = [0; 16];
+        let packet = block_on(radio.rx_timeout(&mut buffer, 5, TimeoutRecovery::None)).unwrap();
+        assert_eq!(packet.payload, b"ping");
+    }
+
+    #[test]
+    fn read_radio_config_matches_the_blocking_driver() {
+        let mut radio = configured();
+        let config = block_on(radio.read_radio_config()).unwrap();
+        let blocking = Spirit1Driver::read_radio_config(&mut radio.0).unwrap();
+
+        assert_eq!(config.data_rate, blocking.data_rate);
+        assert_eq!(config.bandwidth, blocking.bandwidth);
+        assert_eq!(config.channel_number, blocking.channel_number);
+    }
+}
@@ -0,0 +1,303 @@
+//! # Automatic Acknowledgement
+//! *See 9.7.6 Automatic acknowledgement, retransmission and timeout*
+//!
+//! With the STack packet format the SPIRIT1 can acknowledge the received packets and
+//! retransmit the packets which are not acknowledged, without any action from the MCU:
+//! - The transmitter sends its packet with `NO_ACK` cleared, then waits for the ACK in RX
+//!   for the duration of the RX timeout. The packet is sent again, up to `NMAX_RETX`
+//!   times, until an ACK with the same sequence number is received.
+//! - The receiver answers every packet requesting an ACK. With piggybacking, the content
+//!   of its TX FIFO is sent as the ACK payload.
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::prelude::*;
+//! # fn send(radio: &mut impl SpiritAutoAck) -> RadioResult<()> {
+//! radio.configure_auto_ack(AutoAckOpts {
+//!     max_retransmissions: 3,
+//!     ..Default::default()
+//! })?;
+//!
+//! let report = radio.send_reliable(0x42, b"Hello", 500, TimeoutRecovery::Abort)?;
+//! info!("delivered after {} retransmissions", report.retransmissions);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::prelude::*;
+
+use crate::{registers::*, RadioError, RadioResult};
+
+pub trait SpiritAutoAck: SpiritPacketFormats {
+    /// Configures the automatic acknowledgement and retransmission engine. The packet
+    /// format must be [`PacketConfiguration::STack`]
+    fn configure_auto_ack(&mut self, opts: AutoAckOpts) -> RadioResult<()> {
+        if opts.max_retransmissions > 15 {
+            return Err(RadioError::ParameterError);
+        }
+
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.auto_ack = opts.auto_ack;
+        protocol.piggybacking = opts.piggybacking;
+        protocol.nmax_retx = opts.max_retransmissions;
+        self.write_register(protocol)
+    }
+
+    /// Transmit `payload` to `destination` requesting an ACK, returning once the ACK is
+    /// received.
+    ///
+    /// Fails with [`RadioError::MaxRetransmissionsReached`] if the packet is not
+    /// acknowledged after `NMAX_RETX` retransmissions, and with [`RadioError::Timeout`] if
+    /// neither the ACK nor `MAX_RE_TX_REACH` come within `timeout_ms`, `recovery` being
+    /// applied before returning the error. If the ACK carried a payload
+    /// ([`AckReport::piggybacked`]) it is left in the RX FIFO, to be read with
+    /// [`Spirit1Driver::read_rx_packet`].
+    ///
+    /// The destination address set with [`SpiritPacketFormats::set_destination_address`] is
+    /// restored before returning, whatever the outcome.
+    ///
+    /// Returns [`RadioError::ParameterError`] if the packet format is not
+    /// [`PacketConfiguration::STack`].
+    ///
+    /// Requires [`InterruptEvent::TxDataSent`], [`InterruptEvent::MaxReTxReached`] and
+    /// [`InterruptEvent::RxDataReady`] to be enabled in `IRQ_MASK`
+    fn send_reliable(
+        &mut self,
+        destination: u8,
+        payload: &[u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<AckReport> {
+        if payload.len() > Self::MAX_FIFO_LENGTH {
+            return Err(RadioError::PacketTooLong);
+        }
+
+        // Only STack packets carry the sequence number and `NO_ACK` field
+        let packet_format = self.read_register::<PcktCtrl3>()?.pckt_frmt;
+        if !matches!(packet_format, PacketFormat::STack) {
+            error!(
+                "send_reliable requires STack packets, not {}",
+                packet_format
+            );
+            return Err(RadioError::ParameterError);
+        }
+
+        let previous = self.read_register::<PcktFltGoals>()?.rx_source_addr;
+        self.set_destination_address(destination)?;
+
+        let mut send = || {
+            self.set_no_ack(false)?;
+            self.start_tx(payload)?;
+
+            // `TX_DATA_SENT` is only raised once the ACK is received
+            let mut piggybacked = false;
+            let mut elapsed_ms = 0;
+            loop {
+                let irq_status = self.irq_poll([
+                    InterruptEvent::RxDataReady,
+                    InterruptEvent::MaxReTxReached,
+                    InterruptEvent::TxDataSent,
+                ])?;
+                piggybacked |= irq_status.contains(InterruptEvent::RxDataReady);
+
+                if irq_status.contains(InterruptEvent::MaxReTxReached) {
+                    return Err(RadioError::MaxRetransmissionsReached);
+                }
+
+                if irq_status.contains(InterruptEvent::TxDataSent) {
+                    break;
+                }
+
+                if elapsed_ms >= timeout_ms {
+                    return self.timed_out(recovery);
+                }
+
+                self.delay_ms(1);
+                elapsed_ms += 1;
+            }
+
+            let info: TxPacketInfo = self.read_register()?;
+
+            Ok(AckReport {
+                retransmissions: info.n_retx,
+                sequence_number: info.tx_seq_num,
+                piggybacked,
+            })
+        };
+
+        let report = send();
+        self.set_destination_address(previous)?;
+
+        report
+    }
+}
+
+/// Automatic acknowledgement options
+#[derive(Clone, Debug, defmt::Format)]
+pub struct AutoAckOpts {
+    /// Acknowledge the received packets requesting an ACK
+    pub auto_ack: bool,
+    /// Send the content of the TX FIFO with the ACK
+    pub piggybacking: bool,
+    /// Maximum number of retransmissions of a packet which is not acknowledged (`0..=15`).
+    /// With `0` the packet is sent once and never retransmitted
+    pub max_retransmissions: u8,
+}
+
+impl Default for AutoAckOpts {
+    fn default() -> Self {
+        Self {
+            auto_ack: true,
+            piggybacking: false,
+            max_retransmissions: 3,
+        }
+    }
+}
+
+/// Outcome of [`SpiritAutoAck::send_reliable`]
+#[derive(Clone, Copy, Debug, defmt::Format)]
+pub struct AckReport {
+    /// Number of retransmissions done before the ACK was received (`N_RETX`), `0` if the
+    /// first transmission was acknowledged
+    pub retransmissions: u8,
+    /// Sequence number of the acknowledged packet
+    pub sequence_number: u8,
+    /// The ACK carried a payload, available in the RX FIFO
+    pub piggybacked: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Air, LinkProfile, MockSpirit1};
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    /// STack node with address `address`, filtering on its own address
+    fn node<'a>(air: &'a Air, address: u8, opts: AutoAckOpts) -> MockSpirit1<'a> {
+        let mut radio = MockSpirit1::with_air(XTAL, BASE, air).unwrap();
+        radio.init(RadioInitOpts::default()).unwrap();
+        radio
+            .configure_stack(STackProtocolOpts {
+                preamble_length: PreambleLength::Bytes04,
+                sync_length: PacketSyncLength::Bytes04,
+                sync_words: (0x1A, 0x2B, 0x3C, 0x4D),
+                fix_var_length: PacketLengthMode::Variable,
+                packet_length_width: 7,
+                crc_mode: CrcMode::Crc0x1021,
+                control_length: PacketControlLength::Bytes0,
+                fec: false,
+                data_whitening: true,
+            })
+            .unwrap();
+        radio
+            .configure_stack_filter(STackAddressOpts {
+                filter_on_my_address: true,
+                my_address: address,
+                filter_on_multicast_address: false,
+                multicast_address: 0,
+                filter_on_broadcast_address: false,
+                broadcast_address: 0xFF,
+                filter_on_source_address: false,
+                source_reference_address: 0x7F,
+                source_mask: 0,
+            })
+            .unwrap();
+        radio.configure_auto_ack(opts).unwrap();
+
+        radio
+    }
+
+    fn destination(radio: &mut MockSpirit1) -> u8 {
+        radio
+            .read_register::<PcktFltGoals>()
+            .unwrap()
+            .rx_source_addr
+    }
+
+    #[test]
+    fn send_reliable_returns_the_ack_report() {
+        let air = Air::new(LinkProfile::default());
+        let mut tx = node(&air, 0x11, AutoAckOpts::default());
+        let mut rx = node(&air, 0x22, AutoAckOpts::default());
+
+        rx.strobe_rx().unwrap();
+        let report = tx
+            .send_reliable(0x22, b"Hello", 100, TimeoutRecovery::Abort)
+            .unwrap();
+        assert_eq!(report.retransmissions, 0);
+        assert_eq!(report.sequence_number, 0);
+        assert!(!report.piggybacked);
+
+        let mut buffer = [0; 8];
+        assert_eq!(rx.read_rx_packet(&mut buffer).unwrap().payload, b"Hello");
+
+        // The destination address is restored
+        assert_eq!(destination(&mut tx), 0x7F);
+    }
+
+    #[test]
+    fn send_reliable_fails_after_nmax_retx() {
+        let air = Air::new(LinkProfile::default());
+        let opts = AutoAckOpts {
+            max_retransmissions: 3,
+            ..Default::default()
+        };
+        let mut tx = node(&air, 0x11, opts);
+        let mut rx = node(&air, 0x22, AutoAckOpts::default());
+
+        // Every ACK is lost
+        let lossy = LinkProfile {
+            loss_percent: 100,
+            ..Default::default()
+        };
+        air.set_link(rx.node_id().unwrap(), tx.node_id().unwrap(), lossy);
+
+        rx.strobe_rx().unwrap();
+        assert!(matches!(
+            tx.send_reliable(0x22, b"Hello", 100, TimeoutRecovery::Abort),
+            Err(RadioError::MaxRetransmissionsReached)
+        ));
+        assert_eq!(tx.read_register::<TxPacketInfo>().unwrap().n_retx, 3);
+        assert_eq!(destination(&mut tx), 0x7F);
+    }
+
+    #[test]
+    fn send_reliable_reports_the_piggybacked_payload() {
+        let air = Air::new(LinkProfile::default());
+        let mut tx = node(&air, 0x11, AutoAckOpts::default());
+        let mut rx = node(
+            &air,
+            0x22,
+            AutoAckOpts {
+                piggybacking: true,
+                ..Default::default()
+            },
+        );
+
+        // The receiver answers with the content of its TX FIFO
+        rx.write_raw(<MockSpirit1 as Spirit1Driver>::LINEAR_FIFO_ADDRESS, b"pong")
+            .unwrap();
+        rx.strobe_rx().unwrap();
+
+        let report = tx
+            .send_reliable(0x22, b"ping", 100, TimeoutRecovery::Abort)
+            .unwrap();
+        assert!(report.piggybacked);
+
+        let mut buffer = [0; 8];
+        assert_eq!(tx.read_rx_packet(&mut buffer).unwrap().payload, b"pong");
+        assert_eq!(rx.read_rx_packet(&mut buffer).unwrap().payload, b"ping");
+    }
+
+    #[test]
+    fn send_reliable_requires_stack_packets() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+
+        assert!(matches!(
+            radio.send_reliable(0x22, b"Hello", 100, TimeoutRecovery::Abort),
+            Err(RadioError::ParameterError)
+        ));
+    }
+}
@@ -0,0 +1,322 @@
+//! # CSMA/CA
+//! *See 9.9 CSMA/CA engine*
+//!
+//! Before transmitting, the SPIRIT1 senses the channel during `T_listen`, sampling the carrier
+//! sense every `T_cca`. If the channel is busy it backs off for a random number of back-off
+//! units (BU) and senses again, up to `NBACKOFF_MAX` times before raising `MAX_BO_CCA_REACH`.
+//! In persistent mode the channel is sensed continuously instead of backing off.
+//!
+//! The carrier sense uses the RSSI threshold, see [`Spirit1Driver::set_rssi_threshold`].
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::prelude::*;
+//! # fn send(radio: &mut impl SpiritCsma) -> RadioResult<()> {
+//! radio.configure_csma(CsmaOpts::default())?;
+//!
+//! match radio.tx_with_csma(b"Hello", 100, TimeoutRecovery::Abort) {
+//!     Err(RadioError::MaxBoCcaReached) => { /* channel busy, retry later */ }
+//!     result => { result?; }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::prelude::*;
+
+use crate::{registers::*, RadioError, RadioResult};
+
+/// `T_cca` multipliers of `T_bit`, see [`CcaPeriod`]
+const CCA_PERIODS: [(u32, CcaPeriod); 4] = [
+    (64, CcaPeriod::T64),
+    (128, CcaPeriod::T128),
+    (256, CcaPeriod::T256),
+    (512, CcaPeriod::T512),
+];
+
+pub trait SpiritCsma: Spirit1Driver {
+    /// Configures the CSMA/CA engine. The durations are converted with the data rate
+    /// programmed in the device, [`Spirit1Driver::init`] must be called first.
+    ///
+    /// Returns [`RadioError::ParameterError`] if a duration cannot be programmed
+    fn configure_csma(&mut self, opts: CsmaOpts) -> RadioResult<()> {
+        let xtal_frequency = self.get_xtal_frequency();
+        let pd_clkdiv = self.read_register::<XoRcoTest>()?.pd_clkdiv;
+        let data_rate = self
+            .read_register::<Modulation>()?
+            .data_rate_hz(xtal_frequency, pd_clkdiv) as u64;
+
+        // T_cca, the multiple of T_bit closest to the requested period
+        let (multiplier, cca_period) = CCA_PERIODS
+            .into_iter()
+            .min_by_key(|(multiplier, _)| {
+                (*multiplier as u64 * 1_000_000 / data_rate).abs_diff(opts.cca_period_us as u64)
+            })
+            .ok_or(RadioError::ParameterError)?;
+        let cca_period_us = (multiplier as u64 * 1_000_000 / data_rate).max(1);
+
+        // T_listen = CCA_LENGTH * T_cca
+        let cca_length = (opts.listen_time_us as u64 + cca_period_us / 2) / cca_period_us;
+        if !(1..=15).contains(&cca_length) {
+            return Err(RadioError::ParameterError);
+        }
+
+        // BU = BU_PRESCALER * 32 / f_dig
+        let f_dig = (if pd_clkdiv {
+            xtal_frequency
+        } else {
+            xtal_frequency / 2
+        }) as u64;
+        let bu_prescaler = (opts.backoff_unit_us as u64 * f_dig / 32 + 500_000) / 1_000_000;
+        if !(1..=63).contains(&bu_prescaler) {
+            return Err(RadioError::ParameterError);
+        }
+
+        if opts.max_backoffs > 7 || opts.seed == 0 {
+            return Err(RadioError::ParameterError);
+        }
+
+        let [seed_msb, seed_lsb] = opts.seed.to_be_bytes();
+        self.write_register(CsmaConfig::new(
+            seed_msb,
+            seed_lsb,
+            bu_prescaler as u8,
+            cca_period,
+            cca_length as u8,
+            opts.max_backoffs,
+        ))?;
+
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.csma_pers_on = opts.persistent;
+        protocol.seed_reload = opts.seed_reload;
+        self.write_register(protocol)
+    }
+
+    /// Enables or disables the CSMA/CA engine for the following transmissions
+    fn set_csma(&mut self, enable: bool) -> RadioResult<()> {
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.csma_on = enable;
+        self.write_register(protocol)
+    }
+
+    /// Transmit `buf` after the CSMA/CA channel assessment, returning once `TX_DATA_SENT` is
+    /// raised. The engine is only enabled for this transmission.
+    ///
+    /// Fails with [`RadioError::MaxBoCcaReached`] if the channel stayed busy for
+    /// `NBACKOFF_MAX` back-offs, the radio being back in `READY`. Fails with
+    /// [`RadioError::Timeout`] if neither event is raised within `timeout_ms`, which with
+    /// [`CsmaOpts::persistent`] also bounds the time spent waiting for the channel. `recovery`
+    /// is applied before returning the error.
+    ///
+    /// Requires [`InterruptEvent::TxDataSent`] and [`InterruptEvent::MaxBackoffDuringCCA`] to
+    /// be enabled in `IRQ_MASK`
+    fn tx_with_csma(
+        &mut self,
+        buf: &[u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<usize> {
+        if buf.len() > Self::MAX_FIFO_LENGTH {
+            return Err(RadioError::PacketTooLong);
+        }
+
+        self.set_csma(true)?;
+        let tx_len = match self.start_tx(buf) {
+            Ok(tx_len) => tx_len,
+            Err(error) => {
+                self.set_csma(false)?;
+                return Err(error);
+            }
+        };
+
+        let mut elapsed_ms = 0;
+        let result = loop {
+            let irq_status = match self.irq_poll([
+                InterruptEvent::MaxBackoffDuringCCA,
+                InterruptEvent::TxDataSent,
+            ]) {
+                Ok(irq_status) => irq_status,
+                Err(error) => break Err(error),
+            };
+            if irq_status.contains(InterruptEvent::MaxBackoffDuringCCA) {
+                break Err(RadioError::MaxBoCcaReached);
+            }
+
+            if irq_status.contains(InterruptEvent::TxDataSent) {
+                break Ok(tx_len);
+            }
+
+            if elapsed_ms >= timeout_ms {
+                break self.timed_out(recovery);
+            }
+
+            self.delay_ms(1);
+            elapsed_ms += 1;
+        };
+
+        // The engine must not be left enabled in RX
+        self.set_csma(false)?;
+
+        result
+    }
+}
+
+/// CSMA/CA options
+#[derive(Clone, Debug, defmt::Format)]
+pub struct CsmaOpts {
+    /// Sense the channel continuously instead of backing off when it is busy
+    pub persistent: bool,
+    /// Time between two carrier sense samplings (`T_cca`) in µs, rounded to 64, 128, 256
+    /// or 512 bit periods
+    pub cca_period_us: u32,
+    /// Time the channel must be free before transmitting (`T_listen`) in µs, rounded to
+    /// 1 to 15 `T_cca`
+    pub listen_time_us: u32,
+    /// Maximum number of back-offs (`0..=7`)
+    pub max_backoffs: u8,
+    /// Back-off unit in µs, rounded to 1 to 63 times `32 / f_dig`
+    pub backoff_unit_us: u32,
+    /// Seed of the back-off random generator, must not be `0`
+    pub seed: u16,
+    /// Reload the seed before every CSMA procedure
+    pub seed_reload: bool,
+}
+
+impl Default for CsmaOpts {
+    fn default() -> Self {
+        Self {
+            persistent: false,
+            cca_period_us: 1_700,
+            listen_time_us: 5_000,
+            max_backoffs: 5,
+            backoff_unit_us: 20,
+            seed: 0xFA21,
+            seed_reload: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    fn radio(data_rate: u32) -> MockSpirit1<'static> {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        radio
+            .init(RadioInitOpts {
+                data_rate,
+                ..Default::default()
+            })
+            .unwrap();
+        radio.configure_csma(CsmaOpts::default()).unwrap();
+
+        radio
+    }
+
+    #[test]
+    fn csma_durations_follow_the_data_rate() {
+        let opts = CsmaOpts {
+            cca_period_us: 6_700,
+            listen_time_us: 20_000,
+            ..Default::default()
+        };
+
+        // T_bit of 104 µs: T_cca = 64 * T_bit
+        let mut slow = radio(9_600);
+        slow.configure_csma(opts.clone()).unwrap();
+        let config: CsmaConfig = slow.read_register().unwrap();
+        assert!(matches!(config.cca_period, CcaPeriod::T64));
+        assert_eq!(config.cca_length, 3);
+
+        // T_bit of 26 µs: T_cca = 256 * T_bit
+        let mut fast = radio(38_400);
+        fast.configure_csma(opts).unwrap();
+        let config: CsmaConfig = fast.read_register().unwrap();
+        assert!(matches!(config.cca_period, CcaPeriod::T256));
+        assert_eq!(config.cca_length, 3);
+        assert_eq!(config.nbackoff_max, 5);
+        assert_eq!(config.bu_counter_seed_msb, 0xFA);
+        assert_eq!(config.bu_counter_seed_lsb, 0x21);
+    }
+
+    #[test]
+    fn csma_rejects_durations_out_of_range() {
+        let mut radio = radio(38_400);
+
+        for opts in [
+            CsmaOpts {
+                listen_time_us: 100_000,
+                ..Default::default()
+            },
+            CsmaOpts {
+                backoff_unit_us: 100,
+                ..Default::default()
+            },
+            CsmaOpts {
+                max_backoffs: 8,
+                ..Default::default()
+            },
+            CsmaOpts {
+                seed: 0,
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                radio.configure_csma(opts),
+                Err(RadioError::ParameterError)
+            ));
+        }
+    }
+
+    #[test]
+    fn tx_with_csma_on_a_free_channel() {
+        let mut radio = radio(38_400);
+
+        assert_eq!(
+            radio
+                .tx_with_csma(b"Hello", 100, TimeoutRecovery::Abort)
+                .unwrap(),
+            5
+        );
+        assert_eq!(radio.take_transmitted().unwrap().as_slice(), b"Hello");
+        assert!(!radio.read_register::<Protocol>().unwrap().csma_on);
+    }
+
+    #[test]
+    fn tx_with_csma_gives_up_on_a_busy_channel() {
+        let mut radio = radio(38_400);
+        radio.set_channel_busy(true);
+
+        assert!(matches!(
+            radio.tx_with_csma(b"Hello", 100, TimeoutRecovery::Abort),
+            Err(RadioError::MaxBoCcaReached)
+        ));
+        assert!(radio.take_transmitted().is_none());
+        assert_eq!(radio.state(), SpiritState::READY);
+        assert!(!radio.read_register::<Protocol>().unwrap().csma_on);
+    }
+
+    #[test]
+    fn persistent_csma_waits_for_the_channel() {
+        let mut radio = radio(38_400);
+        radio
+            .configure_csma(CsmaOpts {
+                persistent: true,
+                ..Default::default()
+            })
+            .unwrap();
+        radio.set_channel_busy(true);
+
+        assert!(matches!(
+            radio.tx_with_csma(b"Hello", 10, TimeoutRecovery::Abort),
+            Err(RadioError::Timeout(_))
+        ));
+        assert!(radio.take_transmitted().is_none());
+        assert_eq!(radio.state(), SpiritState::READY);
+        assert!(!radio.read_register::<Protocol>().unwrap().csma_on);
+    }
+}
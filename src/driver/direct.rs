@@ -0,0 +1,342 @@
+//! # Direct Modes
+//! *See 9.7 Data modes*
+//!
+//! In direct mode the packet handler is bypassed: no preamble, sync word, CRC or whitening is
+//! added nor checked, the MCU provides and receives the raw bit stream. This allows framing
+//! not supported by the packet handler (e.g. legacy OOK remotes) to be implemented in software.
+//!
+//! - Direct FIFO: the bytes are taken from the TX FIFO and the demodulated bits are written to
+//!   the RX FIFO. Use [`SpiritDirect::direct_tx_fifo`] and [`SpiritDirect::direct_rx_fifo`],
+//!   which select the mode themselves.
+//! - Direct GPIO: the bits are sampled on a GPIO configured as
+//!   [`DigitalInputMode::TxDataInput`], and output on a GPIO configured as
+//!   [`DigitalOutputMode::RxDataOutput`]. The optional clock GPIO tells the MCU when to
+//!   update (TX data are sampled on its rising edge) or sample (recovered RX clock) the data.
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::prelude::*;
+//! # fn bit_bang(radio: &mut impl SpiritDirect) -> RadioResult<()> {
+//! radio.configure_direct_tx_gpio(DirectGpioPins {
+//!     data: GpioPin::Gpio1,
+//!     clock: Some(GpioPin::Gpio2),
+//! })?;
+//! radio.start_direct_tx()?;
+//! // ... bit-bang GPIO1 on the rising edges of GPIO2
+//! radio.stop_direct()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::prelude::*;
+
+use crate::{registers::*, RadioError, RadioResult};
+
+/// GPIOs used by the direct GPIO modes
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub struct DirectGpioPins {
+    /// TX data input or RX data output
+    pub data: GpioPin,
+    /// TX data clock or recovered RX clock output, `None` if not wired to the MCU
+    pub clock: Option<GpioPin>,
+}
+
+pub trait SpiritDirect: Spirit1Driver + SpiritIrq + SpiritGpio {
+    /// Selects the source of the transmitted data (`PCKTCTRL1.TX_SOURCE`)
+    fn set_direct_tx_mode(&mut self, mode: TxMode) -> RadioResult<()> {
+        let mut pckt_ctrl_1: PcktCtrl1 = self.read_register()?;
+        pckt_ctrl_1.tx_source = mode;
+        self.write_register(pckt_ctrl_1)
+    }
+
+    fn get_direct_tx_mode(&mut self) -> RadioResult<TxMode> {
+        Ok(self.read_register::<PcktCtrl1>()?.tx_source)
+    }
+
+    /// Selects the destination of the received data (`PCKTCTRL3.RX_MODE`)
+    fn set_direct_rx_mode(&mut self, mode: RxMode) -> RadioResult<()> {
+        let mut pckt_ctrl_3: PcktCtrl3 = self.read_register()?;
+        pckt_ctrl_3.rx_mode = mode;
+        self.write_register(pckt_ctrl_3)
+    }
+
+    fn get_direct_rx_mode(&mut self) -> RadioResult<RxMode> {
+        Ok(self.read_register::<PcktCtrl3>()?.rx_mode)
+    }
+
+    /// Hands TX and RX back to the packet handler
+    fn disable_direct_modes(&mut self) -> RadioResult<()> {
+        self.set_direct_tx_mode(TxMode::Normal)?;
+        self.set_direct_rx_mode(RxMode::Normal)
+    }
+
+    /// Configures the GPIOs as TX data input and TX data clock output and selects
+    /// [`TxMode::DirectGPIO`]
+    fn configure_direct_tx_gpio(&mut self, pins: DirectGpioPins) -> RadioResult<()> {
+        if pins.clock == Some(pins.data) {
+            return Err(RadioError::InvalidGpioMode);
+        }
+
+        self.configure_gpio(
+            pins.data,
+            GpioMode::DigitalInput(DigitalInputMode::TxDataInput),
+        )?;
+        if let Some(clock) = pins.clock {
+            self.configure_gpio(
+                clock,
+                GpioMode::OutputLowPower(DigitalOutputMode::TxDataInternalClkOutput),
+            )?;
+        }
+
+        self.set_direct_tx_mode(TxMode::DirectGPIO)
+    }
+
+    /// Configures the GPIOs as RX data and recovered clock outputs and selects
+    /// [`RxMode::DirectGPIO`]
+    fn configure_direct_rx_gpio(&mut self, pins: DirectGpioPins) -> RadioResult<()> {
+        if pins.clock == Some(pins.data) {
+            return Err(RadioError::InvalidGpioMode);
+        }
+
+        self.configure_gpio(
+            pins.data,
+            GpioMode::OutputLowPower(DigitalOutputMode::RxDataOutput),
+        )?;
+        if let Some(clock) = pins.clock {
+            self.configure_gpio(
+                clock,
+                GpioMode::OutputLowPower(DigitalOutputMode::RxClockOutput),
+            )?;
+        }
+
+        self.set_direct_rx_mode(RxMode::DirectGPIO)
+    }
+
+    /// Enters TX, the radio transmits until [`SpiritDirect::stop_direct`]
+    fn start_direct_tx(&mut self) -> RadioResult<()> {
+        self.strobe_tx()
+    }
+
+    /// Enters RX, the radio receives until [`SpiritDirect::stop_direct`]
+    fn start_direct_rx(&mut self) -> RadioResult<()> {
+        self.strobe_rx()
+    }
+
+    /// Exits TX or RX to `READY` and flushes both FIFOs
+    fn stop_direct(&mut self) -> RadioResult<()> {
+        self.write_command(SpiritCommand::SABORT)?;
+        self.write_command(SpiritCommand::FLUSH_TX_FIFO)?;
+        self.write_command(SpiritCommand::FLUSH_RX_FIFO)?;
+
+        Ok(())
+    }
+
+    /// Selects [`TxMode::DirectFIFO`] and transmits the raw bytes of `buf`, refilling the TX
+    /// FIFO whenever it drains to the `FIFO_CONFIG` almost empty threshold. The transmission
+    /// ends when the TX FIFO underflows after the last byte. [`TxMode::Normal`] is restored
+    /// before returning, whether the transmission succeeded or not.
+    ///
+    /// While bytes remain to be loaded the FIFO level is polled back to back as long as it
+    /// keeps draining, so that high data rates do not underflow between two polls.
+    ///
+    /// Requires [`InterruptEvent::TxFifoError`] to be enabled in `IRQ_MASK`. Fails with
+    /// [`RadioError::FifoError`] if the FIFO underflows before `buf` is fully loaded, and with
+    /// [`RadioError::Timeout`] if the transmission does not end within `timeout_ms` (counted
+    /// while the FIFO is not draining), `recovery` being applied before returning the error
+    fn direct_tx_fifo(
+        &mut self,
+        buf: &[u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<usize> {
+        self.set_direct_tx_mode(TxMode::DirectFIFO)?;
+
+        let mut transmit = || {
+            let threshold = self.read_register::<FifoConfig>()?.tx_ae_threshold as usize;
+
+            self.write_command(SpiritCommand::FLUSH_TX_FIFO)?;
+            self.irq_poll(InterruptEvent::TxFifoError)?;
+
+            let mut written = buf.len().min(Self::MAX_FIFO_LENGTH);
+            self.write_raw(Self::LINEAR_FIFO_ADDRESS, &buf[..written])?;
+
+            self.start_direct_tx()?;
+
+            let mut last_elements = written;
+            let mut elapsed_ms = 0;
+            loop {
+                if !self.irq_poll(InterruptEvent::TxFifoError)?.is_empty() {
+                    self.stop_direct()?;
+                    if written < buf.len() {
+                        return Err(RadioError::FifoError);
+                    }
+
+                    return Ok(written);
+                }
+
+                let mut draining = false;
+                if written < buf.len() {
+                    let elements = self
+                        .read_register::<LinearFifoStatusTxElements>()?
+                        .elem_txfifo as usize;
+
+                    if elements <= threshold {
+                        let chunk = (buf.len() - written).min(Self::MAX_FIFO_LENGTH - elements);
+                        self.write_raw(Self::LINEAR_FIFO_ADDRESS, &buf[written..written + chunk])?;
+                        written += chunk;
+                        last_elements = elements + chunk;
+                        continue;
+                    }
+
+                    draining = elements < last_elements;
+                    last_elements = elements;
+                }
+
+                if draining {
+                    continue;
+                }
+
+                if elapsed_ms >= timeout_ms {
+                    return self.timed_out(recovery);
+                }
+
+                self.delay_ms(1);
+                elapsed_ms += 1;
+            }
+        };
+
+        let result = transmit();
+        self.set_direct_tx_mode(TxMode::Normal)?;
+
+        result
+    }
+
+    /// Selects [`RxMode::DirectFIFO`] and fills `buffer` with raw demodulated bytes, draining
+    /// the RX FIFO whenever it fills up to the `FIFO_CONFIG` almost full threshold. The
+    /// reception is stopped once `buffer` is full. [`RxMode::Normal`] is restored before
+    /// returning, whether the reception succeeded or not.
+    ///
+    /// Fails with [`RadioError::FifoError`] if the RX FIFO overflows, and with
+    /// [`RadioError::Timeout`] if `buffer` is not filled within `timeout_ms`, `recovery`
+    /// being applied before returning the error
+    fn direct_rx_fifo(
+        &mut self,
+        buffer: &mut [u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<usize> {
+        self.set_direct_rx_mode(RxMode::DirectFIFO)?;
+
+        let mut receive = || {
+            let threshold = self.read_register::<FifoConfig>()?.rx_af_threshold as usize;
+
+            self.write_command(SpiritCommand::FLUSH_RX_FIFO)?;
+            self.irq_poll(InterruptEvent::RxFifoError)?;
+
+            self.start_direct_rx()?;
+
+            let mut received = 0;
+            let mut elapsed_ms = 0;
+            while received < buffer.len() {
+                if !self.irq_poll(InterruptEvent::RxFifoError)?.is_empty() {
+                    self.stop_direct()?;
+                    return Err(RadioError::FifoError);
+                }
+
+                let elements = self
+                    .read_register::<LinearFifoStatusRxElements>()?
+                    .elem_rxfifo as usize;
+                let remaining = buffer.len() - received;
+
+                if elements >= threshold.min(remaining) && elements > 0 {
+                    let chunk = elements.min(remaining);
+                    self.read_raw(Self::LINEAR_FIFO_ADDRESS, chunk, &mut buffer[received..])?;
+                    received += chunk;
+                    continue;
+                }
+
+                if elapsed_ms >= timeout_ms {
+                    return self.timed_out(recovery);
+                }
+
+                self.delay_ms(1);
+                elapsed_ms += 1;
+            }
+
+            self.stop_direct()?;
+
+            Ok(received)
+        };
+
+        let result = receive();
+        self.set_direct_rx_mode(RxMode::Normal)?;
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    fn radio() -> MockSpirit1<'static> {
+        MockSpirit1::new(50_000_000, 868_000_000)
+    }
+
+    #[test]
+    fn direct_tx_fifo_restores_the_normal_mode() {
+        let mut radio = radio();
+        // The simulator ends the transmission on the underflow after the 8 loaded bytes
+        radio.write_register(PcktLen::new(16)).unwrap();
+
+        let sent = radio
+            .direct_tx_fifo(&[0x55; 8], 5, TimeoutRecovery::Abort)
+            .unwrap();
+
+        assert_eq!(sent, 8);
+        assert_eq!(radio.take_transmitted().unwrap().as_slice(), [0x55; 8]);
+        assert!(matches!(radio.get_direct_tx_mode(), Ok(TxMode::Normal)));
+        assert_eq!(radio.state(), SpiritState::READY);
+    }
+
+    #[test]
+    fn direct_tx_fifo_restores_the_normal_mode_on_underflow() {
+        let mut radio = radio();
+        radio.write_register(PcktLen::new(200)).unwrap();
+        radio.set_stream_rate(128);
+
+        let result = radio.direct_tx_fifo(&[0x55; 200], 5, TimeoutRecovery::Abort);
+
+        assert!(matches!(result, Err(RadioError::FifoError)));
+        assert!(matches!(radio.get_direct_tx_mode(), Ok(TxMode::Normal)));
+    }
+
+    #[test]
+    fn direct_rx_fifo_restores_the_normal_mode() {
+        let mut radio = radio();
+        let mut buffer = [0u8; 8];
+        radio.inject_rx_packet(&[0xAA; 8]).unwrap();
+
+        let received = radio
+            .direct_rx_fifo(&mut buffer, 5, TimeoutRecovery::Abort)
+            .unwrap();
+
+        assert_eq!(received, 8);
+        assert_eq!(buffer, [0xAA; 8]);
+        assert!(matches!(radio.get_direct_rx_mode(), Ok(RxMode::Normal)));
+    }
+
+    #[test]
+    fn direct_rx_fifo_restores_the_normal_mode_on_timeout() {
+        let mut radio = radio();
+        let mut buffer = [0u8; 8];
+
+        let result = radio.direct_rx_fifo(&mut buffer, 5, TimeoutRecovery::Abort);
+
+        assert!(matches!(result, Err(RadioError::Timeout(_))));
+        assert!(matches!(radio.get_direct_rx_mode(), Ok(RxMode::Normal)));
+        assert_eq!(radio.state(), SpiritState::READY);
+        assert_eq!(radio.elapsed_ms(), 5);
+    }
+}
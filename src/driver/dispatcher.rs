@@ -0,0 +1,244 @@
+//! # IRQ Dispatcher
+//! Routes the events latched in `IRQ_STATUS` to handlers registered per [`InterruptEvent`].
+//!
+//! The handler table has a fixed capacity `N` and can live in a `static`. Handlers are plain
+//! function pointers receiving a user context `C` (e.g. the application state, or `()`) and
+//! the event that fired. A handler registered for several events is called once per event.
+//!
+//! [`IrqDispatcher::dispatch`] reads (and so resets) `IRQ_STATUS` once, it is meant to be
+//! called from the nIRQ GPIO handler or from the task woken by it.
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::prelude::*;
+//! # struct App { sync_detected: bool, last_error: Option<InterruptEvent> }
+//! # fn run(radio: &mut impl SpiritIrq, app: &mut App) -> RadioResult<()> {
+//! fn on_sync(state: &mut App, _: InterruptEvent) { state.sync_detected = true; }
+//! fn on_error(state: &mut App, event: InterruptEvent) { state.last_error = Some(event); }
+//!
+//! let mut dispatcher: IrqDispatcher<App, 4> = IrqDispatcher::new();
+//! dispatcher.register(InterruptEvent::SyncWordDetected, on_sync)?;
+//! dispatcher.register([InterruptEvent::CrcError, InterruptEvent::LowBatteryLevel], on_error)?;
+//! radio.irq_enable(dispatcher.events())?;
+//!
+//! // nIRQ asserted
+//! dispatcher.dispatch(radio, app)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::prelude::*;
+
+use crate::{registers::*, RadioError, RadioResult};
+
+/// Handler called with the user context and the event that fired
+pub type IrqHandler<C> = fn(&mut C, InterruptEvent);
+
+/// Fixed-capacity table of [`IrqHandler`]s. See the [module documentation](self)
+pub struct IrqDispatcher<C, const N: usize> {
+    handlers: [Option<(IrqSet, IrqHandler<C>)>; N],
+}
+
+impl<C, const N: usize> IrqDispatcher<C, N> {
+    /// Empty table
+    pub const fn new() -> Self {
+        Self {
+            handlers: [None; N],
+        }
+    }
+
+    /// Call `handler` for each of `events`.
+    ///
+    /// Returns [`RadioError::ParameterError`] if the `N` slots are already used
+    pub fn register(
+        &mut self,
+        events: impl Into<IrqSet>,
+        handler: IrqHandler<C>,
+    ) -> RadioResult<()> {
+        let slot = self
+            .handlers
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(RadioError::ParameterError)?;
+        *slot = Some((events.into(), handler));
+
+        Ok(())
+    }
+
+    /// Stop dispatching `events`, handlers left without any event free their slot
+    pub fn unregister(&mut self, events: impl Into<IrqSet>) {
+        let events = events.into();
+        for slot in self.handlers.iter_mut() {
+            if let Some((registered, _)) = slot {
+                *registered = registered.difference(events);
+                if registered.is_empty() {
+                    *slot = None;
+                }
+            }
+        }
+    }
+
+    /// Remove every handler
+    pub fn clear(&mut self) {
+        self.handlers = [None; N];
+    }
+
+    /// Events with at least one handler, to be passed to [`SpiritIrq::irq_enable`]
+    pub fn events(&self) -> IrqSet {
+        self.handlers
+            .iter()
+            .flatten()
+            .fold(IrqSet::empty(), |events, (registered, _)| {
+                events | *registered
+            })
+    }
+
+    /// Read & reset `IRQ_STATUS` and call the handlers of the pending events, in bit order.
+    ///
+    /// Returns every pending event, including the ones without a handler
+    pub fn dispatch<R: SpiritIrq>(&self, radio: &mut R, context: &mut C) -> RadioResult<IrqSet> {
+        let status = radio.irq_take()?;
+        self.dispatch_events(status, context);
+
+        Ok(status)
+    }
+
+    /// Call the handlers of `status`, for an `IRQ_STATUS` already read by the caller
+    pub fn dispatch_events(&self, status: IrqSet, context: &mut C) {
+        for event in status.iter() {
+            for (registered, handler) in self.handlers.iter().flatten() {
+                if registered.contains(event) {
+                    handler(context, event);
+                }
+            }
+        }
+    }
+}
+
+impl<C, const N: usize> Default for IrqDispatcher<C, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    /// Handler calls, in order
+    #[derive(Default)]
+    struct Log {
+        calls: [Option<(char, InterruptEvent)>; 8],
+        len: usize,
+    }
+
+    impl Log {
+        fn push(&mut self, handler: char, event: InterruptEvent) {
+            self.calls[self.len] = Some((handler, event));
+            self.len += 1;
+        }
+
+        fn calls(&self) -> &[Option<(char, InterruptEvent)>] {
+            &self.calls[..self.len]
+        }
+    }
+
+    fn a(log: &mut Log, event: InterruptEvent) {
+        log.push('a', event);
+    }
+
+    fn b(log: &mut Log, event: InterruptEvent) {
+        log.push('b', event);
+    }
+
+    #[test]
+    fn handlers_are_called_in_bit_then_registration_order() {
+        let mut dispatcher: IrqDispatcher<Log, 4> = IrqDispatcher::new();
+        dispatcher
+            .register([InterruptEvent::Ready, InterruptEvent::RxDataReady], b)
+            .unwrap();
+        dispatcher.register(InterruptEvent::Ready, a).unwrap();
+
+        let mut log = Log::default();
+        dispatcher.dispatch_events(
+            IrqSet::from([InterruptEvent::Ready, InterruptEvent::RxDataReady]),
+            &mut log,
+        );
+
+        assert_eq!(
+            log.calls(),
+            [
+                Some(('b', InterruptEvent::RxDataReady)),
+                Some(('b', InterruptEvent::Ready)),
+                Some(('a', InterruptEvent::Ready)),
+            ]
+        );
+    }
+
+    #[test]
+    fn register_fails_over_capacity() {
+        let mut dispatcher: IrqDispatcher<Log, 2> = IrqDispatcher::new();
+        dispatcher.register(InterruptEvent::Ready, a).unwrap();
+        dispatcher.register(InterruptEvent::Lock, b).unwrap();
+
+        assert!(matches!(
+            dispatcher.register(InterruptEvent::CrcError, a),
+            Err(RadioError::ParameterError)
+        ));
+
+        // Unregistering the last event of a handler frees its slot
+        dispatcher.unregister(InterruptEvent::Lock);
+        dispatcher.register(InterruptEvent::CrcError, a).unwrap();
+        assert_eq!(
+            dispatcher.events(),
+            IrqSet::from([InterruptEvent::Ready, InterruptEvent::CrcError])
+        );
+    }
+
+    #[test]
+    fn dispatch_reads_a_multi_event_status_word() {
+        let mut radio = MockSpirit1::new(50_000_000, 868_000_000);
+        let mut dispatcher: IrqDispatcher<Log, 4> = IrqDispatcher::new();
+        dispatcher
+            .register(
+                [InterruptEvent::TxDataSent, InterruptEvent::TimerRxTimeout],
+                a,
+            )
+            .unwrap();
+        dispatcher.register(InterruptEvent::CrcError, b).unwrap();
+
+        radio.raise_irq(InterruptEvent::TimerRxTimeout);
+        radio.raise_irq(InterruptEvent::CrcError);
+        radio.raise_irq(InterruptEvent::TxDataSent);
+        radio.raise_irq(InterruptEvent::Lock);
+
+        let mut log = Log::default();
+        let status = dispatcher.dispatch(&mut radio, &mut log).unwrap();
+
+        // Events without a handler are returned as well
+        assert_eq!(
+            status,
+            IrqSet::from([
+                InterruptEvent::TxDataSent,
+                InterruptEvent::CrcError,
+                InterruptEvent::Lock,
+                InterruptEvent::TimerRxTimeout,
+            ])
+        );
+        assert_eq!(
+            log.calls(),
+            [
+                Some(('a', InterruptEvent::TxDataSent)),
+                Some(('b', InterruptEvent::CrcError)),
+                Some(('a', InterruptEvent::TimerRxTimeout)),
+            ]
+        );
+
+        // IRQ_STATUS was reset by the read
+        assert!(dispatcher
+            .dispatch(&mut radio, &mut log)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! # GPIO Configuration
+//! *See 10.3 GPIOs*
+//!
+//! Typed access to the four `GPIOx_CONF` registers. Modes are checked against the pin before
+//! being written:
+//! - [`GpioMode::Analog`] (temperature sensor output) is only available on `GPIO_0`
+//! - [`DigitalInputMode::ExternalClock`] (34.7 kHz LDC clock) is only available on `GPIO_3`
+//! - [`DigitalInputMode::TxDataInput`] and [`DigitalInputMode::ExternalClock`] feed a single
+//!   internal signal and can only be selected on one pin at a time
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::prelude::*;
+//! # fn configure(radio: &mut impl SpiritGpio) -> RadioResult<()> {
+//! radio.configure_gpio(
+//!     GpioPin::Gpio0,
+//!     GpioMode::OutputLowPower(DigitalOutputMode::nIRQ),
+//! )?;
+//!
+//! let mode = radio.read_gpio_config(GpioPin::Gpio0)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::prelude::*;
+
+use crate::{registers::*, RadioError, RadioResult};
+
+/// SPIRIT1 GPIO pins
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub enum GpioPin {
+    Gpio0,
+    Gpio1,
+    Gpio2,
+    Gpio3,
+}
+
+impl GpioPin {
+    pub const ALL: [GpioPin; 4] = [Self::Gpio0, Self::Gpio1, Self::Gpio2, Self::Gpio3];
+
+    /// Whether the datasheet allows `mode` on this pin
+    pub fn supports(&self, mode: &GpioMode) -> bool {
+        match mode {
+            GpioMode::Analog => *self == Self::Gpio0,
+            GpioMode::DigitalInput(DigitalInputMode::ExternalClock) => *self == Self::Gpio3,
+            _ => true,
+        }
+    }
+}
+
+/// Digital inputs driving a single internal signal
+fn is_exclusive_input(mode: &GpioMode) -> bool {
+    matches!(
+        mode,
+        GpioMode::DigitalInput(DigitalInputMode::TxDataInput | DigitalInputMode::ExternalClock)
+    )
+}
+
+pub trait SpiritGpio: Spirit1HalBlocking {
+    /// Configures `pin` in `mode`.
+    ///
+    /// Returns [`RadioError::InvalidGpioMode`] if the mode is not available on the pin, or if
+    /// it selects the TX data or external clock input while another pin drives either of them
+    fn configure_gpio(&mut self, pin: GpioPin, mode: GpioMode) -> RadioResult<()> {
+        if !pin.supports(&mode) {
+            error!("{} is not available on {}", mode, pin);
+            return Err(RadioError::InvalidGpioMode);
+        }
+
+        if is_exclusive_input(&mode) {
+            for other in GpioPin::ALL.into_iter().filter(|other| *other != pin) {
+                if is_exclusive_input(&self.read_gpio_config(other)?) {
+                    error!("{} is already selected on {}", mode, other);
+                    return Err(RadioError::InvalidGpioMode);
+                }
+            }
+        }
+
+        let conf = GpioConf::new(mode);
+        match pin {
+            GpioPin::Gpio0 => self.write_register(Gpio0Conf(conf)),
+            GpioPin::Gpio1 => self.write_register(Gpio1Conf(conf)),
+            GpioPin::Gpio2 => self.write_register(Gpio2Conf(conf)),
+            GpioPin::Gpio3 => self.write_register(Gpio3Conf(conf)),
+        }
+    }
+
+    /// Mode currently programmed on `pin`
+    fn read_gpio_config(&mut self, pin: GpioPin) -> RadioResult<GpioMode> {
+        let conf = match pin {
+            GpioPin::Gpio0 => self.read_register::<Gpio0Conf>()?.0,
+            GpioPin::Gpio1 => self.read_register::<Gpio1Conf>()?.0,
+            GpioPin::Gpio2 => self.read_register::<Gpio2Conf>()?.0,
+            GpioPin::Gpio3 => self.read_register::<Gpio3Conf>()?.0,
+        };
+
+        Ok(conf.gpio_mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    fn radio() -> MockSpirit1<'static> {
+        MockSpirit1::new(50_000_000, 868_000_000)
+    }
+
+    fn rejected(radio: &mut MockSpirit1, pin: GpioPin, mode: GpioMode) -> bool {
+        matches!(
+            radio.configure_gpio(pin, mode),
+            Err(RadioError::InvalidGpioMode)
+        )
+    }
+
+    #[test]
+    fn analog_mode_only_on_gpio_0() {
+        let mut radio = radio();
+
+        for pin in [GpioPin::Gpio1, GpioPin::Gpio2, GpioPin::Gpio3] {
+            assert!(rejected(&mut radio, pin, GpioMode::Analog));
+        }
+        radio
+            .configure_gpio(GpioPin::Gpio0, GpioMode::Analog)
+            .unwrap();
+    }
+
+    #[test]
+    fn external_clock_only_on_gpio_3() {
+        let mut radio = radio();
+        let clock = GpioMode::DigitalInput(DigitalInputMode::ExternalClock);
+
+        for pin in [GpioPin::Gpio0, GpioPin::Gpio1, GpioPin::Gpio2] {
+            assert!(rejected(&mut radio, pin, clock));
+        }
+        radio.configure_gpio(GpioPin::Gpio3, clock).unwrap();
+    }
+
+    #[test]
+    fn tx_data_input_and_external_clock_are_exclusive() {
+        let mut radio = radio();
+        let tx_data = GpioMode::DigitalInput(DigitalInputMode::TxDataInput);
+        let clock = GpioMode::DigitalInput(DigitalInputMode::ExternalClock);
+
+        radio.configure_gpio(GpioPin::Gpio1, tx_data).unwrap();
+        assert!(rejected(&mut radio, GpioPin::Gpio2, tx_data));
+        assert!(rejected(&mut radio, GpioPin::Gpio3, clock));
+
+        // Reconfiguring the same pin is allowed
+        radio.configure_gpio(GpioPin::Gpio1, tx_data).unwrap();
+
+        // Once the pin is released the input can move
+        radio
+            .configure_gpio(
+                GpioPin::Gpio1,
+                GpioMode::OutputLowPower(DigitalOutputMode::Gnd),
+            )
+            .unwrap();
+        radio.configure_gpio(GpioPin::Gpio3, clock).unwrap();
+        assert!(rejected(&mut radio, GpioPin::Gpio2, tx_data));
+    }
+
+    #[test]
+    fn gpio_configuration_read_back() {
+        let mut radio = radio();
+
+        radio
+            .configure_gpio(
+                GpioPin::Gpio0,
+                GpioMode::OutputLowPower(DigitalOutputMode::nIRQ),
+            )
+            .unwrap();
+        radio
+            .configure_gpio(
+                GpioPin::Gpio1,
+                GpioMode::OutputHighPower(DigitalOutputMode::RxDataOutput),
+            )
+            .unwrap();
+        radio
+            .configure_gpio(
+                GpioPin::Gpio2,
+                GpioMode::DigitalInput(DigitalInputMode::TxCommand),
+            )
+            .unwrap();
+        radio
+            .configure_gpio(
+                GpioPin::Gpio3,
+                GpioMode::DigitalInput(DigitalInputMode::ExternalClock),
+            )
+            .unwrap();
+
+        // GPIO_SELECT in bits 7:3, GPIO_MODE in bits 1:0
+        assert_eq!(radio.register(Gpio0Conf::ADDRESS), 0x02);
+        assert_eq!(radio.register(Gpio1Conf::ADDRESS), 0x43);
+        assert_eq!(radio.register(Gpio2Conf::ADDRESS), 0x01);
+        assert_eq!(radio.register(Gpio3Conf::ADDRESS), 0x21);
+
+        assert!(matches!(
+            radio.read_gpio_config(GpioPin::Gpio0).unwrap(),
+            GpioMode::OutputLowPower(DigitalOutputMode::nIRQ)
+        ));
+        assert!(matches!(
+            radio.read_gpio_config(GpioPin::Gpio1).unwrap(),
+            GpioMode::OutputHighPower(DigitalOutputMode::RxDataOutput)
+        ));
+        assert!(matches!(
+            radio.read_gpio_config(GpioPin::Gpio2).unwrap(),
+            GpioMode::DigitalInput(DigitalInputMode::TxCommand)
+        ));
+        assert!(matches!(
+            radio.read_gpio_config(GpioPin::Gpio3).unwrap(),
+            GpioMode::DigitalInput(DigitalInputMode::ExternalClock)
+        ));
+    }
+}
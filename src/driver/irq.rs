@@ -12,8 +12,98 @@ pub trait SpiritIrq: Spirit1HalBlocking {
     }
 
     fn irq_clear(&mut self) -> RadioResult<()> {
-        // Register is a Read-Reset type
-        let _: IrqStatus = self.read_register()?;
+        self.irq_take()?;
         Ok(())
     }
+
+    /// Read & reset `IRQ_STATUS`, returning the events that were pending, including the
+    /// ones a driver wait read out of `IRQ_STATUS` (see [`SpiritIrq::irq_poll`])
+    ///
+    /// Masked events are latched as well, see [`SpiritIrq::irq_get_mask`]
+    fn irq_take(&mut self) -> RadioResult<IrqSet> {
+        // Register is a Read-Reset type
+        let status: IrqStatus = self.read_register()?;
+        let latched = self.irq_latched().map(core::mem::take).unwrap_or_default();
+
+        Ok(latched | status.events())
+    }
+
+    /// Read & reset `IRQ_STATUS` while waiting for `events`, returning the ones that are
+    /// pending. The other events are kept in [`Spirit1HalBlocking::irq_latched`] for the
+    /// next [`SpiritIrq::irq_take`]
+    fn irq_poll(&mut self, events: impl Into<IrqSet>) -> RadioResult<IrqSet> {
+        // Register is a Read-Reset type
+        let status = self.read_register::<IrqStatus>()?.events();
+        let events = events.into();
+        if let Some(latched) = self.irq_latched() {
+            *latched |= status.difference(events);
+        }
+
+        Ok(status.intersection(events))
+    }
+
+    /// Events currently allowed to drive nIRQ
+    fn irq_get_mask(&mut self) -> RadioResult<IrqSet> {
+        let mask: IrqMask = self.read_register()?;
+        Ok(mask.events())
+    }
+
+    /// Replace the `IRQ_MASK` with `events`
+    fn irq_set_mask(&mut self, events: IrqSet) -> RadioResult<()> {
+        self.write_register(IrqMask::from(events))
+    }
+
+    /// Unmask `events`, keeping the other events as they are
+    fn irq_enable(&mut self, events: impl Into<IrqSet>) -> RadioResult<()> {
+        let mask = self.irq_get_mask()? | events.into();
+        self.irq_set_mask(mask)
+    }
+
+    /// Mask `events`, keeping the other events as they are
+    fn irq_disable(&mut self, events: impl Into<IrqSet>) -> RadioResult<()> {
+        let mask = self.irq_get_mask()?.difference(events.into());
+        self.irq_set_mask(mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+    use crate::{Spirit1Driver, SpiritAes, AES_BLOCK_LENGTH};
+
+    #[test]
+    fn irq_poll_keeps_the_other_events() {
+        let mut radio = MockSpirit1::new(50_000_000, 868_000_000);
+        radio.raise_irq(InterruptEvent::RxDataReady);
+        radio.raise_irq(InterruptEvent::TxDataSent);
+
+        let events = radio.irq_poll(InterruptEvent::TxDataSent).unwrap();
+        assert_eq!(events, IrqSet::from(InterruptEvent::TxDataSent));
+
+        // Read out of IRQ_STATUS, handed back once
+        assert_eq!(
+            radio.irq_take().unwrap(),
+            IrqSet::from(InterruptEvent::RxDataReady)
+        );
+        assert!(radio.irq_take().unwrap().is_empty());
+    }
+
+    #[test]
+    fn driver_waits_keep_the_other_events() {
+        let mut radio = MockSpirit1::new(50_000_000, 868_000_000);
+        radio.raise_irq(InterruptEvent::ValidPreambleDetected);
+        radio.tx_blocking(b"ping").unwrap();
+
+        radio.raise_irq(InterruptEvent::SyncWordDetected);
+        radio.aes_enable(true).unwrap();
+        radio.aes_set_key(&[0; AES_BLOCK_LENGTH]).unwrap();
+        radio.aes_encrypt_block(&[0; AES_BLOCK_LENGTH]).unwrap();
+
+        let events = radio.irq_take().unwrap();
+        assert!(events.contains(InterruptEvent::ValidPreambleDetected));
+        assert!(events.contains(InterruptEvent::SyncWordDetected));
+        assert!(!events.contains(InterruptEvent::TxDataSent));
+        assert!(!events.contains(InterruptEvent::OthersAesEndOfOperation));
+    }
 }
@@ -0,0 +1,212 @@
+//! # Low Duty Cycle
+//! *See 9.8 Timers, Low duty cycle mode*
+//!
+//! In LDC mode the SPIRIT1 sleeps between operations and is woken up by the wake-up timer,
+//! clocked by the RC oscillator:
+//! - LDC-RX: at each wake-up the radio listens for the RX timeout, then goes back to
+//!   `SLEEP`. When `ldc_reload_on_sync` is set, detecting a sync word reloads the wake-up
+//!   timer with the reload period, keeping the receiver synchronised with the transmitter.
+//! - LDC-TX: at each wake-up the radio transmits the content of the TX FIFO, which must
+//!   be reloaded after every `TX_DATA_SENT`.
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::prelude::*;
+//! # fn listen(radio: &mut impl Spirit1) -> RadioResult<()> {
+//! radio.configure_ldc(LdcOpts {
+//!     wake_up_ms: 500.0,
+//!     ..Default::default()
+//! })?;
+//! radio.set_rx_timeout_ms(5.0)?;
+//! radio.start_ldc_rx()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::prelude::*;
+
+use crate::{registers::*, RadioError, RadioResult};
+
+pub trait SpiritLdc: Spirit1Driver {
+    /// Configures the LDC timers and enables the RCO calibration, without entering LDC mode
+    fn configure_ldc(&mut self, opts: LdcOpts) -> RadioResult<()> {
+        self.set_ldc_wake_up_ms(opts.wake_up_ms)?;
+        self.set_ldc_reload_ms(opts.reload_ms)?;
+
+        // The LDC timers are clocked by the RCO
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.rco_calibration = true;
+        protocol.ldc_reload_on_sync = opts.reload_on_sync;
+        self.write_register(protocol)
+    }
+
+    /// Sets the wake-up period, returning the period programmed after quantisation in ms.
+    ///
+    /// Returns [`RadioError::ParameterError`] if `ms` is not in the timer range
+    fn set_ldc_wake_up_ms(&mut self, ms: f32) -> RadioResult<f32> {
+        let rco_frequency = self.get_rco_frequency()?;
+        let (counter, prescaler) = compute_ldc_timer(ms, rco_frequency)?;
+
+        let mut timers: Timers = self.read_register()?;
+        (timers.ldc_counter, timers.ldc_prescaler) = (counter, prescaler);
+        self.write_register(timers)?;
+
+        Ok(wake_up_ms(counter, prescaler, rco_frequency))
+    }
+
+    /// Wake-up period in ms
+    fn get_ldc_wake_up_ms(&mut self) -> RadioResult<f32> {
+        let rco_frequency = self.get_rco_frequency()?;
+        let timers: Timers = self.read_register()?;
+
+        Ok(wake_up_ms(
+            timers.ldc_counter,
+            timers.ldc_prescaler,
+            rco_frequency,
+        ))
+    }
+
+    /// Sets the period used after a sync word detection or an `LDC_RELOAD` command,
+    /// returning the period programmed after quantisation in ms.
+    ///
+    /// Returns [`RadioError::ParameterError`] if `ms` is not in the timer range
+    fn set_ldc_reload_ms(&mut self, ms: f32) -> RadioResult<f32> {
+        let rco_frequency = self.get_rco_frequency()?;
+        let (counter, prescaler) = compute_ldc_timer(ms, rco_frequency)?;
+
+        let mut timers: Timers = self.read_register()?;
+        (timers.ldc_reload_counter, timers.ldc_reload_prescaler) = (counter, prescaler);
+        self.write_register(timers)?;
+
+        Ok(wake_up_ms(counter, prescaler, rco_frequency))
+    }
+
+    /// Reload period in ms
+    fn get_ldc_reload_ms(&mut self) -> RadioResult<f32> {
+        let rco_frequency = self.get_rco_frequency()?;
+        let timers: Timers = self.read_register()?;
+
+        Ok(wake_up_ms(
+            timers.ldc_reload_counter,
+            timers.ldc_reload_prescaler,
+            rco_frequency,
+        ))
+    }
+
+    /// Frequency in Hz of the RC oscillator clocking the LDC timers, see [`rco_frequency`]
+    fn get_rco_frequency(&mut self) -> RadioResult<u32> {
+        let xo_rco_config: XoRcoConfig = self.read_register()?;
+
+        Ok(rco_frequency(
+            self.get_xtal_frequency(),
+            xo_rco_config.rco_divider,
+        ))
+    }
+
+    /// Enables or disables the LDC mode
+    fn set_ldc_mode(&mut self, enable: bool) -> RadioResult<()> {
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.ldc_mode = enable;
+        self.write_register(protocol)
+    }
+
+    /// Reloads the wake-up timer with the reload period (`LDC_RELOAD`)
+    fn ldc_reload(&mut self) -> RadioResult<()> {
+        self.write_command(SpiritCommand::LDC_RELOAD)?;
+
+        Ok(())
+    }
+
+    /// Enters LDC-RX: the radio listens for the RX timeout at every wake-up. Received
+    /// packets raise `RX_DATA_READY` as in normal RX
+    fn start_ldc_rx(&mut self) -> RadioResult<()> {
+        self.set_ldc_mode(true)?;
+        self.strobe_rx()
+    }
+
+    /// Enters LDC-TX, transmitting `buf` now and the content of the TX FIFO at every
+    /// following wake-up, see [`SpiritLdc::load_ldc_tx`]
+    fn start_ldc_tx(&mut self, buf: &[u8]) -> RadioResult<usize> {
+        if buf.len() > Self::MAX_FIFO_LENGTH {
+            return Err(RadioError::PacketTooLong);
+        }
+
+        self.set_ldc_mode(true)?;
+        self.start_tx(buf)
+    }
+
+    /// Loads `buf` in the TX FIFO, to be transmitted at the next wake-up
+    fn load_ldc_tx(&mut self, buf: &[u8]) -> RadioResult<usize> {
+        if buf.len() > Self::MAX_FIFO_LENGTH {
+            return Err(RadioError::PacketTooLong);
+        }
+
+        self.write_command(SpiritCommand::FLUSH_TX_FIFO)?;
+        self.write_raw(Self::LINEAR_FIFO_ADDRESS, buf)?;
+        self.write_register(PcktLen::new(buf.len() as u16))?;
+
+        Ok(buf.len())
+    }
+
+    /// Leaves the LDC mode, the radio being back in `READY`
+    fn stop_ldc(&mut self) -> RadioResult<()> {
+        self.set_ldc_mode(false)?;
+        self.write_command(SpiritCommand::SABORT)?;
+        self.wait_for_ready()
+    }
+}
+
+/// Low duty cycle options
+#[derive(Clone, Debug, defmt::Format)]
+pub struct LdcOpts {
+    /// Period between two wake-ups in ms
+    pub wake_up_ms: f32,
+    /// Period used after a sync word detection or an `LDC_RELOAD` command, in ms
+    pub reload_ms: f32,
+    /// Reload the wake-up timer with `reload_ms` when a sync word is detected
+    pub reload_on_sync: bool,
+}
+
+impl Default for LdcOpts {
+    fn default() -> Self {
+        Self {
+            wake_up_ms: 1000.0,
+            reload_ms: 1000.0,
+            reload_on_sync: false,
+        }
+    }
+}
+
+fn compute_ldc_timer(ms: f32, rco_frequency: u32) -> RadioResult<(u8, u8)> {
+    if !(ms > 0.0 && ms <= wake_up_ms(0xFF, 0xFF, rco_frequency)) {
+        return Err(RadioError::ParameterError);
+    }
+
+    Ok(compute_wake_up_values(ms, rco_frequency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    #[test]
+    fn ldc_timers_use_the_rco_frequency() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+
+        // 25 MHz digital clock with the reset divider
+        assert_eq!(radio.get_rco_frequency().unwrap(), 36_100);
+
+        let programmed = radio.set_ldc_wake_up_ms(500.0).unwrap();
+        assert!((programmed - 500.0).abs() < 1.0);
+        assert_eq!(radio.get_ldc_wake_up_ms().unwrap(), programmed);
+
+        let mut config: XoRcoConfig = radio.read_register().unwrap();
+        config.rco_divider = 0b11;
+        radio.write_register(config).unwrap();
+        assert_eq!(radio.get_rco_frequency().unwrap(), 33_300);
+    }
+}
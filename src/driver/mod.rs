@@ -9,8 +9,75 @@ pub use packet_formats::*;
 mod irq;
 pub use irq::*;
 
-/// All the possible RX timeout stop conditions enumeration
-#[derive(TryValued, Clone, Copy)]
+mod dispatcher;
+pub use dispatcher::*;
+
+mod auto_ack;
+pub use auto_ack::*;
+
+mod csma;
+pub use csma::*;
+
+mod timer;
+pub use timer::*;
+
+mod ldc;
+pub use ldc::*;
+
+mod aes;
+pub use aes::*;
+
+mod gpio;
+pub use gpio::*;
+
+mod direct;
+pub use direct::*;
+
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "async")]
+pub use asynch::*;
+
+/// `MC_STATE` state code reported when the synthesizer fails to lock during the VCO calibration
+const MC_STATE_LOCK_ERROR: u8 = 0x13;
+
+/// Events handled while waiting for a packet
+pub(crate) const RX_EVENTS: IrqSet = IrqSet::empty()
+    .with(InterruptEvent::RxDataReady)
+    .with(InterruptEvent::RxDataDiscarded)
+    .with(InterruptEvent::TimerRxTimeout);
+
+/// Time allowed for each state transition of the VCO calibration, the synthesizer locking
+/// within a few tens of µs
+pub const VCO_LOCK_TIMEOUT_MS: u32 = 10;
+
+/// Recovery action applied when a blocking wait times out
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub enum TimeoutRecovery {
+    /// Leave the radio in its current state
+    None,
+    /// Issue `SABORT`, exiting RX or TX to `READY`
+    Abort,
+    /// Issue `S_RES`, resetting the radio
+    Reset,
+}
+
+impl TimeoutRecovery {
+    /// Command issued by the recovery, if any
+    pub(crate) fn command(self) -> Option<SpiritCommand> {
+        match self {
+            TimeoutRecovery::None => None,
+            TimeoutRecovery::Abort => Some(SpiritCommand::SABORT),
+            TimeoutRecovery::Reset => Some(SpiritCommand::S_RES),
+        }
+    }
+}
+
+/// All the possible RX timeout stop conditions enumeration.
+///
+/// Bits 0 to 2 select the PQI, SQI and RSSI (carrier sense) conditions, bit 3 combines them
+/// with an OR instead of an AND
+#[derive(TryValued, Clone, Copy, Debug, PartialEq, defmt::Format)]
 pub enum RxTimeoutStopCondition {
     /// Timeout never stopped
     #[valued(0x00)]
@@ -53,12 +120,51 @@ pub enum RxTimeoutStopCondition {
     AnyAboveThreshold,
 }
 
+impl RxTimeoutStopCondition {
+    /// Sets the `PROTOCOL` CS, SQI and PQI masks and the `PCKT_FLT_OPTIONS` AND/OR selection
+    /// of the condition
+    pub(crate) fn apply(
+        self,
+        pkt_opts: &mut PcktFltOptions,
+        protocol: &mut Protocol,
+    ) -> RadioResult<()> {
+        let condition = TryInto::<u8>::try_into(self)?;
+        pkt_opts.rx_timeout_and_or_select = condition & 0b1000 != 0;
+        protocol.cs_timeout_mask = condition & 0b0100 != 0;
+        protocol.sqi_timeout_mask = condition & 0b0010 != 0;
+        protocol.pqi_timeout_mask = condition & 0b0001 != 0;
+
+        Ok(())
+    }
+
+    /// Condition programmed in the `PROTOCOL` masks and the `PCKT_FLT_OPTIONS` AND/OR
+    /// selection, see [`Spirit1Driver::get_rx_timeout_stop_condition`]
+    pub(crate) fn from_registers(
+        pkt_opts: &PcktFltOptions,
+        protocol: &Protocol,
+    ) -> RadioResult<Self> {
+        let masks = (protocol.cs_timeout_mask as u8) << 2
+            | (protocol.sqi_timeout_mask as u8) << 1
+            | protocol.pqi_timeout_mask as u8;
+        let or_select = pkt_opts.rx_timeout_and_or_select && !masks.is_power_of_two();
+        let condition = (or_select as u8) << 3 | masks;
+
+        Ok(Self::try_from(condition)?)
+    }
+}
+
 impl<T> Spirit1Driver for T where T: Spirit1HalBlocking {}
 impl<T> SpiritPacketFormats for T where T: Spirit1HalBlocking {}
 impl<T> SpiritIrq for T where T: Spirit1HalBlocking {}
+impl<T> SpiritAutoAck for T where T: Spirit1HalBlocking {}
+impl<T> SpiritCsma for T where T: Spirit1HalBlocking {}
+impl<T> SpiritLdc for T where T: Spirit1HalBlocking {}
+impl<T> SpiritAes for T where T: Spirit1HalBlocking {}
+impl<T> SpiritGpio for T where T: Spirit1HalBlocking {}
+impl<T> SpiritDirect for T where T: Spirit1HalBlocking {}
 impl<T> Spirit1 for T where T: Spirit1HalBlocking {}
 
-pub trait Spirit1Driver: Spirit1HalBlocking {
+pub trait Spirit1Driver: SpiritIrq {
     /// In the SPIRIT1 there are two data FIFOs, a TX FIFO for data to be transmitted and an RX.
     /// FIFO for the received data. The length of both FIFOs is 96 bytes.
     const MAX_FIFO_LENGTH: usize = 96;
@@ -80,18 +186,26 @@ pub trait Spirit1Driver: Spirit1HalBlocking {
 
     /// Blocking wait for `MC_STATE` to be `READY`
     fn wait_for_ready(&mut self) -> RadioResult<()> {
-        // TODO: Implement timeout function
         self.wait_for_state(SpiritState::READY)
     }
 
+    /// Blocking wait for `MC_STATE` to be `READY`, see [`Spirit1Driver::wait_for_state_timeout`]
+    fn wait_for_ready_timeout(
+        &mut self,
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<()> {
+        self.wait_for_state_timeout(SpiritState::READY, timeout_ms, recovery)
+    }
+
     /// Sets the RSSI threshold from its dBm value
     fn set_rssi_threshold(&mut self, dbm: i32) -> RadioResult<()> {
         self.write_register(RssiTh::new(Self::compute_rssi_threshold(dbm)))
     }
 
-    /// Sets the RX timeout timer counter and prescaler from the desired
-    /// value in ms. it is possible to fix the RX_Timeout to a minimum value
-    /// of 50.417us to a maximum value of about 3.28s
+    /// Sets the RX timeout timer counter and prescaler, the timeout lasting
+    /// `(prescaler + 1) * counter` periods of the timer clock. See
+    /// [`Spirit1Driver::set_rx_timeout_ms`]
     fn set_rx_timeout(&mut self, timeout_counter: u8, timeout_prescaler: u8) -> RadioResult<()> {
         let mut timers: Timers = self.read_register()?;
 
@@ -102,10 +216,48 @@ pub trait Spirit1Driver: Spirit1HalBlocking {
         self.write_register(timers)
     }
 
+    /// Sets the RX timeout from its value in ms, returning the timeout programmed after
+    /// quantisation in ms.
+    ///
+    /// The timer resolution is `1210 / f_clk` (`50.417 µs` with a 24 MHz crystal) and the
+    /// maximum timeout `65280` times the resolution (about `3.29 s`). Returns
+    /// [`RadioError::ParameterError`] outside of this range
+    fn set_rx_timeout_ms(&mut self, ms: f32) -> RadioResult<f32> {
+        let xtal_frequency = self.get_xtal_frequency();
+        if !(ms > 0.0 && ms <= rx_timeout_ms(0xFF, 0xFF, xtal_frequency)) {
+            return Err(RadioError::ParameterError);
+        }
+
+        let (counter, prescaler) = compute_rx_timeout_values(ms, xtal_frequency);
+        self.set_rx_timeout(counter, prescaler)?;
+
+        Ok(rx_timeout_ms(counter, prescaler, xtal_frequency))
+    }
+
+    /// RX timeout in ms, `None` if the timeout is infinite
+    fn get_rx_timeout_ms(&mut self) -> RadioResult<Option<f32>> {
+        let timers: Timers = self.read_register()?;
+        if timers.rx_timeout_counter == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(rx_timeout_ms(
+            timers.rx_timeout_counter,
+            timers.rx_timeout_prescaler,
+            self.get_xtal_frequency(),
+        )))
+    }
+
+    /// Disables the RX timeout, the radio staying in RX until a packet is received
+    fn set_rx_timeout_infinite(&mut self) -> RadioResult<()> {
+        let mut timers: Timers = self.read_register()?;
+        timers.rx_timeout_counter = 0;
+        self.write_register(timers)
+    }
+
     /// Blocking wait for `MC_STATE` to enter specified state
     fn wait_for_state(&mut self, state: SpiritState) -> RadioResult<()> {
         trace!("waiting for SpiritState::{}", state);
-        // TODO: Implement timeout function
         while Self::read_register::<McState>(self)?.state != state {
             self.delay_ms(100);
         }
@@ -113,30 +265,47 @@ pub trait Spirit1Driver: Spirit1HalBlocking {
         Ok(())
     }
 
+    /// Blocking wait for `MC_STATE` to enter specified state, polling every millisecond.
+    ///
+    /// Fails with [`RadioError::Timeout`] carrying the last observed `MC_STATE` if the state
+    /// is not reached within `timeout_ms`. `recovery` is applied before returning the error
+    fn wait_for_state_timeout(
+        &mut self,
+        state: SpiritState,
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<()> {
+        trace!("waiting for SpiritState::{} ({} ms)", state, timeout_ms);
+
+        let mut elapsed_ms = 0;
+        while self.read_register::<McState>()?.state != state {
+            if elapsed_ms >= timeout_ms {
+                return self.timed_out(recovery);
+            }
+
+            self.delay_ms(1);
+            elapsed_ms += 1;
+        }
+
+        Ok(())
+    }
+
     /// Compute the RSSI threshold for a given dBm.
     ///
     /// Valid range is `-130 ≤ dBm ≤ 2`. Values outside this range will be clamped
     fn compute_rssi_threshold(dbm: i32) -> u8 {
-        trace!("Compute RSSI: {}", dbm);
-        let dbm_clamped = dbm.max(-130).min(2);
-
-        2 * (dbm_clamped as u8).overflowing_add(130).0
+        compute_rssi_threshold(dbm)
     }
 
+    /// Sets the conditions stopping the RX timeout timer, programming the `PROTOCOL` CS, SQI
+    /// and PQI masks and the `PCKT_FLT_OPTIONS` AND/OR selection
     fn set_rx_timeout_stop_condition(
         &mut self,
         stop_condition: RxTimeoutStopCondition,
     ) -> RadioResult<()> {
         let mut pkt_opts: PcktFltOptions = self.read_register()?;
         let mut protocol: Protocol = self.read_register()?;
-
-        pkt_opts.rx_timeout_and_or_select = (TryInto::<u8>::try_into(stop_condition)? & 0x08) > 1;
-
-        // 0x1F     0b0001_1111
-        let mask = TryInto::<u8>::try_into(stop_condition)?;
-        protocol.cs_timeout_mask = (mask & 0b1000_0000) > 1;
-        protocol.sqi_timeout_mask = (mask & 0b0100_0000) > 1;
-        protocol.pqi_timeout_mask = (mask & 0b0010_0000) > 1;
+        stop_condition.apply(&mut pkt_opts, &mut protocol)?;
 
         self.write_register(pkt_opts)?;
         self.write_register(protocol)?;
@@ -144,118 +313,273 @@ pub trait Spirit1Driver: Spirit1HalBlocking {
         Ok(())
     }
 
+    /// Reads back the conditions stopping the RX timeout timer.
+    ///
+    /// With a single mask set the AND/OR selection makes no difference, the AND variant is
+    /// returned
+    fn get_rx_timeout_stop_condition(&mut self) -> RadioResult<RxTimeoutStopCondition> {
+        let pkt_opts: PcktFltOptions = self.read_register()?;
+        let protocol: Protocol = self.read_register()?;
+
+        RxTimeoutStopCondition::from_registers(&pkt_opts, &protocol)
+    }
+
+    /// Sets the RF carrier base frequency (Hz): `SYNT`, band select, charge pump current and
+    /// VCO selection, then calibrates the VCO. The frequency is recorded in the HAL
+    /// ([`Spirit1HalBlocking::store_base_frequency`]) so that a later [`Spirit1Driver::init`]
+    /// keeps it.
+    ///
+    /// From `SpiritRadioSetFrequencyBase`
+    fn set_base_frequency(&mut self, frequency: u32) -> RadioResult<()> {
+        self.write_base_frequency(frequency)?;
+        self.store_base_frequency(frequency);
+        self.management_wa_vco_calibration()
+    }
+
+    /// Reads back the RF carrier base frequency (Hz) programmed in `SYNT`
+    fn get_base_frequency_from_device(&mut self) -> RadioResult<u32> {
+        let synt: Synt = self.read_register()?;
+        let synth_config: SynthConfig = self.read_register()?;
+
+        Ok(synt.frequency(self.get_xtal_frequency(), synth_config.refdiv))
+    }
+
+    /// Programs `SYNT` and selects the VCO for `frequency`, without calibrating the VCO
+    fn write_base_frequency(&mut self, frequency: u32) -> RadioResult<()> {
+        let (synth_config, synt) =
+            synth_registers(frequency, self.get_xtal_frequency(), self.read_register()?)?;
+
+        self.write_register(synth_config)?;
+        self.write_register(synt)
+    }
+
+    /// Selects the channel `channel` and returns the resulting carrier frequency (Hz)
+    ///
+    /// Returns [`RadioError::FrequencyOutOfBand`] if the carrier leaves the band of the
+    /// base frequency
+    fn set_channel(&mut self, channel: u8) -> RadioResult<u32> {
+        let frequency = self.channel_frequency(channel)?;
+        self.write_register(ChNum::new(channel))?;
+
+        Ok(frequency)
+    }
+
+    /// Currently selected channel
+    fn get_channel(&mut self) -> RadioResult<u8> {
+        Ok(self.read_register::<ChNum>()?.ch_num)
+    }
+
+    /// Carrier frequency (Hz) of `channel` with the programmed channel spacing
+    fn channel_frequency(&mut self, channel: u8) -> RadioResult<u32> {
+        let spacing = self
+            .read_register::<ChSpace>()?
+            .spacing_hz(self.get_xtal_frequency());
+
+        self.carrier_frequency(channel, spacing)
+    }
+
+    /// Sets the channel spacing and returns the actual spacing (Hz) after quantisation to
+    /// `f_XO/2^15` steps.
+    ///
+    /// Returns [`RadioError::ChannelSpaceOutOfRange`] if the spacing does not fit in `CHSPACE` and
+    /// [`RadioError::FrequencyOutOfBand`] if the current channel would leave the band
+    fn set_channel_space(&mut self, spacing: u32) -> RadioResult<u32> {
+        let xtal_frequency = self.get_xtal_frequency();
+        let ch_space =
+            ChSpace::from_hz(spacing, xtal_frequency).ok_or(RadioError::ChannelSpaceOutOfRange)?;
+        let spacing = ch_space.spacing_hz(xtal_frequency);
+
+        let channel = self.get_channel()?;
+        self.carrier_frequency(channel, spacing)?;
+
+        self.write_register(ch_space)?;
+
+        Ok(spacing)
+    }
+
+    /// Carrier frequency (Hz) of `channel` for a channel spacing of `spacing` Hz:
+    /// `f_c = f_base + f_offset + spacing * channel` (*See Equation 3*)
+    ///
+    /// Returns [`RadioError::FrequencyOutOfBand`] if the carrier leaves the band of the
+    /// base frequency
+    fn carrier_frequency(&mut self, channel: u8, spacing: u32) -> RadioResult<u32> {
+        let base = self.get_base_frequency_from_device()?;
+        let band = self.read_register::<Synt>()?.band_select;
+        let offset = self
+            .read_register::<FcOffset>()?
+            .offset_hz(self.get_xtal_frequency());
+
+        let frequency = base as i64 + offset as i64 + spacing as i64 * channel as i64;
+        let frequency = u32::try_from(frequency).map_err(|_| RadioError::FrequencyOutOfBand)?;
+
+        if BandSelect::from_hz(frequency) != Some(band) {
+            return Err(RadioError::FrequencyOutOfBand);
+        }
+
+        Ok(frequency)
+    }
+
+    /// Calibrates the VCO words used in TX and RX and stores them in `RCO_VCO_CALIBR_IN`,
+    /// leaving the automatic VCO calibration disabled.
+    ///
+    /// From `SpiritManagementWaVcoCalibration`
+    fn management_wa_vco_calibration(&mut self) -> RadioResult<()> {
+        let frequency = self.get_base_frequency_from_device()?;
+
+        // Enable the reference divider if the XTAL is between 48 and 52 MHz
+        let mut synth_config: SynthConfig = self.read_register()?;
+        let restore_refdiv = calibration_refdiv(self.get_xtal_frequency(), &synth_config);
+        if restore_refdiv {
+            synth_config.refdiv = true;
+            self.write_register(synth_config)?;
+            self.write_base_frequency(frequency)?;
+        }
+
+        // Increase the VCO current
+        let mut vco_config: VcoConfig = self.read_register()?;
+        vco_config.vco_gen_curr = VCO_GEN_CURR_CALIBRATION;
+        self.write_register(vco_config)?;
+
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.vco_calibration = true;
+        self.write_register(protocol)?;
+
+        let standby = self.read_register::<McState>()?.state == SpiritState::STANDBY;
+        if standby {
+            self.write_command(SpiritCommand::READY)?;
+            self.wait_for_vco_lock(SpiritState::READY)?;
+        }
+
+        self.write_command(SpiritCommand::LOCK_TX)?;
+        self.wait_for_vco_lock(SpiritState::LOCK)?;
+        let vco_calibr_tx = self.read_register::<RcoVcoCalibrOut>()?.vco_calibr_data;
+
+        self.write_command(SpiritCommand::READY)?;
+        self.wait_for_vco_lock(SpiritState::READY)?;
+
+        self.write_command(SpiritCommand::LOCK_RX)?;
+        self.wait_for_vco_lock(SpiritState::LOCK)?;
+        let vco_calibr_rx = self.read_register::<RcoVcoCalibrOut>()?.vco_calibr_data;
+
+        self.write_command(SpiritCommand::READY)?;
+        self.wait_for_vco_lock(SpiritState::READY)?;
+
+        if standby {
+            self.write_command(SpiritCommand::STANDBY)?;
+        }
+
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.vco_calibration = false;
+        self.write_register(protocol)?;
+
+        // Disable the reference divider again
+        if restore_refdiv {
+            let mut synth_config: SynthConfig = self.read_register()?;
+            synth_config.refdiv = false;
+            self.write_register(synth_config)?;
+            self.write_base_frequency(frequency)?;
+        }
+
+        // Restore the VCO current
+        let mut vco_config: VcoConfig = self.read_register()?;
+        vco_config.vco_gen_curr = VCO_GEN_CURR_NOMINAL;
+        self.write_register(vco_config)?;
+
+        let mut calibr_in: RcoVcoCalibrIn = self.read_register()?;
+        calibr_in.vco_calibr_tx = vco_calibr_tx;
+        calibr_in.vco_calibr_rx = vco_calibr_rx;
+        self.write_register(calibr_in)
+    }
+
+    /// Blocking wait for `MC_STATE` to enter `state` while the VCO is being calibrated,
+    /// polling every millisecond.
+    ///
+    /// Returns [`RadioError::VcoCalibration`] if the synthesizer reports a lock error or
+    /// does not reach `state` within [`VCO_LOCK_TIMEOUT_MS`]
+    fn wait_for_vco_lock(&mut self, state: SpiritState) -> RadioResult<()> {
+        for _ in 0..=VCO_LOCK_TIMEOUT_MS {
+            let mut mc_state = [0; McState::LENGTH];
+            self.read_raw(McState::ADDRESS, McState::LENGTH, &mut mc_state)?;
+
+            if vco_lock_state(&mc_state)? == state {
+                return Ok(());
+            }
+
+            self.delay_ms(1);
+        }
+
+        error!("VCO calibration: SpiritState::{} not reached", state);
+        Err(RadioError::VcoCalibration)
+    }
+
+    /// Reads back the radio parameters programmed in the device, after quantisation to the
+    /// register resolution
+    fn read_radio_config(&mut self) -> RadioResult<RadioInitOpts> {
+        let xtal_frequency = self.get_xtal_frequency();
+        let pd_clkdiv = self.read_register::<XoRcoTest>()?.pd_clkdiv;
+
+        let base_frequency = self.get_base_frequency_from_device()?;
+        let offset = self.read_register::<FcOffset>()?.offset_hz(xtal_frequency);
+
+        let modulation: Modulation = self.read_register()?;
+        let fdev: FreqDev0 = self.read_register()?;
+        let flt: ChFlt = self.read_register()?;
+
+        Ok(RadioInitOpts {
+            xtal_offset_ppm: xtal_offset_ppm(offset, base_frequency),
+            channel_space: self.read_register::<ChSpace>()?.spacing_hz(xtal_frequency),
+            channel_number: self.get_channel()?,
+            modulation_select: modulation.mod_type.clone(),
+            data_rate: modulation.data_rate_hz(xtal_frequency, pd_clkdiv),
+            frequency_deviation: fdev.deviation_hz(xtal_frequency),
+            bandwidth: flt.bandwidth_hz(xtal_frequency, pd_clkdiv),
+        })
+    }
+
     /// Initializes the SPIRIT1 analog and digital radio parts according to the
-    /// specified parameters
+    /// specified parameters.
+    ///
+    /// The parameters are checked with [`RadioInitOpts::validate`] before anything is written
     fn init(&mut self, opts: RadioInitOpts) -> RadioResult<()> {
+        let config = opts.init_config(self.get_xtal_frequency(), self.get_base_frequency())?;
+
         // Workaround for V_tune - Set `SEL_TSPLIT` to `1`
         let mut synth_config = SynthConfig::reset_value();
         synth_config.sel_tsplit = true;
         self.write_register(synth_config)?;
 
-        // Calculates the offset respect to RF frequency and according
-        // to xtal_ppm parameter: (xtal_ppm*FBase)/10^6
-        // FIXME: 'attempt to multiply with overflow'
-        // let f_offset = ((opts.xtal_offset_ppm as i32 * self.get_base_frequency() as i32)
-        //     / od_constants::PPM_FACTOR)
-        //     as i32;
-        let (f_offset, _) = self
-            .get_base_frequency()
-            .overflowing_mul(opts.xtal_offset_ppm as u32);
-        let f_offset = f_offset as i32 / od_constants::PPM_FACTOR;
-
-        // TODO: Check the parameters are valid
-        // IS_MODULATION_SELECTED
-        // IS_DATARATE
-        // IS_FREQUENCY_OFFSEt
-        // IS_CHANNEL_SPACE
-        // IS_F_DEV
-
         // Disable the digital, ADC, SMPS reference clock divider if fXO > 24MHz or fXO< 26 MHz
         // TODO: Enter into Standby?
         // self.wait_for_state(SpiritState::STANDBY)?;
 
         let mut rco_test_base: XoRcoTest = self.read_register()?;
-        if self.get_xtal_frequency() < od_constants::DOUBLE_XTAL_THR {
-            rco_test_base.pd_clkdiv = true; // DISABLED
-        } else {
-            rco_test_base.pd_clkdiv = false; // ENABLED
-        }
-        let pd_clkdiv = rco_test_base.pd_clkdiv;
+        rco_test_base.pd_clkdiv = config.pd_clkdiv;
         self.write_register(rco_test_base)?;
 
         // Goes into READY state
         self.wait_for_ready()?;
 
-        // Calculates the FC_OFFSET parameter and cast as signed int: FOffsetTmp = (Fxtal/2^18)*FC_OFFSET
-        let xtal_offset_factor: i16 =
-            ((f_offset * od_constants::FBASE_DIVIDER) / self.get_xtal_frequency() as i32) as i16;
-        let fc_offset = FcOffset::new(xtal_offset_factor);
-
-        let channel_spacing: ChSpace =
-            ChSpace::new(((opts.channel_space << 9) / (self.get_xtal_frequency() >> 6) + 1) as u8);
-
-        // SpiritManagementWaTRxFcMem(pxSRadioInitStruct->lFrequencyBase);
-
         // 2nd order DEM algorithm enabling
         let mut if_offset_dig: IfOffsetDig = self.read_register()?;
         if_offset_dig.if_offset_dig &= !0x02;
         self.write_register(if_offset_dig)?;
 
-        // TODO: Check channel center frequency is in one of the possible ranges
-
-        // Calculates the data rate mantissa and exponent
-        let (m, e) =
-            Modulation::calculate_data_rate(opts.data_rate, pd_clkdiv, self.get_xtal_frequency());
-        let modulation_reg = Modulation::new(false, opts.modulation_select, e, m);
-
-        let mut fdev: FreqDev0 = self.read_register()?;
-        (fdev.fdev_e, fdev.fdev_m) =
-            FreqDev0::calculate_fdev(opts.frequency_deviation, self.get_xtal_frequency());
-
-        let flt = ChFlt::calculate(opts.bandwidth, pd_clkdiv, self.get_xtal_frequency());
-
-        let if_offset: f32 = (3.0 * 480140.0) / (self.get_xtal_frequency() >> 12) as f32 - 64.0; // #1035-D ??
-                                                                                                 // let if_offset_ana = if_offset.round();
-        let if_offset_ana = unsafe { if_offset.to_int_unchecked::<u32>() } as f32;
-
-        let if_offset_dig = if self.get_xtal_frequency() < od_constants::DOUBLE_XTAL_THR {
-            if_offset_ana
-        } else {
-            // ((3.0 * 480140.0) / ( self.get_xtal_frequency() >> 13 ) as f32 - 64.0 ).round()
-            (unsafe {
-                ((3.0 * 480140.0) / (self.get_xtal_frequency() >> 13) as f32 - 64.0)
-                    .to_int_unchecked::<u32>()
-            } as f32)
-        };
-
-        self.write_register(IfOffsetAna::new(if_offset_ana as u8))?;
+        self.write_register(config.if_offset_ana)?;
 
         // Set the XTal Flag
-        let xtal_flag_fn = |freq: u32| freq >= 25_000_000;
-
-        // true for 26, false for 24
-        let frequency_select = if self.get_xtal_frequency() > od_constants::DOUBLE_XTAL_THR {
-            xtal_flag_fn(self.get_xtal_frequency() / 2)
-        } else {
-            xtal_flag_fn(self.get_xtal_frequency())
-        };
-
         let mut conf: AnaFuncConf = self.read_register()?;
-        conf.frequency_select = FrequencySelect::from(frequency_select);
+        conf.frequency_select = config.frequency_select;
         self.write_register(conf)?;
 
         // Set Channel Number
-        self.write_register(ChNum::new(opts.channel_number))?;
+        self.write_register(config.ch_num)?;
 
         // Set the Analog Radio Registers
-        self.write_register(channel_spacing)?;
-        self.write_register(fc_offset)?;
-        self.write_register(IfOffsetDig::new(if_offset_dig as u8))?;
+        self.write_register(config.fc_offset)?;
+        self.write_register(config.if_offset_dig)?;
 
-        // Set the Digital Radio Registers
-        self.write_register(modulation_reg)?;
-        self.write_register(fdev)?;
-        self.write_register(flt)?;
+        // Set the channel spacing and the Digital Radio Registers
+        self.write_modem_config(config.modem)?;
 
         // Enable the freeze option of the AFC on the SYNC word
         let mut afc2: Afc2 = self.read_register()?;
@@ -263,13 +587,68 @@ pub trait Spirit1Driver: Spirit1HalBlocking {
         self.write_register(afc2)?;
 
         // Set the IQC correction optimal value
-        self.write_raw(0x99, &mut [0x80, 0xE3])?;
-        self.write_raw(0xBC, &mut [0x22])?;
+        for (address, value) in IQC_CORRECTION_WRITES {
+            self.write_raw(address, value)?;
+        }
+
+        // Program the base frequency, including the VCO calibration workaround
+        self.set_base_frequency(self.get_base_frequency())?;
 
         Ok(())
     }
 
+    /// Writes the channel spacing, modulation, data rate, frequency deviation and channel
+    /// filter registers, see [`RadioInitOpts::modem_config`]
+    fn write_modem_config(&mut self, config: ModemConfig) -> RadioResult<()> {
+        self.write_register(config.ch_space)?;
+        self.write_register(config.modulation)?;
+
+        let mut fdev: FreqDev0 = self.read_register()?;
+        (fdev.fdev_e, fdev.fdev_m) = config.fdev;
+        self.write_register(fdev)?;
+
+        self.write_register(config.flt)
+    }
+
+    /// Transmit `buf`, returning once `TX_DATA_SENT` is raised.
+    ///
+    /// Packets longer than the TX FIFO are sent with [`Spirit1Driver::tx_stream`], also
+    /// without timeout
     fn tx_blocking(&mut self, buf: &[u8]) -> RadioResult<usize> {
+        if buf.len() > Self::MAX_FIFO_LENGTH {
+            return self.tx_stream(buf, u32::MAX, TimeoutRecovery::None);
+        }
+
+        let tx_len = self.start_tx(buf)?;
+
+        // IMPROVEMENT: Update this behavior a bit, there's also the IRQ GPIO
+        let mut tx_data_sent = false;
+        while !tx_data_sent {
+            let irq_status = self.irq_poll(InterruptEvent::TxDataSent)?;
+            debug!("{}", irq_status);
+            tx_data_sent = irq_status.contains(InterruptEvent::TxDataSent);
+            self.delay_ms(1000);
+        }
+
+        Ok(tx_len)
+    }
+
+    /// [`Spirit1Driver::tx_blocking`] failing with [`RadioError::Timeout`] if `TX_DATA_SENT`
+    /// is not raised within `timeout_ms`. `recovery` is applied before returning the error.
+    ///
+    /// Packets of any length are sent with [`Spirit1Driver::tx_stream`]
+    fn tx_blocking_timeout(
+        &mut self,
+        buf: &[u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<usize> {
+        self.tx_stream(buf, timeout_ms, recovery)
+    }
+
+    /// Load `buf` in the TX FIFO and start the transmission, returning the number of bytes
+    /// to be sent (at most [`Spirit1Driver::MAX_FIFO_LENGTH`])
+    fn start_tx(&mut self, buf: &[u8]) -> RadioResult<usize> {
         self.write_command(SpiritCommand::FLUSH_TX_FIFO)?;
 
         // Check TX FIFO len
@@ -284,82 +663,509 @@ pub trait Spirit1Driver: Spirit1HalBlocking {
         // Set payload length
         self.write_register(PcktLen::new(tx_len as u16))?;
 
-        // TODO: if not in TX state (SpiritManagementWaCmdStrobeTx)
-        {
-            let load_cap = if self.get_frequency_band() < BandSelect::High {
-                AdditionalLoadCapacitors::Cap3p6
-            } else {
-                AdditionalLoadCapacitors::Cap0
-            };
+        self.strobe_tx()?;
 
-            let mut pa_power: PaPower = self.read_register()?;
-            pa_power.additional_load_capacitors = load_cap;
-            self.write_register(pa_power)?;
+        Ok(tx_len)
+    }
 
-            // Some magical undocumented register - apparently it enabled the VCO_L buffer
-            self.write_raw(0xA9, &[0x11])?;
+    /// Transmit `buf` of up to 65535 bytes, refilling the TX FIFO whenever it drains to the
+    /// `FIFO_CONFIG` almost empty threshold.
+    ///
+    /// Requires [`InterruptEvent::TxDataSent`] to be enabled in `IRQ_MASK`, and
+    /// [`InterruptEvent::TxFifoError`] to detect underflows. Fails with
+    /// [`RadioError::Timeout`] if `TX_DATA_SENT` is not raised within `timeout_ms`, `recovery`
+    /// being applied before returning the error
+    fn tx_stream(
+        &mut self,
+        buf: &[u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<usize> {
+        let packet_length = u16::try_from(buf.len()).map_err(|_| RadioError::PacketTooLong)?;
+        let threshold = self.read_register::<FifoConfig>()?.tx_ae_threshold as usize;
 
-            // not sure why but its what the OEM driver does
-            self.write_raw(PmConfig::ADDRESS + 1, &[0x20])?;
-        }
+        self.write_command(SpiritCommand::FLUSH_TX_FIFO)?;
 
-        self.write_command(SpiritCommand::TX)?;
+        let mut written = buf.len().min(Self::MAX_FIFO_LENGTH);
+        self.write_raw(Self::LINEAR_FIFO_ADDRESS, &buf[..written])?;
+        self.write_register(PcktLen::new(packet_length))?;
 
-        // IMPROVEMENT: Update this behavior a bit, there's also the IRQ GPIO
-        let mut tx_data_sent = false;
-        while !tx_data_sent {
-            let irq_status: IrqStatus = self.read_register()?;
-            debug!("{}", irq_status);
-            tx_data_sent = irq_status.is_set(InterruptEvent::TxDataSent);
-            self.delay_ms(1000);
+        self.strobe_tx()?;
+
+        let mut elapsed_ms = 0;
+        loop {
+            // An underflow ends the transmission, raising `TX_DATA_SENT` as well
+            let irq_status =
+                self.irq_poll([InterruptEvent::TxFifoError, InterruptEvent::TxDataSent])?;
+            if irq_status.contains(InterruptEvent::TxFifoError) {
+                self.write_command(SpiritCommand::FLUSH_TX_FIFO)?;
+                return Err(RadioError::FifoError);
+            }
+
+            if irq_status.contains(InterruptEvent::TxDataSent) {
+                return Ok(buf.len());
+            }
+
+            if written < buf.len() {
+                let elements = self
+                    .read_register::<LinearFifoStatusTxElements>()?
+                    .elem_txfifo as usize;
+
+                if elements <= threshold {
+                    let chunk = (buf.len() - written).min(Self::MAX_FIFO_LENGTH - elements);
+                    self.write_raw(Self::LINEAR_FIFO_ADDRESS, &buf[written..written + chunk])?;
+                    written += chunk;
+                    continue;
+                }
+            }
+
+            if elapsed_ms >= timeout_ms {
+                return self.timed_out(recovery);
+            }
+
+            self.delay_ms(1);
+            elapsed_ms += 1;
         }
+    }
 
-        Ok(tx_len)
+    /// Receive a packet of up to `buffer.len()` bytes, draining the RX FIFO whenever it fills
+    /// up to the `FIFO_CONFIG` almost full threshold. Reception restarts if the packet is
+    /// discarded or the RX timeout expires.
+    ///
+    /// Requires [`InterruptEvent::RxDataReady`] to be enabled in `IRQ_MASK`. Fails with
+    /// [`RadioError::BufferTooSmall`] (aborting the reception) if the packet does not fit, and
+    /// with [`RadioError::Timeout`] if no packet is received within `timeout_ms`, `recovery`
+    /// being applied before returning the error
+    fn rx_stream(
+        &mut self,
+        buffer: &mut [u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<usize> {
+        let threshold = self.read_register::<FifoConfig>()?.rx_af_threshold as usize;
+
+        self.write_command(SpiritCommand::FLUSH_RX_FIFO)?;
+        self.strobe_rx()?;
+
+        let mut received = 0;
+        let mut elapsed_ms = 0;
+        loop {
+            let irq_status = self.irq_poll(RX_EVENTS.with(InterruptEvent::RxFifoError))?;
+
+            if irq_status.contains(InterruptEvent::RxDataDiscarded)
+                || irq_status.contains(InterruptEvent::TimerRxTimeout)
+            {
+                received = 0;
+                self.write_command(SpiritCommand::FLUSH_RX_FIFO)?;
+                self.strobe_rx()?;
+                continue;
+            }
+
+            if irq_status.contains(InterruptEvent::RxFifoError) {
+                self.write_command(SpiritCommand::SABORT)?;
+                self.write_command(SpiritCommand::FLUSH_RX_FIFO)?;
+                return Err(RadioError::FifoError);
+            }
+
+            let rx_data_ready = irq_status.contains(InterruptEvent::RxDataReady);
+            let elements = self
+                .read_register::<LinearFifoStatusRxElements>()?
+                .elem_rxfifo as usize;
+
+            if rx_data_ready || elements >= threshold {
+                if received + elements > buffer.len() {
+                    self.write_command(SpiritCommand::SABORT)?;
+                    self.write_command(SpiritCommand::FLUSH_RX_FIFO)?;
+                    return Err(RadioError::BufferTooSmall);
+                }
+
+                self.read_raw(Self::LINEAR_FIFO_ADDRESS, elements, &mut buffer[received..])?;
+                received += elements;
+            }
+
+            if rx_data_ready {
+                return Ok(received);
+            }
+
+            if elapsed_ms >= timeout_ms {
+                return self.timed_out(recovery);
+            }
+
+            if elements < threshold {
+                self.delay_ms(1);
+                elapsed_ms += 1;
+            }
+        }
     }
 
-    fn rx_blocking(&mut self, buffer: &mut [u8; 96]) -> RadioResult<usize> {
-        // TODO: if not in RX state (SpiritManagementWaCmdStrobeRx)
-        let strobe_rx = |s: &mut Self| -> RadioResult<()> {
-            s.write_raw(PmConfig::ADDRESS + 1, &[0x98])?;
+    /// `SpiritManagementWaCmdStrobeTx` followed by the `TX` command
+    fn strobe_tx(&mut self) -> RadioResult<()> {
+        // TODO: if not in TX state (SpiritManagementWaCmdStrobeTx)
+        {
+            // SpiritManagementWaTRxFcMem: use the band actually programmed in SYNT
+            let band = self.read_register::<Synt>()?.band_select;
+
+            let mut pa_power: PaPower = self.read_register()?;
+            pa_power.additional_load_capacitors = tx_load_capacitors(band);
+            self.write_register(pa_power)?;
 
-            let mut pa_power: PaPower = s.read_register()?;
-            pa_power.additional_load_capacitors = AdditionalLoadCapacitors::Cap0;
-            s.write_register(pa_power)?;
+            for (address, value) in WA_STROBE_TX_WRITES {
+                self.write_raw(address, &[value])?;
+            }
+        }
 
-            s.write_command(SpiritCommand::RX)?;
+        self.write_command(SpiritCommand::TX)?;
 
-            Ok(())
-        };
+        Ok(())
+    }
 
-        strobe_rx(self)?;
+    /// Receive a packet into `buffer`, re-entering RX whenever the packet is discarded or
+    /// the RX timeout expires.
+    ///
+    /// The packet must fit in the RX FIFO, see [`Spirit1Driver::rx_stream`] for longer packets
+    fn rx_blocking<'a>(&mut self, buffer: &'a mut [u8]) -> RadioResult<RxPacket<'a>> {
+        self.strobe_rx()?;
 
         // IMPROVEMENT: Update this behavior a bit, there's also the IRQ GPIO
-        // TODO: If RX_DATA_DISCARDED or RX_TIMEOUT, re-enter RX strobe (the above code)
         let mut rx_data_received = false;
         while !rx_data_received {
-            let irq_status: IrqStatus = self.read_register()?;
+            let irq_status = self.irq_poll(RX_EVENTS)?;
 
-            rx_data_received = irq_status.is_set(InterruptEvent::RxDataReady);
+            rx_data_received = irq_status.contains(InterruptEvent::RxDataReady);
 
-            if irq_status.is_set(InterruptEvent::RxDataDiscarded)
-                || irq_status.is_set(InterruptEvent::TimerRxTimeout)
+            if irq_status.contains(InterruptEvent::RxDataDiscarded)
+                || irq_status.contains(InterruptEvent::TimerRxTimeout)
             {
-                strobe_rx(self)?;
+                self.strobe_rx()?;
             }
 
             self.delay_ms(1); // random, should be driven from IRQ IO
         }
 
-        let rx_fifo_len = self
-            .read_register::<LinearFifoStatusRxElements>()?
-            .elem_rxfifo as usize;
+        self.read_rx_packet(buffer)
+    }
+
+    /// [`Spirit1Driver::rx_blocking`] failing with [`RadioError::Timeout`] if no packet is
+    /// received within `timeout_ms`. `recovery` is applied before returning the error
+    fn rx_blocking_timeout<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        timeout_ms: u32,
+        recovery: TimeoutRecovery,
+    ) -> RadioResult<RxPacket<'a>> {
+        self.strobe_rx()?;
+
+        let mut elapsed_ms = 0;
+        loop {
+            let irq_status = self.irq_poll(RX_EVENTS)?;
+            if irq_status.contains(InterruptEvent::RxDataReady) {
+                return self.read_rx_packet(buffer);
+            }
+
+            if elapsed_ms >= timeout_ms {
+                return self.timed_out(recovery);
+            }
+
+            if irq_status.contains(InterruptEvent::RxDataDiscarded)
+                || irq_status.contains(InterruptEvent::TimerRxTimeout)
+            {
+                self.strobe_rx()?;
+            }
+
+            self.delay_ms(1);
+            elapsed_ms += 1;
+        }
+    }
+
+    /// `SpiritManagementWaCmdStrobeRx` followed by the `RX` command
+    fn strobe_rx(&mut self) -> RadioResult<()> {
+        // TODO: if not in RX state (SpiritManagementWaCmdStrobeRx)
+        let (address, value) = WA_STROBE_RX_WRITE;
+        self.write_raw(address, &[value])?;
+
+        let mut pa_power: PaPower = self.read_register()?;
+        pa_power.additional_load_capacitors = AdditionalLoadCapacitors::Cap0;
+        self.write_register(pa_power)?;
+
+        self.write_command(SpiritCommand::RX)?;
+
+        Ok(())
+    }
+
+    /// Read the received packet information followed by the packet out of the RX FIFO.
+    ///
+    /// Must be called right after `RX_DATA_READY`, before the next packet is received
+    fn read_rx_packet<'a>(&mut self, buffer: &'a mut [u8]) -> RadioResult<RxPacket<'a>> {
+        let mut status = [0; RX_PACKET_STATUS_LENGTH];
+        self.read_raw(
+            RX_PACKET_STATUS_ADDRESS,
+            RX_PACKET_STATUS_LENGTH,
+            &mut status,
+        )?;
+
+        let len = self.read_rx_fifo(buffer)?;
+
+        RxPacket::from_status(&buffer[..len], &status, self.get_xtal_frequency())
+    }
+
+    /// Read the received packet out of the RX FIFO.
+    ///
+    /// Fails with [`RadioError::BufferTooSmall`] (flushing the RX FIFO) if the packet does
+    /// not fit in `buffer`
+    fn read_rx_fifo(&mut self, buffer: &mut [u8]) -> RadioResult<usize> {
+        let Some(rx_fifo_len) = rx_fifo_length(self.read_register()?, buffer) else {
+            self.write_command(SpiritCommand::FLUSH_RX_FIFO)?;
+            return Err(RadioError::BufferTooSmall);
+        };
+
         self.read_raw(Self::LINEAR_FIFO_ADDRESS, rx_fifo_len, buffer)?;
 
         Ok(rx_fifo_len)
     }
+
+    /// Apply `recovery` and return [`RadioError::Timeout`] with the last observed `MC_STATE`
+    fn timed_out<T>(&mut self, recovery: TimeoutRecovery) -> RadioResult<T> {
+        let mc_state: McState = self.read_register()?;
+        debug!("timeout in SpiritState::{}", mc_state.state);
+
+        if let Some(command) = recovery.command() {
+            self.write_command(command)?;
+        }
+
+        Err(RadioError::Timeout(mc_state))
+    }
+}
+
+/// Compute the RSSI threshold for a given dBm.
+///
+/// Valid range is `-130 ≤ dBm ≤ 2`. Values outside this range will be clamped
+pub(crate) fn compute_rssi_threshold(dbm: i32) -> u8 {
+    trace!("Compute RSSI: {}", dbm);
+    let dbm_clamped = dbm.max(-130).min(2);
+
+    2 * (dbm_clamped as u8).overflowing_add(130).0
+}
+
+/// `SpiritManagementWaCmdStrobeTx` raw writes: the undocumented register `0xA9` enabling the
+/// VCO_L buffer, then `PM_CONFIG[1]` as the OEM driver does
+pub(crate) const WA_STROBE_TX_WRITES: [(u8, u8); 2] = [(0xA9, 0x11), (PmConfig::ADDRESS + 1, 0x20)];
+
+/// `SpiritManagementWaCmdStrobeRx` raw write of `PM_CONFIG[1]`
+pub(crate) const WA_STROBE_RX_WRITE: (u8, u8) = (PmConfig::ADDRESS + 1, 0x98);
+
+/// Additional PA load capacitors used in TX for `band`, from `SpiritManagementWaTRxFcMem`
+pub(crate) fn tx_load_capacitors(band: BandSelect) -> AdditionalLoadCapacitors {
+    if band < BandSelect::High {
+        AdditionalLoadCapacitors::Cap3p6
+    } else {
+        AdditionalLoadCapacitors::Cap0
+    }
+}
+
+/// First register of the received packet information, `RX_PCKT_INFO`
+pub(crate) const RX_PACKET_STATUS_ADDRESS: u8 = RxPacketInfo::ADDRESS;
+
+/// Length of the received packet information, from `RX_PCKT_INFO` to `RX_ADDR_FIELD`, read
+/// in a single burst
+pub(crate) const RX_PACKET_STATUS_LENGTH: usize =
+    (RxAddrField::ADDRESS - RX_PACKET_STATUS_ADDRESS) as usize + RxAddrField::LENGTH;
+
+/// Register `R` out of the received packet information read at `RX_PACKET_STATUS_ADDRESS`
+fn rx_packet_status_field<R>(status: &[u8; RX_PACKET_STATUS_LENGTH]) -> RadioResult<R>
+where
+    R: Register<WORD> + ReadableRegister<WORD>,
+    [(); R::LENGTH]: Sized,
+{
+    let offset = (R::ADDRESS - RX_PACKET_STATUS_ADDRESS) as usize;
+    let mut bytes = [0; R::LENGTH];
+    bytes.copy_from_slice(&status[offset..offset + R::LENGTH]);
+
+    Ok(R::from_bytes(&bytes)?)
+}
+
+/// Intermediate frequency offsets `(IF_OFFSET_ANA, IF_OFFSET_DIG)` for a crystal of
+/// `xtal_frequency` Hz, from `SpiritRadioInit`
+pub(crate) fn if_offsets(xtal_frequency: u32) -> (u8, u8) {
+    let if_offset: f32 = (3.0 * 480140.0) / (xtal_frequency >> 12) as f32 - 64.0; // #1035-D ??
+    let if_offset_ana = unsafe { if_offset.to_int_unchecked::<u32>() as u8 };
+
+    let if_offset_dig = if xtal_frequency < od_constants::DOUBLE_XTAL_THR {
+        if_offset_ana
+    } else {
+        let if_offset: f32 = (3.0 * 480140.0) / (xtal_frequency >> 13) as f32 - 64.0;
+        unsafe { if_offset.to_int_unchecked::<u32>() as u8 }
+    };
+
+    (if_offset_ana, if_offset_dig)
+}
+
+/// `ANA_FUNC_CONF` crystal flag: 26 MHz for a digital clock of 25 MHz or more, 24 MHz below
+pub(crate) fn frequency_select(xtal_frequency: u32) -> FrequencySelect {
+    let digital_frequency = if xtal_frequency > od_constants::DOUBLE_XTAL_THR {
+        xtal_frequency / 2
+    } else {
+        xtal_frequency
+    };
+
+    FrequencySelect::from(digital_frequency >= 25_000_000)
+}
+
+/// Crystal offset (ppm) compensated by an `FC_OFFSET` of `offset` Hz at `base_frequency` Hz
+pub(crate) fn xtal_offset_ppm(offset: i32, base_frequency: u32) -> i16 {
+    ((offset as i64 * od_constants::PPM_FACTOR as i64) / base_frequency as i64) as i16
+}
+
+/// `SYNTH_CONFIG` and `SYNT` programming the base frequency `frequency` (Hz) with a crystal of
+/// `xtal_frequency` Hz, the VCO being selected against the threshold of the band.
+/// `synth_config` is the current register value, whose reference divider is kept
+pub(crate) fn synth_registers(
+    frequency: u32,
+    xtal_frequency: u32,
+    mut synth_config: SynthConfig,
+) -> RadioResult<(SynthConfig, Synt)> {
+    let synt = Synt::from_frequency(frequency, xtal_frequency, synth_config.refdiv)
+        .ok_or(RadioError::FrequencyOutOfBand)?;
+
+    let vco_h = frequency >= synt.band_select.vco_h_threshold();
+    synth_config.vco_h_sel = vco_h;
+    synth_config.vco_l_sel = !vco_h;
+
+    Ok((synth_config, synt))
+}
+
+/// `VCO_CONFIG.VCO_GEN_CURR` while the VCO is calibrated, from `SpiritManagementWaVcoCalibration`
+pub(crate) const VCO_GEN_CURR_CALIBRATION: u8 = 0x19;
+
+/// `VCO_CONFIG.VCO_GEN_CURR` restored after the VCO calibration
+pub(crate) const VCO_GEN_CURR_NOMINAL: u8 = 0x11;
+
+/// Whether the reference divider must be enabled for the VCO calibration (and disabled again
+/// afterwards): crystals between 48 and 52 MHz, the divider not being already enabled
+pub(crate) fn calibration_refdiv(xtal_frequency: u32, synth_config: &SynthConfig) -> bool {
+    xtal_frequency > od_constants::DOUBLE_XTAL_THR && !synth_config.refdiv
+}
+
+/// State out of the raw `MC_STATE` bytes read during the VCO calibration.
+///
+/// The lock error code is not a valid `SpiritState`, it is checked on `MC_STATE[0]` first and
+/// reported as [`RadioError::VcoCalibration`]
+pub(crate) fn vco_lock_state(mc_state: &[u8; McState::LENGTH]) -> RadioResult<SpiritState> {
+    if mc_state[1] >> 1 == MC_STATE_LOCK_ERROR {
+        return Err(RadioError::VcoCalibration);
+    }
+
+    Ok(McState::from_bytes(mc_state)?.state)
+}
+
+/// Number of bytes to read out of the RX FIFO into `buffer`, `None` if they do not fit
+pub(crate) fn rx_fifo_length(elements: LinearFifoStatusRxElements, buffer: &[u8]) -> Option<usize> {
+    let rx_fifo_len = elements.elem_rxfifo as usize;
+    (rx_fifo_len <= buffer.len()).then_some(rx_fifo_len)
+}
+
+/// IQC correction optimal values written by `SpiritRadioInit`
+pub(crate) const IQC_CORRECTION_WRITES: [(u8, &[u8]); 2] = [(0x99, &[0x80, 0xE3]), (0xBC, &[0x22])];
+
+/// Packet returned by the receive functions, with the information the packet handler
+/// stored about it
+#[derive(Debug, defmt::Format)]
+pub struct RxPacket<'a> {
+    /// Received bytes
+    pub payload: &'a [u8],
+    /// RSSI of the packet in dBm
+    pub rssi_dbm: i16,
+    /// Sync quality indicator
+    pub sqi: u8,
+    /// Preamble quality indicator
+    pub pqi: u8,
+    /// AGC word at the end of the packet
+    pub agc_word: u8,
+    /// Carrier sense indication
+    pub carrier_sense: bool,
+    /// Frequency correction applied by the AFC in Hz
+    pub afc_hz: i32,
+    /// CRC field of the packet, `0` if `CrcMode::NoCrc`
+    pub crc: u32,
+    /// Control field of the packet, control byte n.0 being the least significant byte
+    pub control: u32,
+    /// Source address, only sent in STack packets
+    pub source: u8,
+    /// Destination address
+    pub destination: u8,
+    /// Sequence number, only sent in STack packets
+    pub sequence_number: u8,
+    /// `NO_ACK` field, only sent in STack packets
+    pub no_ack: bool,
+}
+
+impl<'a> RxPacket<'a> {
+    /// Packet of `payload` with the information read at `RX_PACKET_STATUS_ADDRESS`, with a
+    /// crystal of `xtal_frequency` Hz
+    pub(crate) fn from_status(
+        payload: &'a [u8],
+        status: &[u8; RX_PACKET_STATUS_LENGTH],
+        xtal_frequency: u32,
+    ) -> RadioResult<Self> {
+        let info: RxPacketInfo = rx_packet_status_field(status)?;
+        let afc: AfcCorr = rx_packet_status_field(status)?;
+        let link_qualif: LinkQualif = rx_packet_status_field(status)?;
+        let rssi: RssiLevel = rx_packet_status_field(status)?;
+        let crc: CrcField = rx_packet_status_field(status)?;
+        let control: RxCtrlField = rx_packet_status_field(status)?;
+        let address: RxAddrField = rx_packet_status_field(status)?;
+
+        Ok(RxPacket {
+            payload,
+            rssi_dbm: rssi.dbm(),
+            sqi: link_qualif.sqi,
+            pqi: link_qualif.pqi,
+            agc_word: link_qualif.agc_word,
+            carrier_sense: link_qualif.carrier_sense,
+            afc_hz: afc.afc_hz(xtal_frequency),
+            crc: crc.crc(),
+            control: control.control(),
+            source: address.source,
+            destination: address.destination,
+            sequence_number: info.rx_seq_num,
+            no_ack: info.nack_rx,
+        })
+    }
+}
+
+/// Channel spacing and modem registers of a [`RadioInitOpts`], see
+/// [`RadioInitOpts::modem_config`]
+#[derive(defmt::Format)]
+pub struct ModemConfig {
+    /// Channel spacing
+    pub ch_space: ChSpace,
+    /// Modulation type and data rate
+    pub modulation: Modulation,
+    /// `FDEV0` exponent and mantissa
+    pub fdev: (u8, u8),
+    /// Channel filter bandwidth
+    pub flt: ChFlt,
+}
+
+/// Radio registers programmed by [`Spirit1Driver::init`], see [`RadioInitOpts::init_config`]
+pub(crate) struct InitConfig {
+    /// `XO_RCO_TEST.PD_CLKDIV`, the digital clock divider being disabled below 26 MHz
+    pub pd_clkdiv: bool,
+    /// Analog intermediate frequency offset
+    pub if_offset_ana: IfOffsetAna,
+    /// Digital intermediate frequency offset
+    pub if_offset_dig: IfOffsetDig,
+    /// `ANA_FUNC_CONF` crystal flag
+    pub frequency_select: FrequencySelect,
+    /// Selected channel
+    pub ch_num: ChNum,
+    /// Crystal offset compensation
+    pub fc_offset: FcOffset,
+    /// Channel spacing and modem registers
+    pub modem: ModemConfig,
 }
 
 /// Main radio parameters
+#[derive(Clone, Debug, defmt::Format)]
 pub struct RadioInitOpts {
     /// Specifies the offset frequency (in ppm) to compensate crystal
     /// inaccuracy expressed as signed value
@@ -384,11 +1190,12 @@ pub struct RadioInitOpts {
     pub data_rate: u32,
     /// Specifies the frequency deviation expressed in Hz.
     ///
-    /// This parameter can be in the range: `[F_Xo*8/2^18, F_Xo*7680/2^18]` Hz
+    /// This parameter can be in the range: `[F_Xo*8/2^19, F_Xo*7680/2^19]` Hz
     pub frequency_deviation: u32,
     /// Specifies the channel filter bandwidth expressed in Hz.
     ///
-    /// This parameter can be in the range between 1100 and 800100 Hz
+    /// This parameter can be in the range between 1100 and 800100 Hz for a digital clock
+    /// of 26 MHz, scaled by `F_dig/26 MHz` (`F_dig` being `F_Xo`, or `F_Xo/2` above 30 MHz)
     pub bandwidth: u32,
 }
 
@@ -401,49 +1208,405 @@ impl Default for RadioInitOpts {
             modulation_select: ModulationType::Fsk2,
             data_rate: 38_400,
             frequency_deviation: 20_000,
-            bandwidth: 1_005_000,
+            bandwidth: 100_500,
         }
     }
 }
 
-// TODO Check if this outputs the correct values
-pub const fn calculate_rx_timeout(timeout_ms: u32, xtal_frequency: u32) -> (u8, u8) {
-    let xtal = if xtal_frequency > od_constants::DOUBLE_XTAL_THR {
-        xtal_frequency >> 1
-    } else {
-        xtal_frequency
-    };
+impl RadioInitOpts {
+    /// Frequency offset (Hz) compensating the crystal inaccuracy:
+    /// `xtal_offset_ppm * f_base / 10^6`
+    pub fn frequency_offset(&self, base_frequency: u32) -> i32 {
+        ((self.xtal_offset_ppm as i64 * base_frequency as i64) / od_constants::PPM_FACTOR as i64)
+            as i32
+    }
 
-    let n = timeout_ms.overflowing_mul(xtal).0 / 1210000; // imma gonna be sick
+    /// `FC_OFFSET` compensating the crystal inaccuracy at `base_frequency` Hz with a crystal of
+    /// `xtal_frequency` Hz: `FC_OFFSET = f_offset * 2^18 / F_Xo`
+    pub fn fc_offset(&self, xtal_frequency: u32, base_frequency: u32) -> FcOffset {
+        let f_offset = self.frequency_offset(base_frequency);
+        let xtal_offset_factor =
+            (f_offset as i64 * od_constants::FBASE_DIVIDER as i64) / xtal_frequency as i64;
 
-    if n / 0xFF > 0xFD {
-        return (0xFF, 0xFF);
-    } else {
-        let mut prescaler = n / 0xFF;
-        let mut counter = n / prescaler;
-
-        let err = (counter
-            .overflowing_mul(prescaler)
-            .0
-            .overflowing_mul(1210000)
-            .0
-            / xtal)
-            .overflowing_sub(timeout_ms)
-            .0;
-
-        if counter < 255 {
-            if (((counter + 1) * prescaler * 1210000) / xtal - timeout_ms) < err {
-                counter += 1;
-            }
+        FcOffset::new(xtal_offset_factor as i16)
+    }
+
+    /// Channel spacing and modem registers for a crystal of `xtal_frequency` Hz, `pd_clkdiv`
+    /// telling whether the digital clock divider is disabled (`XO_RCO_TEST.PD_CLKDIV`)
+    pub fn modem_config(&self, xtal_frequency: u32, pd_clkdiv: bool) -> RadioResult<ModemConfig> {
+        let ch_space = ChSpace::from_hz(self.channel_space, xtal_frequency)
+            .ok_or(RadioError::ChannelSpaceOutOfRange)?;
+
+        // Calculates the data rate mantissa and exponent
+        let (m, e) = Modulation::calculate_data_rate(self.data_rate, pd_clkdiv, xtal_frequency);
+
+        Ok(ModemConfig {
+            ch_space,
+            modulation: Modulation::new(false, self.modulation_select.clone(), e, m),
+            fdev: FreqDev0::calculate_fdev(self.frequency_deviation, xtal_frequency),
+            flt: ChFlt::calculate(self.bandwidth, pd_clkdiv, xtal_frequency),
+        })
+    }
+
+    /// Validates the parameters and computes the registers programmed by
+    /// [`Spirit1Driver::init`] for a crystal of `xtal_frequency` Hz and a base frequency of
+    /// `base_frequency` Hz
+    pub(crate) fn init_config(
+        &self,
+        xtal_frequency: u32,
+        base_frequency: u32,
+    ) -> RadioResult<InitConfig> {
+        self.validate(xtal_frequency, base_frequency)?;
+
+        let pd_clkdiv = xtal_frequency < od_constants::DOUBLE_XTAL_THR;
+        let (if_offset_ana, if_offset_dig) = if_offsets(xtal_frequency);
+
+        Ok(InitConfig {
+            pd_clkdiv,
+            if_offset_ana: IfOffsetAna::new(if_offset_ana),
+            if_offset_dig: IfOffsetDig::new(if_offset_dig),
+            frequency_select: frequency_select(xtal_frequency),
+            ch_num: ChNum::new(self.channel_number),
+            fc_offset: self.fc_offset(xtal_frequency, base_frequency),
+            modem: self.modem_config(xtal_frequency, pd_clkdiv)?,
+        })
+    }
+
+    /// Checks the parameters against the SPIRIT1 limits for a crystal of `xtal_frequency` Hz
+    /// and a base frequency of `base_frequency` Hz, including that the carrier of the
+    /// selected channel stays in the band of the base frequency
+    pub fn validate(&self, xtal_frequency: u32, base_frequency: u32) -> RadioResult<()> {
+        // IS_DATARATE
+        if !(100..=500_000).contains(&self.data_rate) {
+            return Err(RadioError::DataRateOutOfRange);
+        }
+
+        // IS_F_DEV, the limits of `FreqDev0::deviation_hz`
+        let xtal = xtal_frequency as u64;
+        let fdev = self.frequency_deviation as u64;
+        if fdev < (xtal * 8) >> 19 || fdev > (xtal * 7680) >> 19 {
+            return Err(RadioError::DeviationOutOfRange);
         }
 
-        prescaler -= 1;
-        if counter > 1 {
-            counter -= 1;
+        // IS_CH_BW, the limits at 26 MHz scaled to the digital clock as in `ChFlt::bandwidth_hz`
+        let f_dig = if xtal_frequency < od_constants::DOUBLE_XTAL_THR {
+            xtal
         } else {
-            counter = 1;
+            xtal / 2
+        };
+        let bandwidth = self.bandwidth as u64;
+        if bandwidth < 1_100 * f_dig / 26_000_000 || bandwidth > 800_100 * f_dig / 26_000_000 {
+            return Err(RadioError::BandwidthOutOfRange);
+        }
+
+        // IS_CHANNEL_SPACE
+        if self.channel_space > xtal_frequency / (1 << 15) * 255 {
+            return Err(RadioError::ChannelSpaceOutOfRange);
         }
 
-        return (counter as u8, prescaler as u8);
+        // IS_FREQUENCY_OFFSET
+        let f_offset = self.frequency_offset(base_frequency);
+        if f_offset < od_constants::f_offset_lower_limit(xtal_frequency as i32)
+            || f_offset > od_constants::f_offset_upper_limit(xtal_frequency as i32)
+        {
+            return Err(RadioError::FrequencyOffsetOutOfRange);
+        }
+
+        // IS_FREQUENCY_BAND, for the base frequency and the selected channel
+        let band = BandSelect::from_hz(base_frequency).ok_or(RadioError::FrequencyOutOfBand)?;
+        let carrier = base_frequency as i64
+            + f_offset as i64
+            + self.channel_space as i64 * self.channel_number as i64;
+        if u32::try_from(carrier).ok().and_then(BandSelect::from_hz) != Some(band) {
+            return Err(RadioError::FrequencyOutOfBand);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    const XTAL_26M: u32 = 26_000_000;
+    const XTAL_50M: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    fn opts(frequency_deviation: u32, bandwidth: u32) -> RadioInitOpts {
+        RadioInitOpts {
+            frequency_deviation,
+            bandwidth,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_default() {
+        assert!(RadioInitOpts::default().validate(XTAL_26M, BASE).is_ok());
+        assert!(RadioInitOpts::default().validate(XTAL_50M, BASE).is_ok());
+    }
+
+    #[test]
+    fn validate_deviation_edges() {
+        // 26 MHz * 8 / 2^19 = 396.7 Hz, 26 MHz * 7680 / 2^19 = 380859.4 Hz
+        assert!(matches!(
+            opts(395, 100_500).validate(XTAL_26M, BASE),
+            Err(RadioError::DeviationOutOfRange)
+        ));
+        assert!(opts(396, 100_500).validate(XTAL_26M, BASE).is_ok());
+        assert!(opts(380_859, 100_500).validate(XTAL_26M, BASE).is_ok());
+        assert!(matches!(
+            opts(380_860, 100_500).validate(XTAL_26M, BASE),
+            Err(RadioError::DeviationOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn validate_deviation_edges_match_registers() {
+        let min = FreqDev0 {
+            fdev_e: 0,
+            fdev_m: 0,
+            ..FreqDev0::reset_value()
+        };
+        let max = FreqDev0 {
+            fdev_e: 9,
+            fdev_m: 7,
+            ..FreqDev0::reset_value()
+        };
+
+        for xtal in [24_000_000, XTAL_26M, XTAL_50M] {
+            let (min, max) = (min.deviation_hz(xtal), max.deviation_hz(xtal));
+            assert!(opts(min, 100_500).validate(xtal, BASE).is_ok());
+            assert!(opts(max, 100_500).validate(xtal, BASE).is_ok());
+            assert!(opts(min - 1, 100_500).validate(xtal, BASE).is_err());
+            assert!(opts(max + 1, 100_500).validate(xtal, BASE).is_err());
+        }
+    }
+
+    #[test]
+    fn validate_bandwidth_edges_26mhz() {
+        assert!(matches!(
+            opts(20_000, 1_099).validate(XTAL_26M, BASE),
+            Err(RadioError::BandwidthOutOfRange)
+        ));
+        assert!(opts(20_000, 1_100).validate(XTAL_26M, BASE).is_ok());
+        assert!(opts(20_000, 800_100).validate(XTAL_26M, BASE).is_ok());
+        assert!(matches!(
+            opts(20_000, 800_101).validate(XTAL_26M, BASE),
+            Err(RadioError::BandwidthOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn validate_bandwidth_edges_scaled() {
+        // 24 MHz: [1015.4, 738553.8] Hz
+        assert!(opts(20_000, 1_014).validate(24_000_000, BASE).is_err());
+        assert!(opts(20_000, 1_015).validate(24_000_000, BASE).is_ok());
+        assert!(opts(20_000, 738_553).validate(24_000_000, BASE).is_ok());
+        assert!(opts(20_000, 738_554).validate(24_000_000, BASE).is_err());
+
+        // 50 MHz, divided to a 25 MHz digital clock: [1057.7, 769326.9] Hz
+        assert!(opts(20_000, 1_056).validate(XTAL_50M, BASE).is_err());
+        assert!(opts(20_000, 1_057).validate(XTAL_50M, BASE).is_ok());
+        assert!(opts(20_000, 769_326).validate(XTAL_50M, BASE).is_ok());
+        assert!(opts(20_000, 769_327).validate(XTAL_50M, BASE).is_err());
+    }
+
+    #[test]
+    fn validate_data_rate_edges() {
+        let opts = |data_rate| RadioInitOpts {
+            data_rate,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            opts(99).validate(XTAL_26M, BASE),
+            Err(RadioError::DataRateOutOfRange)
+        ));
+        assert!(opts(100).validate(XTAL_26M, BASE).is_ok());
+        assert!(opts(500_000).validate(XTAL_26M, BASE).is_ok());
+        assert!(matches!(
+            opts(500_001).validate(XTAL_26M, BASE),
+            Err(RadioError::DataRateOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn rx_packet_status_offsets() {
+        assert_eq!(RX_PACKET_STATUS_LENGTH, 0xD4 - 0xC3);
+
+        let mut status = [0; RX_PACKET_STATUS_LENGTH];
+        for (i, byte) in status.iter_mut().enumerate() {
+            *byte = 0x40 + i as u8;
+        }
+
+        let packet = RxPacket::from_status(&[], &status, XTAL_26M).unwrap();
+
+        let rssi = RssiLevel::from_bytes(&[status[0xC8 - 0xC3]]).unwrap();
+        let address = RxAddrField::from_bytes(&[status[0xD2 - 0xC3], status[0xD3 - 0xC3]]).unwrap();
+        let info = RxPacketInfo::from_bytes(&[status[0]]).unwrap();
+        assert_eq!(packet.rssi_dbm, rssi.dbm());
+        assert_eq!(packet.source, address.source);
+        assert_eq!(packet.destination, address.destination);
+        assert_eq!(packet.sequence_number, info.rx_seq_num);
+    }
+
+    #[test]
+    fn rx_timeout_stop_condition_round_trip() {
+        use RxTimeoutStopCondition::*;
+
+        let mut radio = MockSpirit1::new(XTAL_50M, BASE);
+        assert_eq!(
+            radio.get_rx_timeout_stop_condition().unwrap(),
+            TimeoutAlwaysStopped
+        );
+
+        // Bits sharing the registers are left alone
+        let mut protocol: Protocol = radio.read_register().unwrap();
+        protocol.vco_calibration = false;
+        radio.write_register(protocol).unwrap();
+
+        for condition in [
+            NoTimeoutStop,
+            PqiAboveThreshold,
+            SqiAboveThreshold,
+            SqiAndPqiAboveThreshold,
+            RssiAboveThreshold,
+            RssiAndPqiAboveThreshold,
+            RssiAndSqiAboveThreshold,
+            AllAboveThreshold,
+            TimeoutAlwaysStopped,
+            SqiOrPqiAboveThreshold,
+            RssiOrPqiAboveThreshold,
+            RssiOrSqiAboveThreshold,
+            AnyAboveThreshold,
+        ] {
+            radio.set_rx_timeout_stop_condition(condition).unwrap();
+            assert_eq!(radio.get_rx_timeout_stop_condition().unwrap(), condition);
+            assert!(!radio.read_register::<Protocol>().unwrap().vco_calibration);
+        }
+    }
+
+    #[test]
+    fn init_keeps_a_base_frequency_set_afterwards() {
+        let mut radio = MockSpirit1::new(XTAL_50M, BASE);
+        radio.init(RadioInitOpts::default()).unwrap();
+
+        radio.set_base_frequency(915_000_000).unwrap();
+        assert_eq!(radio.get_base_frequency(), 915_000_000);
+
+        radio.init(RadioInitOpts::default()).unwrap();
+        let base = radio.get_base_frequency_from_device().unwrap();
+        assert!(base.abs_diff(915_000_000) < 200);
+
+        // The channel math follows the new base frequency
+        assert!(radio.set_channel(0).unwrap().abs_diff(915_000_000) < 200);
+    }
+
+    /// Streamed packet lengths: the FIFO depth, one byte over, and over the 8 bit length
+    const STREAM_LENGTHS: [usize; 3] = [96, 97, 300];
+
+    fn stream_payload() -> [u8; 300] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    fn streaming_radio() -> MockSpirit1<'static> {
+        let mut radio = MockSpirit1::new(XTAL_50M, BASE);
+        radio.init(RadioInitOpts::default()).unwrap();
+        radio
+    }
+
+    #[test]
+    fn tx_stream_sends_packets_longer_than_the_fifo() {
+        let payload = stream_payload();
+
+        for length in STREAM_LENGTHS {
+            let mut radio = streaming_radio();
+            let sent = radio
+                .tx_stream(&payload[..length], 1_000, TimeoutRecovery::Abort)
+                .unwrap();
+
+            assert_eq!(sent, length);
+            assert_eq!(
+                radio.take_transmitted().unwrap().as_slice(),
+                &payload[..length]
+            );
+        }
+    }
+
+    #[test]
+    fn rx_stream_receives_packets_longer_than_the_fifo() {
+        let payload = stream_payload();
+
+        for length in STREAM_LENGTHS {
+            let mut radio = streaming_radio();
+            radio.inject_rx_packet(&payload[..length]).unwrap();
+
+            let mut buffer = [0; 300];
+            let received = radio
+                .rx_stream(&mut buffer, 1_000, TimeoutRecovery::Abort)
+                .unwrap();
+
+            assert_eq!(received, length);
+            assert_eq!(&buffer[..length], &payload[..length]);
+        }
+    }
+
+    #[test]
+    fn tx_stream_reports_an_underflow() {
+        let mut radio = streaming_radio();
+        radio.set_stream_rate(128);
+
+        assert!(matches!(
+            radio.tx_stream(&stream_payload(), 1_000, TimeoutRecovery::Abort),
+            Err(RadioError::FifoError)
+        ));
+        assert_eq!(
+            radio
+                .read_register::<LinearFifoStatusTxElements>()
+                .unwrap()
+                .elem_txfifo,
+            0
+        );
+    }
+
+    #[test]
+    fn tx_stream_rejects_packets_over_65535_bytes() {
+        static PAYLOAD: [u8; 65_536] = [0; 65_536];
+        let mut radio = streaming_radio();
+
+        assert!(matches!(
+            radio.tx_stream(&PAYLOAD, 1_000, TimeoutRecovery::Abort),
+            Err(RadioError::PacketTooLong)
+        ));
+    }
+
+    #[test]
+    fn rx_stream_reports_an_overflow() {
+        let mut radio = streaming_radio();
+        radio.set_stream_rate(128);
+        radio.inject_rx_packet(&stream_payload()).unwrap();
+
+        let mut buffer = [0; 300];
+        assert!(matches!(
+            radio.rx_stream(&mut buffer, 1_000, TimeoutRecovery::Abort),
+            Err(RadioError::FifoError)
+        ));
+        assert_eq!(radio.state(), SpiritState::READY);
+    }
+
+    #[test]
+    fn rx_stream_checks_the_buffer_length() {
+        let mut radio = streaming_radio();
+        radio.inject_rx_packet(&stream_payload()).unwrap();
+
+        let mut buffer = [0; 200];
+        assert!(matches!(
+            radio.rx_stream(&mut buffer, 1_000, TimeoutRecovery::Abort),
+            Err(RadioError::BufferTooSmall)
+        ));
+        assert_eq!(radio.state(), SpiritState::READY);
     }
 }
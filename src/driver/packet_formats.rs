@@ -1,77 +1,156 @@
 use crate::prelude::*;
 use register_rs::*;
 
-use crate::{registers::*, RadioResult, RadioError};
+use crate::{registers::*, RadioResult};
 use crate::constants::official_driver_constants as od_constants;
 // use crate::Spirit1;
 
-pub trait SpiritPacketFormats: Spirit1HalBlocking
+pub trait SpiritPacketFormats: Spirit1Driver
 {
     fn configure_packet_protocol(&mut self, configuration: PacketConfiguration) -> RadioResult<()> {
         match configuration {
             PacketConfiguration::Basic(config) => self.configure_basic(config),
-            _ => Err(RadioError::NotImplemented)
+            PacketConfiguration::STack(config) => self.configure_stack(config),
+            PacketConfiguration::WMBus(config) => self.configure_wmbus(config),
         }
     }
 
     fn configure_basic_filter(&mut self, opts: BasicAddressOpts) -> RadioResult<()> {
         let mut flt_opts: PcktFltOptions = self.read_register()?;
-        flt_opts.dest_vs_source_addr = opts.filter_on_my_address;
-        flt_opts.dest_vs_multicast_addr = opts.filter_on_multicast_address;
-        flt_opts.dest_vs_broadcast_addr = opts.filter_on_broadcast_address;
-        self.write_register(flt_opts)?;
-
         let mut goals: PcktFltGoals = self.read_register()?;
-        goals.broadcast = opts.broadcast_address;
-        goals.multicast = opts.multicast_address;
-        goals.tx_source_addr = opts.my_address;
+        opts.apply(&mut flt_opts, &mut goals);
+
+        self.write_register(flt_opts)?;
         self.write_register(goals)?;
 
         Ok(())
     }
 
     fn configure_basic(&mut self, opts: BasicProtocolOpts) -> RadioResult<()> {
+        let registers = opts.registers()?;
+
         let mut protocol: Protocol = self.read_register()?;
-        protocol.auto_pckt_flt = true;
+        let mut flt_opts: PcktFltOptions = self.read_register()?;
+        registers.apply(&mut protocol, &mut flt_opts);
+
         self.write_register(protocol)?;
+        self.write_register(flt_opts)?;
+
+        self.write_register(registers.pkt_ctrl_4)?;
+        self.write_register(registers.pkt_ctrl_3)?;
+        self.write_register(registers.pkt_ctrl_2)?;
+        self.write_register(registers.pkt_ctrl_1)?;
+
+        let (sync_1, sync_2, sync_3, sync_4) = registers.sync;
+        self.write_register(sync_1)?;
+        self.write_register(sync_2)?;
+        self.write_register(sync_3)?;
+        self.write_register(sync_4)?;
 
+        Ok(())
+    }
+
+    fn configure_stack_filter(&mut self, opts: STackAddressOpts) -> RadioResult<()> {
         let mut flt_opts: PcktFltOptions = self.read_register()?;
-        flt_opts.source_filtering = true;
-        flt_opts.control_filtering = true;
-        flt_opts.crc_check = if opts.crc_mode == CrcMode::NoCrc { false } else { true };
+        let mut goals: PcktFltGoals = self.read_register()?;
+        opts.apply(&mut flt_opts, &mut goals);
+
         self.write_register(flt_opts)?;
+        self.write_register(goals)?;
 
-        let pkt_ctrl_4 = PcktCtrl4::new(
-            if opts.address_field { 1 } else { 0 }, 
-            opts.control_length.try_into()?
-        );
+        Ok(())
+    }
 
-        let mut pkt_ctrl_3 = PcktCtrl3::reset_value();
-        pkt_ctrl_3.len_wid = opts.packet_length_width.max(1);
-        pkt_ctrl_3.pckt_frmt = PacketFormat::Basic;
-
-        let pkt_ctrl_2 = PcktCtrl2::new(
-            opts.preamble_length.try_into()?,
-            opts.sync_length.try_into()?,
-            opts.fix_var_length
-        );
-
-        let pkt_ctrl_1 = PcktCtrl1::new(
-            opts.crc_mode,
-            opts.data_whitening,
-            TxMode::Normal,
-            opts.fec
-        );
-
-        self.write_register(pkt_ctrl_4)?;
-        self.write_register(pkt_ctrl_3)?;
-        self.write_register(pkt_ctrl_2)?;
-        self.write_register(pkt_ctrl_1)?;
-
-        self.write_register(Sync1::new(opts.sync_words.0))?;
-        self.write_register(Sync2::new(opts.sync_words.1))?;
-        self.write_register(Sync3::new(opts.sync_words.2))?;
-        self.write_register(Sync4::new(opts.sync_words.3))?;
+    fn configure_stack(&mut self, opts: STackProtocolOpts) -> RadioResult<()> {
+        let registers = opts.registers()?;
+
+        let mut protocol: Protocol = self.read_register()?;
+        let mut flt_opts: PcktFltOptions = self.read_register()?;
+        registers.apply(&mut protocol, &mut flt_opts);
+
+        self.write_register(protocol)?;
+        self.write_register(flt_opts)?;
+
+        self.write_register(registers.pkt_ctrl_4)?;
+        self.write_register(registers.pkt_ctrl_3)?;
+        self.write_register(registers.pkt_ctrl_2)?;
+        self.write_register(registers.pkt_ctrl_1)?;
+
+        let (sync_1, sync_2, sync_3, sync_4) = registers.sync;
+        self.write_register(sync_1)?;
+        self.write_register(sync_2)?;
+        self.write_register(sync_3)?;
+        self.write_register(sync_4)?;
+
+        Ok(())
+    }
+
+    /// Configures the Wireless M-Bus packet handler for `opts.submode` and programs the
+    /// modem and base frequency of the matching [`WMBusPreset`].
+    ///
+    /// The preset is checked with [`RadioInitOpts::validate`] before anything is written.
+    /// The selected channel and the crystal settings (including the frequency offset) are
+    /// kept, the carrier being `preset.frequency + channel * preset.channel_space`
+    fn configure_wmbus(&mut self, opts: WMBusProtocolOpts) -> RadioResult<()> {
+        let preset = WMBusPreset::from_submode(&opts.submode);
+        let pd_clkdiv = self.read_register::<XoRcoTest>()?.pd_clkdiv;
+        let modem = preset.modem_config(&self.read_radio_config()?, self.get_xtal_frequency(), pd_clkdiv)?;
+
+        self.write_modem_config(modem)?;
+        self.set_base_frequency(preset.frequency)?;
+
+        let mut format = WMBusFormat {
+            pkt_ctrl_3: self.read_register()?,
+            pkt_ctrl_2: self.read_register()?,
+            pkt_ctrl_1: self.read_register()?,
+            flt_opts: self.read_register()?,
+            ant_select: self.read_register()?
+        };
+        format.apply();
+
+        self.write_register(format.pkt_ctrl_3)?;
+        self.write_register(format.pkt_ctrl_2)?;
+        self.write_register(format.pkt_ctrl_1)?;
+        self.write_register(format.flt_opts)?;
+        self.write_register(format.ant_select)?;
+
+        let (preamble, postamble, ctrl) = opts.registers();
+        self.write_register(preamble)?;
+        self.write_register(postamble)?;
+        self.write_register(ctrl)?;
+
+        Ok(())
+    }
+
+    /// Sets the destination address of the transmitted packets (`RX_SOURCE_ADDR`)
+    fn set_destination_address(&mut self, address: u8) -> RadioResult<()> {
+        let mut goals: PcktFltGoals = self.read_register()?;
+        goals.rx_source_addr = address;
+        self.write_register(goals)
+    }
+
+    /// Sets the control field of the transmitted packets, control byte n.0 being the least
+    /// significant byte of `control`
+    fn set_tx_control_field(&mut self, control: u32) -> RadioResult<()> {
+        let [ctrl3, ctrl2, ctrl1, ctrl0] = control.to_be_bytes();
+        self.write_register(TxCtrlField::new(ctrl3, ctrl2, ctrl1, ctrl0))
+    }
+
+    /// Sets the `NO_ACK` field of the transmitted STack packets. When set, the receiver does
+    /// not send an acknowledgement even if automatic acknowledgement is enabled
+    fn set_no_ack(&mut self, no_ack: bool) -> RadioResult<()> {
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.nack_tx = no_ack;
+        self.write_register(protocol)
+    }
+
+    /// Reloads the 2 bit sequence number of the transmitted STack packets with `sequence`
+    fn reload_sequence_number(&mut self, sequence: u8) -> RadioResult<()> {
+        let mut protocol: Protocol = self.read_register()?;
+        protocol.tx_seq_num_reload = sequence & 0b11;
+        self.write_register(protocol)?;
+
+        self.write_command(SpiritCommand::SEQUENCE_UPDATE)?;
 
         Ok(())
     }
@@ -109,6 +188,30 @@ pub struct BasicProtocolOpts {
     pub data_whitening: bool
 }
 
+impl BasicProtocolOpts {
+    /// Packet handler registers of the Basic format
+    pub(crate) fn registers(self) -> RadioResult<PacketRegisters> {
+        let crc_check = self.crc_mode != CrcMode::NoCrc;
+
+        Ok(PacketRegisters {
+            pkt_ctrl_4: PcktCtrl4::new(
+                if self.address_field { 1 } else { 0 },
+                self.control_length.try_into()?
+            ),
+            pkt_ctrl_3: PacketRegisters::pkt_ctrl_3(PacketFormat::Basic, self.packet_length_width),
+            pkt_ctrl_2: PcktCtrl2::new(
+                self.preamble_length.try_into()?,
+                self.sync_length.try_into()?,
+                self.fix_var_length
+            ),
+            pkt_ctrl_1: PcktCtrl1::new(self.crc_mode, self.data_whitening, TxMode::Normal, self.fec),
+            sync: PacketRegisters::sync(self.sync_words),
+            source_and_control_filtering: true,
+            crc_check
+        })
+    }
+}
+
 /// SPIRIT Basic Packet address structure definition. This structure allows users to specify
 /// the node/multicast/broadcast addresses and the correspondent filtering options.
 pub struct BasicAddressOpts {
@@ -126,12 +229,281 @@ pub struct BasicAddressOpts {
     pub broadcast_address: u8
 }
 
+impl BasicAddressOpts {
+    /// Sets the destination filtering options and the addresses of this node
+    pub(crate) fn apply(&self, flt_opts: &mut PcktFltOptions, goals: &mut PcktFltGoals) {
+        flt_opts.dest_vs_source_addr = self.filter_on_my_address;
+        flt_opts.dest_vs_multicast_addr = self.filter_on_multicast_address;
+        flt_opts.dest_vs_broadcast_addr = self.filter_on_broadcast_address;
+
+        goals.broadcast = self.broadcast_address;
+        goals.multicast = self.multicast_address;
+        goals.tx_source_addr = self.my_address;
+    }
+}
+
+/// SPIRIT Wireless M-Bus Packet Init structure definition (EN 13757-4). In this mode the
+/// packet handler only adds the preamble, sync word and postamble of the submode, the
+/// frame (L, C, M, A, CI fields and block CRCs) is the payload.
 pub struct WMBusProtocolOpts {
-    // TODO Implement WMBusProtocolOps
+    /// Specifies the WM-Bus submode
+    pub submode: MBusSubMode,
+    /// Specifies the number of `01` chip sequences added to the minimum preamble
+    /// length of the submode
+    pub preamble_length: u8,
+    /// Specifies the number of `01` chip sequences of the postamble
+    pub postamble_length: u8
+}
+
+impl WMBusProtocolOpts {
+    /// Preamble, postamble and submode registers
+    pub(crate) fn registers(&self) -> (MbusPRMBL, MbusPSTMBL, MbusCtrl) {
+        (
+            MbusPRMBL::new(self.preamble_length),
+            MbusPSTMBL::new(self.postamble_length),
+            MbusCtrl::new(self.submode.clone())
+        )
+    }
+}
+
+/// Packet handler registers switched to the Wireless M-Bus format by
+/// [`SpiritPacketFormats::configure_wmbus`]
+pub(crate) struct WMBusFormat {
+    pub pkt_ctrl_3: PcktCtrl3,
+    pub pkt_ctrl_2: PcktCtrl2,
+    pub pkt_ctrl_1: PcktCtrl1,
+    pub flt_opts: PcktFltOptions,
+    pub ant_select: AntSelectConf
+}
+
+impl WMBusFormat {
+    /// Selects the WM-Bus format, the normal TX and RX modes and the carrier sense blanking
+    pub(crate) fn apply(&mut self) {
+        self.pkt_ctrl_3.pckt_frmt = PacketFormat::WMBus;
+        self.pkt_ctrl_3.rx_mode = RxMode::Normal;
+
+        // The packet length is given by the L field of the frame
+        self.pkt_ctrl_2.fix_var_len = PacketLengthMode::Fixed;
+
+        self.pkt_ctrl_1.tx_source = TxMode::Normal;
+
+        // The block CRCs are part of the payload, see the `wmbus` module
+        self.flt_opts.crc_check = false;
+
+        self.ant_select.cs_blanking = true;
+    }
+}
+
+/// Physical layer parameters of a WM-Bus submode, as defined by EN 13757-4
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub struct WMBusPreset {
+    /// Carrier frequency in Hz
+    pub frequency: u32,
+    /// Chip rate in chip/s
+    pub data_rate: u32,
+    /// Frequency deviation in Hz
+    pub frequency_deviation: u32,
+    /// Channel filter bandwidth in Hz
+    pub bandwidth: u32,
+    /// Channel spacing in Hz, only used by the R2 submode
+    pub channel_space: u32
+}
+
+impl WMBusPreset {
+    /// S mode: 32.768 kchip/s Manchester at 868.3 MHz
+    pub const S: Self = Self {
+        frequency: 868_300_000,
+        data_rate: 32_768,
+        frequency_deviation: 50_000,
+        bandwidth: 250_000,
+        channel_space: 20_000
+    };
+
+    /// T mode, meter to other: 100 kchip/s 3 out of 6 at 868.95 MHz
+    pub const T1: Self = Self {
+        frequency: 868_950_000,
+        data_rate: 100_000,
+        frequency_deviation: 50_000,
+        bandwidth: 330_000,
+        channel_space: 20_000
+    };
+
+    /// R2 mode: 4.8 kchip/s Manchester at 868.33 MHz, 60 kHz channel spacing
+    pub const R2: Self = Self {
+        frequency: 868_330_000,
+        data_rate: 4_800,
+        frequency_deviation: 6_000,
+        bandwidth: 58_000,
+        channel_space: 60_000
+    };
+
+    /// 2-FSK radio parameters of the preset, keeping the crystal offset and the channel
+    /// number of the current configuration
+    pub fn radio_opts(&self, xtal_offset_ppm: i16, channel_number: u8) -> RadioInitOpts {
+        RadioInitOpts {
+            xtal_offset_ppm,
+            channel_space: self.channel_space,
+            channel_number,
+            modulation_select: ModulationType::Fsk2,
+            data_rate: self.data_rate,
+            frequency_deviation: self.frequency_deviation,
+            bandwidth: self.bandwidth
+        }
+    }
+
+    /// Modem registers of the preset, keeping the crystal offset and the channel number of
+    /// `current`. The preset is checked with [`RadioInitOpts::validate`] first
+    pub(crate) fn modem_config(&self, current: &RadioInitOpts, xtal_frequency: u32, pd_clkdiv: bool) -> RadioResult<ModemConfig> {
+        let radio = self.radio_opts(current.xtal_offset_ppm, current.channel_number);
+        radio.validate(xtal_frequency, self.frequency)?;
+
+        radio.modem_config(xtal_frequency, pd_clkdiv)
+    }
+
+    /// Preset used by `submode`. The other to meter direction of the T mode (T2)
+    /// uses the S mode parameters
+    pub const fn from_submode(submode: &MBusSubMode) -> Self {
+        match submode {
+            MBusSubMode::S1S2LongHeader | MBusSubMode::S1mS2T2OtherToMeter => Self::S,
+            MBusSubMode::T1T2MeterToOther => Self::T1,
+            MBusSubMode::R2ShortHeader => Self::R2,
+        }
+    }
 }
 
+/// SPIRIT STack Packet Init structure definition. This structure allows users to set
+/// the main options for the STack packet.
+///
+/// The STack packet always carries a destination and a source address, a 2 bit sequence
+/// number and the `NO_ACK` field used by the automatic acknowledgement.
 pub struct STackProtocolOpts {
-    // TODO Implement STackProtocolOpts
+    /// Specifies the preamble length
+    pub preamble_length: PreambleLength,
+    /// Specifies the sync word length
+    pub sync_length: PacketSyncLength,
+    /// Specifies the sync words
+    pub sync_words: (u8, u8, u8, u8),
+    /// Specifies if a fixed length of packet has to be used
+    pub fix_var_length: PacketLengthMode,
+    /// Specifies the size of the length of packet in bits.
+    ///
+    /// For STack packets the length width is
+    /// log2( max payload length + control length (0 to 4) + address length (always 2)).
+    pub packet_length_width: u8,
+    /// Specifies the CRC word length of packet
+    pub crc_mode: CrcMode,
+    /// Specifies the length of a control field to be sent
+    pub control_length: PacketControlLength,
+    /// Specifies if FEC has to be enabled
+    pub fec: bool,
+    /// Specifies if data whitening has to be enabled
+    pub data_whitening: bool
+}
+
+impl STackProtocolOpts {
+    /// Packet handler registers of the STack format
+    pub(crate) fn registers(self) -> RadioResult<PacketRegisters> {
+        let crc_check = self.crc_mode != CrcMode::NoCrc;
+
+        Ok(PacketRegisters {
+            // Source and destination addresses are always present
+            pkt_ctrl_4: PcktCtrl4::new(2, self.control_length.try_into()?),
+            pkt_ctrl_3: PacketRegisters::pkt_ctrl_3(PacketFormat::STack, self.packet_length_width),
+            pkt_ctrl_2: PcktCtrl2::new(
+                self.preamble_length.try_into()?,
+                self.sync_length.try_into()?,
+                self.fix_var_length
+            ),
+            pkt_ctrl_1: PcktCtrl1::new(self.crc_mode, self.data_whitening, TxMode::Normal, self.fec),
+            sync: PacketRegisters::sync(self.sync_words),
+            // Source and control filtering are set by `configure_stack_filter`
+            source_and_control_filtering: false,
+            crc_check
+        })
+    }
+}
+
+/// SPIRIT STack Packet address structure definition. On top of the destination filtering of
+/// [`BasicAddressOpts`], STack packets can be filtered on their source address.
+pub struct STackAddressOpts {
+    /// If set RX packet is accepted if its destination address matches with `my_address`
+    pub filter_on_my_address: bool,
+    /// Specifies the TX packet source address (address of this node)
+    pub my_address: u8,
+    /// If set RX packet is accepted if its destination address matches with `multicast_address`
+    pub filter_on_multicast_address: bool,
+    /// Specifies the Multicast group address for this node
+    pub multicast_address: u8,
+    /// If set RX packet is accepted if its destination address matches with `broadcast_address`
+    pub filter_on_broadcast_address: bool,
+    /// Specifies the Broadcast address for this node.
+    pub broadcast_address: u8,
+    /// If set RX packet is accepted if its source address matches with
+    /// `source_reference_address` on the bits set in `source_mask`
+    pub filter_on_source_address: bool,
+    /// Specifies the source address reference. It is also the destination address of the
+    /// transmitted packets
+    pub source_reference_address: u8,
+    /// Specifies the bits of the source address to compare, `0`: no filtering
+    pub source_mask: u8
+}
+
+impl STackAddressOpts {
+    /// Sets the destination and source filtering options and the addresses of this node
+    pub(crate) fn apply(&self, flt_opts: &mut PcktFltOptions, goals: &mut PcktFltGoals) {
+        flt_opts.dest_vs_source_addr = self.filter_on_my_address;
+        flt_opts.dest_vs_multicast_addr = self.filter_on_multicast_address;
+        flt_opts.dest_vs_broadcast_addr = self.filter_on_broadcast_address;
+        flt_opts.source_filtering = self.filter_on_source_address;
+
+        goals.broadcast = self.broadcast_address;
+        goals.multicast = self.multicast_address;
+        goals.tx_source_addr = self.my_address;
+        goals.rx_source_addr = self.source_reference_address;
+        goals.rx_source_mask = self.source_mask;
+    }
+}
+
+/// Packet handler registers of the Basic and STack formats, see
+/// [`BasicProtocolOpts::registers`] and [`STackProtocolOpts::registers`]
+pub(crate) struct PacketRegisters {
+    pub pkt_ctrl_4: PcktCtrl4,
+    pub pkt_ctrl_3: PcktCtrl3,
+    pub pkt_ctrl_2: PcktCtrl2,
+    pub pkt_ctrl_1: PcktCtrl1,
+    pub sync: (Sync1, Sync2, Sync3, Sync4),
+    /// `PCKT_FLT_OPTIONS` source and control filtering
+    pub source_and_control_filtering: bool,
+    /// `PCKT_FLT_OPTIONS` CRC check
+    pub crc_check: bool
+}
+
+impl PacketRegisters {
+    /// Enables the automatic packet filtering and sets the filtering options of the format
+    pub(crate) fn apply(&self, protocol: &mut Protocol, flt_opts: &mut PcktFltOptions) {
+        protocol.auto_pckt_flt = true;
+
+        flt_opts.source_filtering = self.source_and_control_filtering;
+        flt_opts.control_filtering = self.source_and_control_filtering;
+        flt_opts.crc_check = self.crc_check;
+    }
+
+    fn pkt_ctrl_3(format: PacketFormat, packet_length_width: u8) -> PcktCtrl3 {
+        let mut pkt_ctrl_3 = PcktCtrl3::reset_value();
+        pkt_ctrl_3.len_wid = packet_length_width.max(1);
+        pkt_ctrl_3.pckt_frmt = format;
+
+        pkt_ctrl_3
+    }
+
+    fn sync(sync_words: (u8, u8, u8, u8)) -> (Sync1, Sync2, Sync3, Sync4) {
+        (
+            Sync1::new(sync_words.0),
+            Sync2::new(sync_words.1),
+            Sync3::new(sync_words.2),
+            Sync4::new(sync_words.3)
+        )
+    }
 }
 
 /// Before on-the-air transmission, raw data is properly cast into a packet structure. The 
@@ -237,4 +609,169 @@ pub enum PacketControlLength {
     Bytes03,
     #[valued(4)]
     Bytes04,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    fn stack_opts(crc_mode: CrcMode) -> STackProtocolOpts {
+        STackProtocolOpts {
+            preamble_length: PreambleLength::Bytes04,
+            sync_length: PacketSyncLength::Bytes03,
+            sync_words: (0x1A, 0x2B, 0x3C, 0x4D),
+            fix_var_length: PacketLengthMode::Variable,
+            packet_length_width: 7,
+            crc_mode,
+            control_length: PacketControlLength::Bytes02,
+            fec: false,
+            data_whitening: true
+        }
+    }
+
+    fn radio() -> MockSpirit1<'static> {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        radio.init(RadioInitOpts::default()).unwrap();
+        radio
+    }
+
+    #[test]
+    fn configure_stack_programs_the_packet_handler() {
+        let mut radio = radio();
+        radio.configure_stack(stack_opts(CrcMode::Crc0x1021)).unwrap();
+
+        let pkt_ctrl_4: PcktCtrl4 = radio.read_register().unwrap();
+        assert_eq!(pkt_ctrl_4.address_len, 2);
+        assert_eq!(pkt_ctrl_4.control_len, 2);
+
+        let pkt_ctrl_3: PcktCtrl3 = radio.read_register().unwrap();
+        assert!(matches!(pkt_ctrl_3.pckt_frmt, PacketFormat::STack));
+        assert_eq!(pkt_ctrl_3.len_wid, 7);
+
+        let pkt_ctrl_1: PcktCtrl1 = radio.read_register().unwrap();
+        assert_eq!(pkt_ctrl_1.crc_mode, CrcMode::Crc0x1021);
+        assert!(pkt_ctrl_1.whit_en);
+        assert!(matches!(pkt_ctrl_1.tx_source, TxMode::Normal));
+
+        assert_eq!(radio.read_register::<Sync4>().unwrap().sync4, 0x4D);
+        assert!(radio.read_register::<Protocol>().unwrap().auto_pckt_flt);
+        assert!(radio.read_register::<PcktFltOptions>().unwrap().crc_check);
+
+        radio.configure_stack(stack_opts(CrcMode::NoCrc)).unwrap();
+        assert!(!radio.read_register::<PcktFltOptions>().unwrap().crc_check);
+    }
+
+    #[test]
+    fn configure_stack_filter_sets_the_filter_bits() {
+        let mut radio = radio();
+        radio.configure_stack(stack_opts(CrcMode::Crc0x1021)).unwrap();
+        radio.configure_stack_filter(STackAddressOpts {
+            filter_on_my_address: true,
+            my_address: 0x11,
+            filter_on_multicast_address: false,
+            multicast_address: 0xEE,
+            filter_on_broadcast_address: true,
+            broadcast_address: 0xFF,
+            filter_on_source_address: true,
+            source_reference_address: 0x22,
+            source_mask: 0xF0
+        }).unwrap();
+
+        let flt_opts: PcktFltOptions = radio.read_register().unwrap();
+        assert!(flt_opts.dest_vs_source_addr);
+        assert!(!flt_opts.dest_vs_multicast_addr);
+        assert!(flt_opts.dest_vs_broadcast_addr);
+        assert!(flt_opts.source_filtering);
+        assert!(flt_opts.crc_check);
+
+        let goals: PcktFltGoals = radio.read_register().unwrap();
+        assert_eq!(goals.tx_source_addr, 0x11);
+        assert_eq!(goals.multicast, 0xEE);
+        assert_eq!(goals.broadcast, 0xFF);
+        assert_eq!(goals.rx_source_addr, 0x22);
+        assert_eq!(goals.rx_source_mask, 0xF0);
+    }
+
+    #[test]
+    fn set_tx_control_field_orders_the_control_bytes() {
+        let mut radio = radio();
+        radio.set_tx_control_field(0x1122_3344).unwrap();
+
+        // Control byte n.0 is the last one in register order
+        let control: TxCtrlField = radio.read_register().unwrap();
+        assert_eq!(control.tx_ctrl3, 0x11);
+        assert_eq!(control.tx_ctrl2, 0x22);
+        assert_eq!(control.tx_ctrl1, 0x33);
+        assert_eq!(control.tx_ctrl0, 0x44);
+    }
+
+    #[test]
+    fn set_no_ack_sets_the_protocol_field() {
+        let mut radio = radio();
+
+        radio.set_no_ack(false).unwrap();
+        assert!(!radio.read_register::<Protocol>().unwrap().nack_tx);
+
+        radio.set_no_ack(true).unwrap();
+        assert!(radio.read_register::<Protocol>().unwrap().nack_tx);
+    }
+
+    #[test]
+    fn reload_sequence_number_sets_the_next_sequence_number() {
+        let mut radio = radio();
+        radio.configure_stack(stack_opts(CrcMode::Crc0x1021)).unwrap();
+
+        // Only the 2 bit sequence number is kept
+        radio.reload_sequence_number(0b110).unwrap();
+        assert_eq!(radio.read_register::<Protocol>().unwrap().tx_seq_num_reload, 0b10);
+
+        radio.tx_blocking(b"Hello").unwrap();
+        assert_eq!(radio.read_register::<TxPacketInfo>().unwrap().tx_seq_num, 0b10);
+        radio.tx_blocking(b"Hello").unwrap();
+        assert_eq!(radio.read_register::<TxPacketInfo>().unwrap().tx_seq_num, 0b11);
+    }
+    #[test]
+    fn configure_wmbus_programs_each_preset() {
+        for (submode, preset) in [
+            (MBusSubMode::S1S2LongHeader, WMBusPreset::S),
+            (MBusSubMode::T1T2MeterToOther, WMBusPreset::T1),
+            (MBusSubMode::R2ShortHeader, WMBusPreset::R2)
+        ] {
+            assert_eq!(WMBusPreset::from_submode(&submode), preset);
+
+            let mut radio = radio();
+            radio.configure_wmbus(WMBusProtocolOpts {
+                submode: submode.clone(),
+                preamble_length: 10,
+                postamble_length: 2
+            }).unwrap();
+
+            let pkt_ctrl_3: PcktCtrl3 = radio.read_register().unwrap();
+            assert!(matches!(pkt_ctrl_3.pckt_frmt, PacketFormat::WMBus));
+            assert!(matches!(pkt_ctrl_3.rx_mode, RxMode::Normal));
+            assert!(matches!(radio.read_register::<PcktCtrl2>().unwrap().fix_var_len, PacketLengthMode::Fixed));
+            assert!(!radio.read_register::<PcktFltOptions>().unwrap().crc_check);
+            assert!(radio.read_register::<AntSelectConf>().unwrap().cs_blanking);
+
+            let ctrl: MbusCtrl = radio.read_register().unwrap();
+            assert_eq!(ctrl.mbus_submode.clone() as u8, submode.clone() as u8);
+            assert_eq!(radio.read_register::<MbusPRMBL>().unwrap().mbus_prmbl, 10);
+            assert_eq!(radio.read_register::<MbusPSTMBL>().unwrap().mbus_pstmbl, 2);
+
+            let base = radio.get_base_frequency_from_device().unwrap();
+            assert!(base.abs_diff(preset.frequency) < 200, "base frequency {}", base);
+            assert_eq!(radio.get_base_frequency(), preset.frequency);
+
+            let config = radio.read_radio_config().unwrap();
+            assert!(config.data_rate.abs_diff(preset.data_rate) * 100 < preset.data_rate, "data rate {}", config.data_rate);
+            assert!(config.frequency_deviation.abs_diff(preset.frequency_deviation) * 20 < preset.frequency_deviation);
+        }
+
+        // T2 other to meter uses the S mode parameters
+        assert_eq!(WMBusPreset::from_submode(&MBusSubMode::S1mS2T2OtherToMeter), WMBusPreset::S);
+    }
 }
\ No newline at end of file
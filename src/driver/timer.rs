@@ -0,0 +1,291 @@
+//! # Timers
+//! *See 9.8 Timers*
+//!
+//! The SPIRIT1 timers (RX timeout, LDC wake-up and LDC reload) are each made of an 8 bit
+//! prescaler and an 8 bit counter. The RX timeout timer is clocked by the digital clock
+//! divided by 1210, the LDC timers by the RC oscillator.
+
+use crate::constants::official_driver_constants as od_constants;
+
+/// Nominal frequency of the RC oscillator clocking the LDC timers, in Hz
+pub const RCO_FREQUENCY: u32 = 34_700;
+
+/// Frequency of the RC oscillator in Hz (`SpiritTimerGetRcoFrequency`). The RCO is
+/// calibrated against the digital clock: with a 25 MHz digital clock it runs at 33.3 kHz
+/// or 36.1 kHz depending on `XO_RCO_CONFIG.RCO_DIVIDER`, at [`RCO_FREQUENCY`] otherwise
+pub fn rco_frequency(xtal_frequency: u32, rco_divider: u8) -> u32 {
+    let f_dig = if xtal_frequency > od_constants::DOUBLE_XTAL_THR {
+        xtal_frequency / 2
+    } else {
+        xtal_frequency
+    };
+
+    match (f_dig, rco_divider) {
+        (25_000_000, 0b11) => 33_300,
+        (25_000_000, _) => 36_100,
+        _ => RCO_FREQUENCY,
+    }
+}
+
+/// Counter and prescaler of a timer clocked at `clock_khz` expiring after `ms`, minimising
+/// the error (`SpiritTimerComputeWakeUpValues`/`SpiritTimerComputeRxTimeoutValues`).
+///
+/// Returns the maximum values if `ms` is out of range
+pub(crate) fn compute_timer_values(ms: f32, clock_khz: f32) -> (u8, u8) {
+    // Number of cycles of the timer time base
+    let n = (ms * clock_khz) as u32;
+
+    if n / 0xFF > 0xFD {
+        return (0xFF, 0xFF);
+    }
+
+    // The prescaler is 2 at least
+    let prescaler = n / 0xFF + 2;
+    let mut counter = n / prescaler;
+
+    let error = |counter: u32| abs(counter as f32 * prescaler as f32 / clock_khz - ms);
+    if counter <= 254 && error(counter + 1) < error(counter) {
+        counter += 1;
+    }
+
+    // The timer counts one more cycle than the register values
+    (
+        counter.saturating_sub(1).max(1) as u8,
+        (prescaler - 1) as u8,
+    )
+}
+
+/// RX timeout timer counter and prescaler for a timeout of `ms`.
+///
+/// Unlike `SpiritTimerComputeRxTimeoutValues` the counter is not decremented, the
+/// timeout lasting `(RX_TIMEOUT_PRESCALER + 1) * RX_TIMEOUT_COUNTER` timer periods. The
+/// smallest prescaler is used for the best resolution. Returns the maximum values if
+/// `ms` is out of range
+pub const fn compute_rx_timeout_values(ms: f32, xtal_frequency: u32) -> (u8, u8) {
+    let clock_khz = rx_timeout_clock_khz(xtal_frequency);
+    let n = (ms * clock_khz + 0.5) as u32;
+
+    if n > 0x100 * 0xFF {
+        return (0xFF, 0xFF);
+    }
+
+    let prescaler = if n > 0xFF { n.div_ceil(0xFF) } else { 1 };
+    let counter = match (n + prescaler / 2) / prescaler {
+        0 => 1,
+        counter => counter,
+    };
+
+    (counter as u8, (prescaler - 1) as u8)
+}
+
+/// RX timeout timer counter and prescaler for a timeout of `timeout_ms`
+#[deprecated(note = "use `compute_rx_timeout_values`, which takes fractional milliseconds")]
+pub const fn calculate_rx_timeout(timeout_ms: u32, xtal_frequency: u32) -> (u8, u8) {
+    compute_rx_timeout_values(timeout_ms as f32, xtal_frequency)
+}
+
+/// RX timeout in ms (`SpiritTimerGetRxTimeout`), a counter of `0` meaning no timeout
+pub fn rx_timeout_ms(counter: u8, prescaler: u8, xtal_frequency: u32) -> f32 {
+    (prescaler as f32 + 1.0) * counter as f32 / rx_timeout_clock_khz(xtal_frequency)
+}
+
+/// The RX timeout timer is clocked by the digital clock divided by 1210
+const fn rx_timeout_clock_khz(xtal_frequency: u32) -> f32 {
+    let f_clk = if xtal_frequency > od_constants::DOUBLE_XTAL_THR {
+        xtal_frequency / 2
+    } else {
+        xtal_frequency
+    };
+
+    f_clk as f32 / 1_210_000.0
+}
+
+/// LDC wake-up (or reload) timer counter and prescaler for a period of `ms` with the RCO
+/// running at `rco_frequency` Hz, in the range of about `0.1 ms` to `1.9 s`
+pub fn compute_wake_up_values(ms: f32, rco_frequency: u32) -> (u8, u8) {
+    compute_timer_values(ms, rco_frequency as f32 / 1000.0)
+}
+
+/// LDC wake-up (or reload) timer period in ms (`SpiritTimerGetWakeUpTimer`)
+pub fn wake_up_ms(counter: u8, prescaler: u8, rco_frequency: u32) -> f32 {
+    (prescaler as f32 + 1.0) * (counter as f32 + 1.0) * 1000.0 / rco_frequency as f32
+}
+
+fn abs(value: f32) -> f32 {
+    if value < 0.0 {
+        -value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+    use crate::{RadioError, Spirit1Driver};
+
+    #[test]
+    fn rco_frequency_follows_the_digital_clock() {
+        // XO_RCO_CONFIG reset value
+        assert_eq!(rco_frequency(50_000_000, 0b10), 36_100);
+        assert_eq!(rco_frequency(25_000_000, 0b10), 36_100);
+        assert_eq!(rco_frequency(50_000_000, 0b11), 33_300);
+        assert_eq!(rco_frequency(52_000_000, 0b11), RCO_FREQUENCY);
+        assert_eq!(rco_frequency(24_000_000, 0b10), RCO_FREQUENCY);
+    }
+
+    /// Timer period with a 50 MHz crystal: `1210 / 25 MHz`
+    const RX_TIMEOUT_PERIOD_MS: f32 = 0.0484;
+
+    #[test]
+    fn rx_timeout_minimum() {
+        assert_eq!(compute_rx_timeout_values(0.0, 50_000_000), (1, 0));
+        assert_eq!(compute_rx_timeout_values(0.01, 50_000_000), (1, 0));
+        assert_eq!(
+            compute_rx_timeout_values(RX_TIMEOUT_PERIOD_MS, 50_000_000),
+            (1, 0)
+        );
+        assert_eq!(
+            compute_rx_timeout_values(2.0 * RX_TIMEOUT_PERIOD_MS, 50_000_000),
+            (2, 0)
+        );
+
+        // 50.417 µs with a 24 MHz crystal
+        assert_eq!(compute_rx_timeout_values(0.050_417, 24_000_000), (1, 0));
+        assert!(abs(rx_timeout_ms(1, 0, 24_000_000) - 0.050_417) < 1e-6);
+    }
+
+    #[test]
+    fn rx_timeout_saturation() {
+        let max = rx_timeout_ms(0xFF, 0xFF, 50_000_000);
+        assert!(abs(max - 0xFF as f32 * 0x100 as f32 * RX_TIMEOUT_PERIOD_MS) < 1e-3);
+
+        assert_eq!(compute_rx_timeout_values(max, 50_000_000), (0xFF, 0xFF));
+        assert_eq!(
+            compute_rx_timeout_values(max - RX_TIMEOUT_PERIOD_MS, 50_000_000),
+            (0xFF, 0xFF)
+        );
+        assert_eq!(
+            compute_rx_timeout_values(max + RX_TIMEOUT_PERIOD_MS, 50_000_000),
+            (0xFF, 0xFF)
+        );
+        assert_eq!(
+            compute_rx_timeout_values(10_000.0, 50_000_000),
+            (0xFF, 0xFF)
+        );
+    }
+
+    #[test]
+    fn rx_timeout_rounding() {
+        let period = |n: f32| n * RX_TIMEOUT_PERIOD_MS;
+
+        // Periods are rounded to the nearest one
+        assert_eq!(
+            compute_rx_timeout_values(period(10.49), 50_000_000),
+            (10, 0)
+        );
+        assert_eq!(
+            compute_rx_timeout_values(period(10.51), 50_000_000),
+            (11, 0)
+        );
+
+        // Largest timeout with the smallest prescaler, then the prescaler steps up
+        assert_eq!(
+            compute_rx_timeout_values(period(255.0), 50_000_000),
+            (255, 0)
+        );
+        assert_eq!(
+            compute_rx_timeout_values(period(256.0), 50_000_000),
+            (128, 1)
+        );
+        assert_eq!(
+            compute_rx_timeout_values(period(510.0), 50_000_000),
+            (255, 1)
+        );
+        assert_eq!(
+            compute_rx_timeout_values(period(511.0), 50_000_000),
+            (170, 2)
+        );
+    }
+
+    #[test]
+    fn rx_timeout_round_trip() {
+        // Within half a counter step, plus the rounding to timer periods, from 50 µs to 3.28 s
+        for xtal in [24_000_000, 26_000_000, 50_000_000, 52_000_000] {
+            let max = rx_timeout_ms(0xFF, 0xFF, xtal);
+            let mut ms = 0.05;
+            while ms <= max {
+                let (counter, prescaler) = compute_rx_timeout_values(ms, xtal);
+                let actual = rx_timeout_ms(counter, prescaler, xtal);
+                let step = rx_timeout_ms(1, prescaler, xtal) + rx_timeout_ms(1, 0, xtal);
+                assert!(
+                    abs(actual - ms) <= step / 2.0 + 1e-3,
+                    "{} ms: {}",
+                    ms,
+                    actual
+                );
+
+                ms *= 1.1;
+            }
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn calculate_rx_timeout_wraps_compute_rx_timeout_values() {
+        for ms in [1, 10, 100, 1000, 3000] {
+            assert_eq!(
+                calculate_rx_timeout(ms, 50_000_000),
+                compute_rx_timeout_values(ms as f32, 50_000_000)
+            );
+        }
+    }
+
+    #[test]
+    fn wake_up_values() {
+        for rco in [33_300, 34_700, 36_100] {
+            for ms in [1.0, 10.0, 100.0, 500.0, 1000.0] {
+                let (counter, prescaler) = compute_wake_up_values(ms, rco);
+                let actual = wake_up_ms(counter, prescaler, rco);
+                // Within one timer period
+                let period = (prescaler as f32 + 1.0) * 1000.0 / rco as f32;
+                assert!(
+                    abs(actual - ms) <= period,
+                    "{} ms at {} Hz: {}",
+                    ms,
+                    rco,
+                    actual
+                );
+            }
+        }
+
+        // Out of range periods saturate
+        assert_eq!(compute_wake_up_values(10_000.0, 34_700), (0xFF, 0xFF));
+    }
+
+    #[test]
+    fn rx_timeout_ms_range() {
+        let mut radio = MockSpirit1::new(50_000_000, 868_000_000);
+        let max = rx_timeout_ms(0xFF, 0xFF, 50_000_000);
+
+        assert!(matches!(
+            radio.set_rx_timeout_ms(0.0),
+            Err(RadioError::ParameterError)
+        ));
+        assert!(matches!(
+            radio.set_rx_timeout_ms(max + 0.1),
+            Err(RadioError::ParameterError)
+        ));
+
+        assert_eq!(radio.set_rx_timeout_ms(max).unwrap(), max);
+        assert_eq!(radio.get_rx_timeout_ms().unwrap(), Some(max));
+
+        let min = radio.set_rx_timeout_ms(0.01).unwrap();
+        assert_eq!(min, rx_timeout_ms(1, 0, 50_000_000));
+        assert_eq!(radio.get_rx_timeout_ms().unwrap(), Some(min));
+
+        radio.set_rx_timeout_infinite().unwrap();
+        assert_eq!(radio.get_rx_timeout_ms().unwrap(), None);
+    }
+}
@@ -0,0 +1,369 @@
+//! # embedded-hal Implementation
+//! A ready made [`Spirit1HalBlocking`] implementation on top of the `embedded-hal` 1.0 traits.
+//!
+//! Every SPI transaction starts with a two byte header: the first byte selects the
+//! operation ([`WRITE`], [`READ`] or [`COMMAND`]) and the second one is the register
+//! address or the command code. While the header is clocked out the SPIRIT1 returns
+//! the two `MC_STATE` status bytes on `MISO`.
+//!
+//! *See 10.1 Serial Peripheral Interface*
+//!
+//! ## Example
+//! ```no_run
+//! # use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
+//! # use spirit1_rs::{hal::Spirit1Hal, prelude::*};
+//! # fn open(spi: impl SpiDevice, sdn: impl OutputPin, delay: impl DelayNs) -> RadioResult<()> {
+//! let mut radio = Spirit1Hal::new(spi, Some(sdn), delay, 50_000_000, 868_000_000)?;
+//! radio.power_on()?;
+//!
+//! let device_info: DeviceInfo = radio.read_register()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `async` feature enabled, [`Spirit1AsyncHal`] additionally awaits the nIRQ GPIO.
+
+use core::convert::Infallible;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal::spi::{Operation, SpiDevice};
+
+use crate::prelude::*;
+use crate::{RadioError, RadioResult, COMMAND, READ, WORD, WRITE};
+
+/// Time to wait after releasing `SDN` for the power-on reset to complete
+const SHUTDOWN_EXIT_DELAY_MS: u32 = 2;
+
+/// SPIRIT1 radio attached to an `embedded-hal` [`SpiDevice`]
+///
+/// The shutdown pin (`SDN`) is optional, use [`NoPin`] when it is hard wired.
+pub struct Spirit1Hal<SPI, SDN, DELAY> {
+    spi: SPI,
+    sdn: Option<SDN>,
+    delay: DELAY,
+    xtal_frequency: u32,
+    base_frequency: u32,
+    band: BandSelect,
+    irq_latched: IrqSet,
+}
+
+impl<SPI, SDN, DELAY> Spirit1Hal<SPI, SDN, DELAY>
+where
+    SPI: SpiDevice,
+    SDN: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Create a new radio from its bus, shutdown pin and delay provider.
+    ///
+    /// `xtal_frequency` is the frequency of the crystal fitted to the board and
+    /// `base_frequency` the RF carrier base frequency (both in Hz). Returns
+    /// [`RadioError::FrequencyOutOfBand`] if the base frequency is not in a SPIRIT1 band.
+    pub fn new(
+        spi: SPI,
+        sdn: Option<SDN>,
+        delay: DELAY,
+        xtal_frequency: u32,
+        base_frequency: u32,
+    ) -> RadioResult<Self> {
+        let band = BandSelect::from_hz(base_frequency).ok_or(RadioError::FrequencyOutOfBand)?;
+
+        Ok(Self {
+            spi,
+            sdn,
+            delay,
+            xtal_frequency,
+            base_frequency,
+            band,
+            irq_latched: IrqSet::empty(),
+        })
+    }
+
+    /// Put the radio into `SHUTDOWN` by driving `SDN` high
+    pub fn shutdown(&mut self) -> RadioResult<()> {
+        if let Some(sdn) = self.sdn.as_mut() {
+            sdn.set_high().map_err(|_| RadioError::Gpio)?;
+        }
+
+        Ok(())
+    }
+
+    /// Exit `SHUTDOWN` by driving `SDN` low and wait for the power-on reset
+    pub fn power_on(&mut self) -> RadioResult<()> {
+        if let Some(sdn) = self.sdn.as_mut() {
+            sdn.set_low().map_err(|_| RadioError::Gpio)?;
+        }
+        self.delay.delay_ms(SHUTDOWN_EXIT_DELAY_MS);
+
+        Ok(())
+    }
+
+    /// Release the underlying peripherals
+    pub fn release(self) -> (SPI, Option<SDN>, DELAY) {
+        (self.spi, self.sdn, self.delay)
+    }
+}
+
+impl<SPI, SDN, DELAY> Spirit1HalBlocking for Spirit1Hal<SPI, SDN, DELAY>
+where
+    SPI: SpiDevice,
+    SDN: OutputPin,
+    DELAY: DelayNs,
+{
+    fn read_register<R>(&mut self) -> RadioResult<R>
+    where
+        R: Register<WORD> + ReadableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        let mut buffer = [0; R::LENGTH];
+        self.read_raw(R::ADDRESS, R::LENGTH, &mut buffer)?;
+
+        Ok(R::from_bytes(&buffer)?)
+    }
+
+    fn read_raw(&mut self, address: u8, length: usize, buffer: &mut [u8]) -> RadioResult<()> {
+        if length > buffer.len() {
+            return Err(RadioError::BufferTooSmall);
+        }
+
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&[READ, address]),
+                Operation::Read(&mut buffer[..length]),
+            ])
+            .map_err(|_| RadioError::Spi)
+    }
+
+    fn write_register<R>(&mut self, value: R) -> RadioResult<()>
+    where
+        R: WriteableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        self.write_raw(R::ADDRESS, &value.into_bytes()?)
+    }
+
+    fn write_raw(&mut self, base: u8, value: &[u8]) -> RadioResult<()> {
+        self.spi
+            .transaction(&mut [Operation::Write(&[WRITE, base]), Operation::Write(value)])
+            .map_err(|_| RadioError::Spi)
+    }
+
+    fn write_command(&mut self, command: SpiritCommand) -> RadioResult<McState> {
+        trace!("command {}", command);
+        // The status bytes are shifted out while the header is shifted in
+        let mut frame = [COMMAND, command.try_into()?];
+        self.spi
+            .transfer_in_place(&mut frame)
+            .map_err(|_| RadioError::Spi)?;
+
+        Ok(McState::from_bytes(&frame)?)
+    }
+
+    fn get_xtal_frequency(&self) -> u32 {
+        self.xtal_frequency
+    }
+
+    fn get_base_frequency(&self) -> u32 {
+        self.base_frequency
+    }
+
+    fn get_frequency_band(&self) -> BandSelect {
+        self.band.clone()
+    }
+
+    fn store_base_frequency(&mut self, frequency: u32) {
+        if let Some(band) = BandSelect::from_hz(frequency) {
+            self.base_frequency = frequency;
+            self.band = band;
+        }
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms);
+    }
+
+    fn irq_latched(&mut self) -> Option<&mut IrqSet> {
+        Some(&mut self.irq_latched)
+    }
+}
+
+/// Placeholder for a hard wired `SDN` pin
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Async SPIRIT1 radio attached to an `embedded-hal-async` [`SpiDevice`](embedded_hal_async::spi::SpiDevice)
+///
+/// `IRQ` is the MCU input wired to the SPIRIT1 GPIO configured as nIRQ.
+#[cfg(feature = "async")]
+pub struct Spirit1AsyncHal<SPI, SDN, IRQ, DELAY> {
+    spi: SPI,
+    sdn: Option<SDN>,
+    irq: IRQ,
+    delay: DELAY,
+    xtal_frequency: u32,
+    base_frequency: u32,
+    band: BandSelect,
+    irq_latched: IrqSet,
+}
+
+#[cfg(feature = "async")]
+impl<SPI, SDN, IRQ, DELAY> Spirit1AsyncHal<SPI, SDN, IRQ, DELAY>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    SDN: OutputPin,
+    IRQ: embedded_hal_async::digital::Wait,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    /// Create a new radio from its bus, shutdown pin, nIRQ input and delay provider.
+    ///
+    /// See [`Spirit1Hal::new`]
+    pub fn new(
+        spi: SPI,
+        sdn: Option<SDN>,
+        irq: IRQ,
+        delay: DELAY,
+        xtal_frequency: u32,
+        base_frequency: u32,
+    ) -> RadioResult<Self> {
+        let band = BandSelect::from_hz(base_frequency).ok_or(RadioError::FrequencyOutOfBand)?;
+
+        Ok(Self {
+            spi,
+            sdn,
+            irq,
+            delay,
+            xtal_frequency,
+            base_frequency,
+            band,
+            irq_latched: IrqSet::empty(),
+        })
+    }
+
+    /// Put the radio into `SHUTDOWN` by driving `SDN` high
+    pub fn shutdown(&mut self) -> RadioResult<()> {
+        if let Some(sdn) = self.sdn.as_mut() {
+            sdn.set_high().map_err(|_| RadioError::Gpio)?;
+        }
+
+        Ok(())
+    }
+
+    /// Exit `SHUTDOWN` by driving `SDN` low and wait for the power-on reset
+    pub async fn power_on(&mut self) -> RadioResult<()> {
+        if let Some(sdn) = self.sdn.as_mut() {
+            sdn.set_low().map_err(|_| RadioError::Gpio)?;
+        }
+        self.delay.delay_ms(SHUTDOWN_EXIT_DELAY_MS).await;
+
+        Ok(())
+    }
+
+    /// Release the underlying peripherals
+    pub fn release(self) -> (SPI, Option<SDN>, IRQ, DELAY) {
+        (self.spi, self.sdn, self.irq, self.delay)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<SPI, SDN, IRQ, DELAY> Spirit1HalAsync for Spirit1AsyncHal<SPI, SDN, IRQ, DELAY>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    SDN: OutputPin,
+    IRQ: embedded_hal_async::digital::Wait,
+    DELAY: embedded_hal_async::delay::DelayNs,
+{
+    async fn read_register<R>(&mut self) -> RadioResult<R>
+    where
+        R: Register<WORD> + ReadableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        let mut buffer = [0; R::LENGTH];
+        self.read_raw(R::ADDRESS, R::LENGTH, &mut buffer).await?;
+
+        Ok(R::from_bytes(&buffer)?)
+    }
+
+    async fn read_raw(&mut self, address: u8, length: usize, buffer: &mut [u8]) -> RadioResult<()> {
+        if length > buffer.len() {
+            return Err(RadioError::BufferTooSmall);
+        }
+
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&[READ, address]),
+                Operation::Read(&mut buffer[..length]),
+            ])
+            .await
+            .map_err(|_| RadioError::Spi)
+    }
+
+    async fn write_register<R>(&mut self, value: R) -> RadioResult<()>
+    where
+        R: WriteableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        self.write_raw(R::ADDRESS, &value.into_bytes()?).await
+    }
+
+    async fn write_raw(&mut self, base: u8, value: &[u8]) -> RadioResult<()> {
+        self.spi
+            .transaction(&mut [Operation::Write(&[WRITE, base]), Operation::Write(value)])
+            .await
+            .map_err(|_| RadioError::Spi)
+    }
+
+    async fn write_command(&mut self, command: SpiritCommand) -> RadioResult<McState> {
+        trace!("command {}", command);
+        let mut frame = [COMMAND, command.try_into()?];
+        self.spi
+            .transfer_in_place(&mut frame)
+            .await
+            .map_err(|_| RadioError::Spi)?;
+
+        Ok(McState::from_bytes(&frame)?)
+    }
+
+    async fn wait_for_irq(&mut self) -> RadioResult<()> {
+        self.irq.wait_for_low().await.map_err(|_| RadioError::Gpio)
+    }
+
+    fn get_xtal_frequency(&self) -> u32 {
+        self.xtal_frequency
+    }
+
+    fn get_base_frequency(&self) -> u32 {
+        self.base_frequency
+    }
+
+    fn get_frequency_band(&self) -> BandSelect {
+        self.band.clone()
+    }
+
+    fn store_base_frequency(&mut self, frequency: u32) {
+        if let Some(band) = BandSelect::from_hz(frequency) {
+            self.base_frequency = frequency;
+            self.band = band;
+        }
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms).await;
+    }
+
+    fn irq_latched(&mut self) -> Option<&mut IrqSet> {
+        Some(&mut self.irq_latched)
+    }
+}
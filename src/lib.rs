@@ -37,7 +37,7 @@
 // Nightly! We use generic const expressions for this driver!
 #![feature(generic_const_exprs)]
 
-use prelude::{registers::BandSelect, McState, SpiritCommand};
+use prelude::{registers::BandSelect, IrqSet, McState, SpiritCommand};
 use register_rs::*;
 
 pub mod registers;
@@ -46,6 +46,31 @@ pub use driver::*;
 
 pub mod constants;
 
+pub mod hal;
+
+pub mod wmbus;
+
+pub mod crypto;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+/// Discards the defmt frames logged by the unit tests, which run on the host
+#[cfg(test)]
+mod test_logger {
+    #[defmt::global_logger]
+    struct Logger;
+
+    unsafe impl defmt::Logger for Logger {
+        fn acquire() {}
+        unsafe fn flush() {}
+        unsafe fn release() {}
+        unsafe fn write(_bytes: &[u8]) {}
+    }
+
+    defmt::timestamp!("{=u64}", 0);
+}
+
 
 /// Prelude
 pub mod prelude {
@@ -55,17 +80,57 @@ pub mod prelude {
     pub use defmt::{error, info, trace, debug};
 }
 
-pub trait Spirit1: SpiritPacketFormats + Spirit1Driver + SpiritIrq {}
+pub trait Spirit1: SpiritPacketFormats + Spirit1Driver + SpiritIrq + SpiritAutoAck + SpiritCsma + SpiritLdc + SpiritAes + SpiritGpio + SpiritDirect {}
+
+#[cfg(feature = "async")]
+pub trait Spirit1Async: SpiritPacketFormatsAsync + Spirit1DriverAsync + SpiritIrqAsync {}
 
 /// Error
 #[derive(Clone, Copy, Debug, defmt::Format)]
 pub enum RadioError {
     /// SpiDevice Error
     Spi,
+    /// GPIO Error (`SDN` or nIRQ pin)
+    Gpio,
     /// Woopsie
     Invalid,
     /// Parameters supplied resulted in run-time checks failing
     ParameterError,
+    /// Carrier frequency outside of the SPIRIT1 bands, or of the band of the base frequency
+    FrequencyOutOfBand,
+    /// Data rate outside of `[100, 500000]` bps
+    DataRateOutOfRange,
+    /// Frequency deviation outside of `[F_Xo*8/2^19, F_Xo*7680/2^19]` Hz
+    DeviationOutOfRange,
+    /// Channel filter bandwidth outside of `[1100, 800100] * F_dig/26 MHz` Hz
+    BandwidthOutOfRange,
+    /// Channel spacing above `F_Xo/2^15*255` Hz
+    ChannelSpaceOutOfRange,
+    /// Crystal frequency offset outside of `[-F_Xo/2^18*2048, F_Xo/2^18*2047]` Hz
+    FrequencyOffsetOutOfRange,
+    /// The synthesizer failed to lock during the VCO calibration
+    VcoCalibration,
+    /// A blocking wait timed out, with the last observed `MC_STATE`
+    Timeout(McState),
+    /// Packet longer than the 65535 bytes supported by the packet handler, or than the TX
+    /// FIFO where the packet is not streamed
+    PacketTooLong,
+    /// Received packet does not fit in the supplied buffer
+    BufferTooSmall,
+    /// TX FIFO underflow or RX FIFO overflow while streaming
+    FifoError,
+    /// Frame check sequence of a received frame does not match its content
+    CrcMismatch,
+    /// Frame fields are inconsistent with its length or coding
+    InvalidFrame,
+    /// Packet not acknowledged after the maximum number of retransmissions
+    MaxRetransmissionsReached,
+    /// Channel found busy after the maximum number of CSMA back-offs
+    MaxBoCcaReached,
+    /// Authentication tag of a decrypted payload does not match
+    AuthenticationFailed,
+    /// GPIO mode not available on the pin, or input already selected on another pin
+    InvalidGpioMode,
     /// Yeah haven't got to that yet
     NotImplemented
 }
@@ -87,7 +152,42 @@ pub trait Spirit1HalBlocking {
     fn get_xtal_frequency(&self) -> u32;
     fn get_base_frequency(&self) -> u32;
     fn get_frequency_band(&self) -> BandSelect;
-    fn delay_ms(&self, ms: u32);
+    /// Records the base frequency programmed by [`Spirit1Driver::set_base_frequency`], returned
+    /// from then on by `get_base_frequency` and `get_frequency_band`
+    fn store_base_frequency(&mut self, frequency: u32);
+    fn delay_ms(&mut self, ms: u32);
+    /// Storage for the events read out of `IRQ_STATUS` while the driver waits for other
+    /// events, handed back by the next [`SpiritIrq::irq_take`]. Without storage (the
+    /// default) these events are lost
+    fn irq_latched(&mut self) -> Option<&mut IrqSet> {
+        None
+    }
+}
+
+/// Async counterpart of [`Spirit1HalBlocking`]
+///
+/// Instead of polling `IRQ_STATUS`, the driver awaits the nIRQ line of the GPIO configured
+/// as `GpioMode::OutputLowPower(DigitalOutputMode::nIRQ)`.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait, reason = "the HAL futures are awaited in place, no `Send` bound is needed")]
+pub trait Spirit1HalAsync {
+    async fn read_register<R>(&mut self) -> RadioResult<R> where R: Register<WORD> + ReadableRegister<WORD> + defmt::Format, [(); R::LENGTH]: Sized,;
+    async fn read_raw(&mut self, address: u8, length: usize, buffer: &mut[u8]) -> RadioResult<()>;
+    async fn write_register<R>(&mut self, value: R) -> RadioResult<()> where R: WriteableRegister<WORD> + defmt::Format, [(); R::LENGTH]: Sized;
+    async fn write_raw(&mut self, base: u8, value: &[u8]) -> RadioResult<()>;
+    async fn write_command(&mut self, command: SpiritCommand) -> RadioResult<McState>;
+    /// Wait for the (active low) nIRQ line to be asserted
+    async fn wait_for_irq(&mut self) -> RadioResult<()>;
+    fn get_xtal_frequency(&self) -> u32;
+    fn get_base_frequency(&self) -> u32;
+    fn get_frequency_band(&self) -> BandSelect;
+    /// See [`Spirit1HalBlocking::store_base_frequency`]
+    fn store_base_frequency(&mut self, frequency: u32);
+    async fn delay_ms(&mut self, ms: u32);
+    /// See [`Spirit1HalBlocking::irq_latched`]
+    fn irq_latched(&mut self) -> Option<&mut IrqSet> {
+        None
+    }
 }
 
 /// Radio result
@@ -61,17 +61,19 @@ fn _configure_radio(radio: &mut impl Spirit1) -> RadioResult<()> {
 
     // Configure interrupt pin
     // We have GPIO0 and GPIO1 wired up
-    let gpio_0 = GpioConf::new(GpioMode::OutputLowPower(DigitalOutputMode::nIRQ));
-    radio.write_register(Gpio0Conf(gpio_0))?;
+    radio.configure_gpio(
+        GpioPin::Gpio0,
+        GpioMode::OutputLowPower(DigitalOutputMode::nIRQ),
+    )?;
 
     // Configure IRQ listener
     radio.irq_silence()?;
-    let mut irq = IrqMaskBuilder::new();
-    irq.set(InterruptEvent::TxDataSent)
-        .set(InterruptEvent::RxDataReady)
-        .set(InterruptEvent::RxDataDiscarded)
-        .set(InterruptEvent::TimerRxTimeout);
-    radio.write_register(IrqMask::from(irq))?;
+    radio.irq_enable([
+        InterruptEvent::TxDataSent,
+        InterruptEvent::RxDataReady,
+        InterruptEvent::RxDataDiscarded,
+        InterruptEvent::TimerRxTimeout,
+    ])?;
     radio.irq_clear()?;
 
     // Receiver Quality Indicator Configuration
@@ -85,7 +87,7 @@ fn _configure_radio(radio: &mut impl Spirit1) -> RadioResult<()> {
     radio.set_rssi_threshold(-120)?;
 
     // Timer Configuration ❔
-    // radio.set_rx_timeout(2000.0)?;
+    // radio.set_rx_timeout_ms(2000.0)?;
     radio.set_rx_timeout_stop_condition(RxTimeoutStopCondition::SqiAboveThreshold)?;
     
 
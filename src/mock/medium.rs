@@ -0,0 +1,543 @@
+//! # Air Medium
+//! An in-process radio channel linking several [`MockSpirit1`](super::MockSpirit1) nodes.
+//!
+//! A `TX` strobe on one node hands an [`AirFrame`] to the medium, which copies it to the
+//! inbox of every other node currently in `RX`. Each directed link has a [`LinkProfile`]
+//! describing the packet loss, the received signal strength and the propagation latency.
+//!
+//! The medium only carries frames: packet filtering (`PCKT_FLT_OPTIONS`) and the CRC check
+//! are performed by the receiving node with its own register configuration. A frame arriving
+//! at a full inbox is dropped and counted in [`Air::dropped`].
+//!
+//! STack frames requesting an ACK are acknowledged by the medium on behalf of every node with
+//! `PROTOCOL.AUTO_ACK` set whose packet filters accept them, with the register configuration
+//! the node had when it entered RX. The ACK travels back over the reverse link (loss and
+//! latency included) and carries the TX FIFO content of the node when piggybacking is
+//! enabled. The transmitter retransmits the frame on each RX timeout expiry, up to
+//! `NMAX_RETX` times.
+//!
+//! Every node keeps its own virtual clock (advanced by `delay_ms`). A frame is delivered
+//! once the receiver clock reaches the clock value it had at transmission time plus the
+//! link latency.
+//!
+//! ## Example
+//! ```
+//! # use spirit1_rs::mock::*;
+//! # fn main() -> Result<(), spirit1_rs::RadioError> {
+//! let air = Air::new(LinkProfile::default());
+//!
+//! let a = MockSpirit1::with_air(50_000_000, 868_000_000, &air)?;
+//! let b = MockSpirit1::with_air(50_000_000, 868_000_000, &air)?;
+//! let (a, b) = (a.node_id().unwrap(), b.node_id().unwrap());
+//! air.set_link(a, b, LinkProfile { loss_percent: 10, ..Default::default() });
+//! # Ok(())
+//! # }
+//! ```
+
+use core::cell::{Cell, RefCell};
+
+use crate::prelude::*;
+
+use super::MockPacket;
+
+/// Maximum number of nodes attached to one [`Air`]
+pub const MAX_NODES: usize = 8;
+
+/// Number of frames a node can have in flight towards it, see [`Air::dropped`]
+pub const INBOX_DEPTH: usize = 4;
+
+/// Handle of a node attached to an [`Air`]
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub struct NodeId(pub(super) usize);
+
+/// Channel characteristics of one directed link
+#[derive(Clone, Copy, Debug, defmt::Format)]
+pub struct LinkProfile {
+    /// Probability for a frame to be lost, in percent (`0..=100`)
+    pub loss_percent: u8,
+    /// Signal strength reported in `RSSI_LEVEL` by the receiver
+    pub rssi_dbm: i16,
+    /// Delay between the end of the transmission and the reception
+    pub latency_ms: u32,
+}
+
+impl Default for LinkProfile {
+    fn default() -> Self {
+        Self {
+            loss_percent: 0,
+            rssi_dbm: -60,
+            latency_ms: 0,
+        }
+    }
+}
+
+/// A packet on the air, as built by the transmitter packet handler
+#[derive(Clone, Copy)]
+pub struct AirFrame {
+    /// Payload (PCKTLEN bytes taken from the TX FIFO)
+    pub payload: MockPacket,
+    /// Number of address bytes (`PCKTCTRL4.ADDRESS_LEN`)
+    pub address_len: u8,
+    /// Destination address (transmitter `RX_SOURCE_ADDR`)
+    pub destination: u8,
+    /// Source address (transmitter `TX_SOURCE_ADDR`)
+    pub source: u8,
+    /// Number of control bytes (`PCKTCTRL4.CONTROL_LEN`)
+    pub control_len: u8,
+    /// `TX_CTRL_FIELD` in register order
+    pub control: [u8; 4],
+    /// CRC appended by the transmitter, `None` if `CrcMode::NoCrc`
+    pub crc: Option<u32>,
+    /// Sequence number, only sent in STack packets
+    pub sequence_number: u8,
+    /// `NO_ACK` field, set for the packets other than STack
+    pub no_ack: bool,
+    /// The frame is an ACK sent by the automatic acknowledgement
+    pub ack: bool,
+    /// Set by the medium: signal strength at the receiver
+    pub rssi_dbm: i16,
+    /// Set by the medium: receiver clock value at which the frame is received
+    pub deliver_at: u64,
+}
+
+impl AirFrame {
+    /// The frame asks the receiver for an ACK
+    pub fn requests_ack(&self) -> bool {
+        !self.no_ack && !self.ack
+    }
+}
+
+/// Configuration of a node in RX, captured when it enters RX so that the medium can
+/// acknowledge frames on its behalf
+#[derive(Clone, Copy)]
+pub(super) struct Listener {
+    /// Register file of the node
+    pub registers: [u8; 256],
+    /// ACK payload: the TX FIFO content if `PROTOCOL.PIGGYBACKING` is set
+    pub piggyback: Option<MockPacket>,
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    listener: Option<Listener>,
+    now: u64,
+    inbox: [Option<AirFrame>; INBOX_DEPTH],
+    dropped: usize,
+}
+
+impl Node {
+    const fn new() -> Self {
+        Self {
+            listener: None,
+            now: 0,
+            inbox: [None; INBOX_DEPTH],
+            dropped: 0,
+        }
+    }
+
+    /// Queue `frame`, counting it as dropped if the inbox is full
+    fn push(&mut self, frame: AirFrame) {
+        match self.inbox.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => *slot = Some(frame),
+            None => self.dropped += 1,
+        }
+    }
+}
+
+/// Shared radio channel. See the [module documentation](self)
+pub struct Air {
+    nodes: RefCell<[Node; MAX_NODES]>,
+    count: Cell<usize>,
+    links: RefCell<[[LinkProfile; MAX_NODES]; MAX_NODES]>,
+    rng: Cell<u32>,
+}
+
+impl Air {
+    /// Create a medium where every link uses `profile`
+    pub fn new(profile: LinkProfile) -> Self {
+        Self {
+            nodes: RefCell::new([Node::new(); MAX_NODES]),
+            count: Cell::new(0),
+            links: RefCell::new([[profile; MAX_NODES]; MAX_NODES]),
+            rng: Cell::new(0x2545_F491),
+        }
+    }
+
+    /// Seed the generator deciding which frames are lost
+    pub fn seed(&self, seed: u32) {
+        // xorshift must not be seeded with 0
+        self.rng.set(seed.max(1));
+    }
+
+    /// Set the profile of the link from `tx` to `rx`
+    pub fn set_link(&self, tx: NodeId, rx: NodeId, profile: LinkProfile) {
+        self.links.borrow_mut()[tx.0][rx.0] = profile;
+    }
+
+    /// Profile of the link from `tx` to `rx`
+    pub fn link(&self, tx: NodeId, rx: NodeId) -> LinkProfile {
+        self.links.borrow()[tx.0][rx.0]
+    }
+
+    /// Number of attached nodes
+    pub fn nodes(&self) -> usize {
+        self.count.get()
+    }
+
+    /// Allocate a node, `None` when [`MAX_NODES`] are already attached
+    pub(super) fn join(&self) -> Option<NodeId> {
+        let id = self.count.get();
+        if id == MAX_NODES {
+            return None;
+        }
+        self.count.set(id + 1);
+
+        Some(NodeId(id))
+    }
+
+    /// Number of frames dropped because the inbox of `node` was full
+    pub fn dropped(&self, node: NodeId) -> usize {
+        self.nodes.borrow()[node.0].dropped
+    }
+
+    pub(super) fn set_listener(&self, node: NodeId, listener: Option<Listener>) {
+        self.nodes.borrow_mut()[node.0].listener = listener;
+    }
+
+    pub(super) fn set_clock(&self, node: NodeId, now: u64) {
+        self.nodes.borrow_mut()[node.0].now = now;
+    }
+
+    /// Copy `frame` to every other listening node, applying the link profiles, and queue the
+    /// ACKs of the nodes acknowledging it in the inbox of the transmitter
+    pub(super) fn transmit(&self, from: NodeId, frame: AirFrame) {
+        let links = self.links.borrow();
+        let mut nodes = self.nodes.borrow_mut();
+        let sent_at = nodes[from.0].now;
+
+        let mut acks = [None; MAX_NODES];
+        for (id, node) in nodes.iter_mut().enumerate().take(self.count.get()) {
+            let Some(listener) = node.listener.filter(|_| id != from.0) else {
+                continue;
+            };
+
+            let link = links[from.0][id];
+            if self.roll() < link.loss_percent as u32 {
+                continue;
+            }
+
+            node.push(AirFrame {
+                rssi_dbm: link.rssi_dbm,
+                deliver_at: node.now + link.latency_ms as u64,
+                ..frame
+            });
+
+            let back = links[id][from.0];
+            if let Some(ack) = super::ack_frame(&listener, &frame) {
+                if self.roll() >= back.loss_percent as u32 {
+                    acks[id] = Some(AirFrame {
+                        rssi_dbm: back.rssi_dbm,
+                        deliver_at: sent_at + link.latency_ms as u64 + back.latency_ms as u64,
+                        ..ack
+                    });
+                }
+            }
+        }
+
+        for ack in acks.into_iter().flatten() {
+            nodes[from.0].push(ack);
+        }
+    }
+
+    /// Take the oldest frame that reached `node` by its current clock
+    pub(super) fn receive(&self, node: NodeId) -> Option<AirFrame> {
+        let mut nodes = self.nodes.borrow_mut();
+        let node = &mut nodes[node.0];
+        let now = node.now;
+
+        let index = node
+            .inbox
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| slot.map(|frame| (i, frame.deliver_at)))
+            .filter(|(_, deliver_at)| *deliver_at <= now)
+            .min_by_key(|(_, deliver_at)| *deliver_at)?
+            .0;
+
+        node.inbox[index].take()
+    }
+
+    /// xorshift32, returns a value in `0..100`
+    fn roll(&self) -> u32 {
+        let mut x = self.rng.get();
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng.set(x);
+
+        x % 100
+    }
+}
+
+/// CRC as appended by the packet handler for `mode`, `None` if `CrcMode::NoCrc`
+pub(super) fn crc(mode: &CrcMode, data: &[u8]) -> Option<u32> {
+    let (poly, width): (u32, u32) = match mode {
+        CrcMode::NoCrc => return None,
+        CrcMode::Crc0x07 => (0x07, 8),
+        CrcMode::Crc0x8005 => (0x8005, 16),
+        CrcMode::Crc0x1021 => (0x1021, 16),
+        CrcMode::Crc0x864CBF => (0x86_4CBF, 24),
+    };
+
+    let top = 1 << (width - 1);
+    let mask = (1u32 << width) - 1;
+
+    let mut crc = mask;
+    for byte in data {
+        crc ^= (*byte as u32) << (width - 8);
+        for _ in 0..8 {
+            crc = if crc & top != 0 {
+                (crc << 1) ^ poly
+            } else {
+                crc << 1
+            };
+        }
+        crc &= mask;
+    }
+
+    Some(crc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSpirit1;
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    fn stack_opts(crc_mode: CrcMode) -> STackProtocolOpts {
+        STackProtocolOpts {
+            preamble_length: PreambleLength::Bytes04,
+            sync_length: PacketSyncLength::Bytes04,
+            sync_words: (0x1A, 0x2B, 0x3C, 0x4D),
+            fix_var_length: PacketLengthMode::Variable,
+            packet_length_width: 7,
+            crc_mode,
+            control_length: PacketControlLength::Bytes0,
+            fec: false,
+            data_whitening: true,
+        }
+    }
+
+    /// STack node with address `address`, sending to `destination`
+    fn node<'a>(air: &'a Air, address: u8, destination: u8) -> MockSpirit1<'a> {
+        let mut radio = MockSpirit1::with_air(XTAL, BASE, air).unwrap();
+        radio.init(RadioInitOpts::default()).unwrap();
+        radio
+            .configure_stack(stack_opts(CrcMode::Crc0x1021))
+            .unwrap();
+        radio
+            .configure_stack_filter(STackAddressOpts {
+                filter_on_my_address: true,
+                my_address: address,
+                filter_on_multicast_address: false,
+                multicast_address: 0,
+                filter_on_broadcast_address: false,
+                broadcast_address: 0xFF,
+                filter_on_source_address: false,
+                source_reference_address: destination,
+                source_mask: 0,
+            })
+            .unwrap();
+
+        radio
+    }
+
+    /// Send `count` packets from `tx` to `rx`, returns the number received
+    fn exchange(tx: &mut MockSpirit1, rx: &mut MockSpirit1, count: usize) -> usize {
+        let mut received = 0;
+        for _ in 0..count {
+            rx.strobe_rx().unwrap();
+            tx.tx_blocking(b"ping").unwrap();
+            rx.delay_ms(1);
+
+            received += rx.irq_take().unwrap().contains(InterruptEvent::RxDataReady) as usize;
+            rx.write_command(SpiritCommand::SABORT).unwrap();
+        }
+
+        received
+    }
+
+    #[test]
+    fn lost_frames_are_not_received() {
+        let air = Air::new(LinkProfile::default());
+        let mut a = node(&air, 0x11, 0x22);
+        let mut b = node(&air, 0x22, 0x11);
+
+        let lossy = LinkProfile {
+            loss_percent: 100,
+            ..Default::default()
+        };
+        air.set_link(a.node_id().unwrap(), b.node_id().unwrap(), lossy);
+        assert_eq!(exchange(&mut a, &mut b, 10), 0);
+
+        // Only the configured direction is affected
+        assert_eq!(exchange(&mut b, &mut a, 10), 10);
+
+        air.seed(7);
+        let lossy = LinkProfile {
+            loss_percent: 50,
+            ..Default::default()
+        };
+        air.set_link(a.node_id().unwrap(), b.node_id().unwrap(), lossy);
+        let received = exchange(&mut a, &mut b, 100);
+        assert!((25..75).contains(&received), "received {}", received);
+    }
+
+    #[test]
+    fn receiver_reports_the_link_rssi() {
+        let air = Air::new(LinkProfile::default());
+        let mut a = node(&air, 0x11, 0x22);
+        let mut b = node(&air, 0x22, 0x11);
+
+        let weak = LinkProfile {
+            rssi_dbm: -95,
+            ..Default::default()
+        };
+        air.set_link(a.node_id().unwrap(), b.node_id().unwrap(), weak);
+
+        b.strobe_rx().unwrap();
+        a.tx_blocking(b"ping").unwrap();
+
+        let mut buffer = [0; 8];
+        let packet = b
+            .rx_blocking_timeout(&mut buffer, 10, TimeoutRecovery::Abort)
+            .unwrap();
+        assert_eq!(packet.rssi_dbm, -95);
+    }
+
+    #[test]
+    fn frames_arrive_after_the_link_latency() {
+        let air = Air::new(LinkProfile::default());
+        let mut a = node(&air, 0x11, 0x22);
+        let mut b = node(&air, 0x22, 0x11);
+
+        let slow = LinkProfile {
+            latency_ms: 5,
+            ..Default::default()
+        };
+        air.set_link(a.node_id().unwrap(), b.node_id().unwrap(), slow);
+
+        b.strobe_rx().unwrap();
+        a.tx_blocking(b"ping").unwrap();
+
+        b.delay_ms(4);
+        assert!(!b.irq_take().unwrap().contains(InterruptEvent::RxDataReady));
+
+        b.delay_ms(1);
+        assert!(b.irq_take().unwrap().contains(InterruptEvent::RxDataReady));
+    }
+
+    #[test]
+    fn receiver_filters_on_the_destination_address() {
+        let air = Air::new(LinkProfile::default());
+        let mut a = node(&air, 0x11, 0x33);
+        let mut b = node(&air, 0x22, 0x11);
+
+        b.strobe_rx().unwrap();
+        a.tx_blocking(b"ping").unwrap();
+        let irq = b.irq_take().unwrap();
+        assert!(irq.contains(InterruptEvent::RxDataDiscarded));
+        assert!(!irq.contains(InterruptEvent::RxDataReady));
+
+        a.set_destination_address(0x22).unwrap();
+        b.strobe_rx().unwrap();
+        a.tx_blocking(b"ping").unwrap();
+        assert!(b.irq_take().unwrap().contains(InterruptEvent::RxDataReady));
+    }
+
+    #[test]
+    fn receiver_rejects_a_crc_mismatch() {
+        let air = Air::new(LinkProfile::default());
+        let mut a = node(&air, 0x11, 0x22);
+        let mut b = node(&air, 0x22, 0x11);
+        b.configure_stack(stack_opts(CrcMode::Crc0x8005)).unwrap();
+
+        b.strobe_rx().unwrap();
+        a.tx_blocking(b"ping").unwrap();
+
+        let irq = b.irq_take().unwrap();
+        assert!(irq.contains(InterruptEvent::CrcError));
+        assert!(irq.contains(InterruptEvent::RxDataDiscarded));
+        assert!(!irq.contains(InterruptEvent::RxDataReady));
+    }
+
+    #[test]
+    fn full_inbox_counts_the_dropped_frames() {
+        let air = Air::new(LinkProfile {
+            latency_ms: 10,
+            ..Default::default()
+        });
+        let mut a = node(&air, 0x11, 0x22);
+        let mut b = node(&air, 0x22, 0x11);
+
+        b.strobe_rx().unwrap();
+        for _ in 0..INBOX_DEPTH + 2 {
+            a.tx_blocking(b"ping").unwrap();
+        }
+
+        assert_eq!(air.dropped(b.node_id().unwrap()), 2);
+        assert_eq!(air.dropped(a.node_id().unwrap()), 0);
+    }
+
+    #[test]
+    fn acknowledged_packet_completes_the_transmission() {
+        let air = Air::new(LinkProfile::default());
+        let mut a = node(&air, 0x11, 0x22);
+        let mut b = node(&air, 0x22, 0x11);
+        a.configure_auto_ack(AutoAckOpts::default()).unwrap();
+        b.configure_auto_ack(AutoAckOpts::default()).unwrap();
+
+        b.strobe_rx().unwrap();
+        let report = a
+            .send_reliable(0x22, b"ping", 100, TimeoutRecovery::Abort)
+            .unwrap();
+        assert_eq!(report.retransmissions, 0);
+        assert!(b.irq_take().unwrap().contains(InterruptEvent::RxDataReady));
+
+        // The sequence number moves on with every acknowledged packet
+        b.strobe_rx().unwrap();
+        let report = a
+            .send_reliable(0x22, b"ping", 100, TimeoutRecovery::Abort)
+            .unwrap();
+        assert_eq!(report.sequence_number, 1);
+    }
+
+    #[test]
+    fn unacknowledged_packet_is_retransmitted_nmax_retx_times() {
+        let air = Air::new(LinkProfile::default());
+        let mut a = node(&air, 0x11, 0x22);
+        let mut b = node(&air, 0x22, 0x11);
+        a.configure_auto_ack(AutoAckOpts {
+            max_retransmissions: 2,
+            ..Default::default()
+        })
+        .unwrap();
+        b.configure_auto_ack(AutoAckOpts::default()).unwrap();
+
+        let lossy = LinkProfile {
+            loss_percent: 100,
+            ..Default::default()
+        };
+        air.set_link(b.node_id().unwrap(), a.node_id().unwrap(), lossy);
+
+        b.strobe_rx().unwrap();
+        assert!(matches!(
+            a.send_reliable(0x22, b"ping", 100, TimeoutRecovery::Abort),
+            Err(RadioError::MaxRetransmissionsReached)
+        ));
+        assert_eq!(a.read_register::<TxPacketInfo>().unwrap().n_retx, 2);
+        assert_eq!(a.state(), SpiritState::READY);
+    }
+}
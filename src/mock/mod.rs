@@ -0,0 +1,1291 @@
+//! # SPIRIT1 Simulator
+//! A host-side model of the SPIRIT1 implementing [`Spirit1HalBlocking`], so the driver can be
+//! exercised without hardware. Enabled with the `mock` feature, and always built for the
+//! unit tests.
+//!
+//! The model consists of:
+//! - A 256 byte register file loaded with the reset values of the register definitions
+//! - The main controller state machine, driven by [`SpiritCommand`] strobes
+//! - The 96 byte TX and RX FIFOs, accessed through the linear FIFO address (`0xFF`)
+//! - The read-and-reset `IRQ_STATUS` register. Events are latched regardless of
+//!   `IRQ_MASK`, which only drives the simulated nIRQ line ([`MockSpirit1::irq_pending`])
+//! - The AES co-processor, computed with [`SoftAes128`](crate::crypto::SoftAes128) and
+//!   completing instantly
+//! - The CSMA/CA engine, sensing the channel state set with [`MockSpirit1::set_channel_busy`]
+//!
+//! Transmission and reception complete instantly: a `TX` strobe moves the TX FIFO content
+//! into [`MockSpirit1::take_transmitted`] and raises `TX_DATA_SENT`, a `RX` strobe delivers
+//! the packet queued with [`MockSpirit1::inject_rx_packet`] and raises `RX_DATA_READY`.
+//! Time only advances through [`Spirit1HalBlocking::delay_ms`].
+//!
+//! Packets longer than the FIFO content (up to [`MAX_PACKET_LENGTH`]) are streamed
+//! [`STREAM_BYTES_PER_MS`] bytes per millisecond (*see [`MockSpirit1::set_stream_rate`]*),
+//! raising the almost empty/full events on the `FIFO_CONFIG` thresholds and the FIFO errors
+//! on underflow and overflow. A STack packet requesting an ACK leaves the radio in RX until
+//! the ACK arrives (`TX_DATA_SENT`) or the RX timeout expired `NMAX_RETX` times
+//! (`MAX_RE_TX_REACH`).
+//!
+//! Several nodes created with [`MockSpirit1::with_air`] share an [`Air`] medium: packets sent
+//! by one node are received by the others, subject to their packet filters (*see [`medium`]*).
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::{mock::*, prelude::*};
+//! # fn main() -> Result<(), spirit1_rs::RadioError> {
+//! let mut radio = MockSpirit1::new(50_000_000, 868_000_000);
+//!
+//! radio.init(RadioInitOpts::default())?;
+//! radio.tx_blocking("Hello World".as_bytes())?;
+//!
+//! assert_eq!(radio.take_transmitted().unwrap().as_slice(), "Hello World".as_bytes());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::crypto::SoftAes128;
+use crate::prelude::*;
+use crate::{RadioError, RadioResult, WORD};
+
+pub mod medium;
+pub use medium::{Air, AirFrame, LinkProfile, NodeId};
+
+use medium::Listener;
+
+/// Depth of the TX and RX FIFOs
+pub const FIFO_LENGTH: usize = 96;
+
+/// Longest packet the simulator can send or receive, streamed through the FIFOs when
+/// longer than [`FIFO_LENGTH`]
+pub const MAX_PACKET_LENGTH: usize = 512;
+
+/// Default number of bytes moved between a FIFO and the air per millisecond of a streamed
+/// packet, see [`MockSpirit1::set_stream_rate`]
+pub const STREAM_BYTES_PER_MS: usize = 16;
+
+/// Address used to access the TX (write) and RX (read) FIFOs
+const LINEAR_FIFO_ADDRESS: u8 = 0xFF;
+
+/// `MC_STATE[1]` reserved bits as read from the device
+const MC_STATE_1_RESERVED: u8 = 0b0010_0000;
+
+/// A packet moved in or out of the simulated FIFOs
+#[derive(Clone, Copy)]
+pub struct MockPacket {
+    data: [u8; MAX_PACKET_LENGTH],
+    len: usize,
+}
+
+impl MockPacket {
+    const EMPTY: Self = Self {
+        data: [0; MAX_PACKET_LENGTH],
+        len: 0,
+    };
+
+    /// Returns `None` if `payload` is longer than [`MAX_PACKET_LENGTH`]
+    pub fn from_slice(payload: &[u8]) -> Option<Self> {
+        if payload.len() > MAX_PACKET_LENGTH {
+            return None;
+        }
+
+        let mut data = [0; MAX_PACKET_LENGTH];
+        data[..payload.len()].copy_from_slice(payload);
+
+        Some(Self {
+            data,
+            len: payload.len(),
+        })
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+/// Simulated 96 byte FIFO
+struct Fifo {
+    data: [u8; FIFO_LENGTH],
+    len: usize,
+}
+
+impl Fifo {
+    const fn new() -> Self {
+        Self {
+            data: [0; FIFO_LENGTH],
+            len: 0,
+        }
+    }
+
+    /// Push as many bytes as possible, returns `false` on overflow
+    fn push(&mut self, bytes: &[u8]) -> bool {
+        let count = bytes.len().min(FIFO_LENGTH - self.len);
+        self.data[self.len..self.len + count].copy_from_slice(&bytes[..count]);
+        self.len += count;
+
+        count == bytes.len()
+    }
+
+    /// Pop as many bytes as possible, returns `false` on underflow
+    fn pop(&mut self, out: &mut [u8]) -> bool {
+        let count = out.len().min(self.len);
+        out[..count].copy_from_slice(&self.data[..count]);
+        self.data.copy_within(count..self.len, 0);
+        self.len -= count;
+
+        count == out.len()
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+/// Load the reset value of every listed writeable register into the register file
+macro_rules! load_reset_values {
+    ($mock:expr, $($register:ty),* $(,)?) => {
+        $( $mock.load_reset_value::<$register>(); )*
+    };
+}
+
+/// Packet longer than the FIFO, moved through it while the time advances
+#[derive(Clone, Copy)]
+struct Stream {
+    packet: MockPacket,
+    /// Number of bytes already moved
+    done: usize,
+}
+
+/// Transmitted packet waiting for its ACK
+#[derive(Clone, Copy)]
+struct AckWait {
+    frame: AirFrame,
+    retransmissions: u8,
+    /// Clock value at which the RX timeout expires, `None` without RX timeout
+    deadline: Option<u64>,
+}
+
+/// Simulated SPIRIT1
+pub struct MockSpirit1<'a> {
+    registers: [u8; 256],
+    state: SpiritState,
+    tx_fifo: Fifo,
+    rx_fifo: Fifo,
+    irq_status: u32,
+    irq_latched: IrqSet,
+    pending_rx: Option<MockPacket>,
+    transmitted: Option<MockPacket>,
+    xtal_frequency: u32,
+    base_frequency: u32,
+    elapsed_ms: u64,
+    tx_seq_num: u8,
+    ack_wait: Option<AckWait>,
+    tx_stream: Option<Stream>,
+    rx_stream: Option<Stream>,
+    stream_rate: usize,
+    channel_busy: bool,
+    csma_pending: bool,
+    air: Option<(&'a Air, NodeId)>,
+}
+
+impl<'a> MockSpirit1<'a> {
+    /// Create a powered up radio in `READY` with all registers at their reset value
+    pub fn new(xtal_frequency: u32, base_frequency: u32) -> Self {
+        let mut mock = Self {
+            registers: [0; 256],
+            state: SpiritState::READY,
+            tx_fifo: Fifo::new(),
+            rx_fifo: Fifo::new(),
+            irq_status: 0,
+            irq_latched: IrqSet::empty(),
+            pending_rx: None,
+            transmitted: None,
+            xtal_frequency,
+            base_frequency,
+            elapsed_ms: 0,
+            tx_seq_num: 0,
+            ack_wait: None,
+            tx_stream: None,
+            rx_stream: None,
+            stream_rate: STREAM_BYTES_PER_MS,
+            channel_busy: false,
+            csma_pending: false,
+            air: None,
+        };
+        mock.reset();
+
+        mock
+    }
+
+    /// Create a radio attached to the `air` medium.
+    ///
+    /// Returns [`RadioError::ParameterError`] if the medium has no free node
+    pub fn with_air(xtal_frequency: u32, base_frequency: u32, air: &'a Air) -> RadioResult<Self> {
+        let id = air.join().ok_or(RadioError::ParameterError)?;
+
+        let mut mock = Self::new(xtal_frequency, base_frequency);
+        mock.air = Some((air, id));
+
+        Ok(mock)
+    }
+
+    /// Identifier on the attached medium
+    pub fn node_id(&self) -> Option<NodeId> {
+        self.air.map(|(_, id)| id)
+    }
+
+    /// Simulate a power-on reset (or the `SRES` command)
+    pub fn reset(&mut self) {
+        self.registers = [0; 256];
+
+        load_reset_values!(
+            self,
+            AnaFuncConf,
+            Gpio0Conf,
+            Gpio1Conf,
+            Gpio2Conf,
+            Gpio3Conf,
+            McuCkConf,
+            XoRcoTest,
+            SynthConfig,
+            IfOffsetAna,
+            Synt,
+            ChSpace,
+            IfOffsetDig,
+            FcOffset,
+            PaPower8,
+            PaPower7,
+            PaPower6,
+            PaPower5,
+            PaPower4,
+            PaPower3,
+            PaPower2,
+            PaPower1,
+            PaPower,
+            Modulation,
+            FreqDev0,
+            ChFlt,
+            Afc2,
+            Afc1,
+            Afc0,
+            RssiFlt,
+            RssiTh,
+            ClockRec,
+            AgcCtrl2,
+            AgcCtrl1,
+            AgcCtrl0,
+            AntSelectConf,
+            PcktCtrl4,
+            PcktCtrl3,
+            PcktCtrl2,
+            PcktCtrl1,
+            PcktLen,
+            Sync4,
+            Sync3,
+            Sync2,
+            Sync1,
+            QI,
+            MbusPRMBL,
+            MbusPSTMBL,
+            MbusCtrl,
+            FifoConfig,
+            PcktFltGoals,
+            PcktFltOptions,
+            Protocol,
+            Timers,
+            CsmaConfig,
+            TxCtrlField,
+            PmConfig,
+            XoRcoConfig,
+            TestSelect,
+            PmTest,
+            ChNum,
+            VcoConfig,
+            RcoVcoCalibrIn,
+            AesKeyIn,
+            AesDataIn,
+            IrqMask,
+            DemConfig,
+        );
+
+        // Read only registers with a non zero reset value
+        self.store(DeviceInfo::ADDRESS, &[0x01, 0x30]);
+
+        self.ack_wait = None;
+        self.tx_stream = None;
+        self.rx_stream = None;
+        self.csma_pending = false;
+        self.tx_seq_num = 0;
+        self.set_state(SpiritState::READY);
+        self.tx_fifo.clear();
+        self.rx_fifo.clear();
+        self.irq_status = 0;
+    }
+
+    /// Current main controller state
+    pub fn state(&self) -> SpiritState {
+        self.state
+    }
+
+    /// Raw value of the register at `address`, without read side effects
+    pub fn register(&self, address: u8) -> u8 {
+        self.registers[address as usize]
+    }
+
+    /// Overwrite the register at `address`, e.g. to set a read-only register
+    pub fn set_register(&mut self, address: u8, value: u8) {
+        self.registers[address as usize] = value;
+    }
+
+    /// Latch `event` in `IRQ_STATUS`
+    pub fn raise_irq(&mut self, event: InterruptEvent) {
+        self.irq_status |= event.bit();
+    }
+
+    /// Level of the simulated nIRQ line: `true` while an unmasked event is latched
+    pub fn irq_pending(&self) -> bool {
+        let mask = u32::from_be_bytes([
+            self.registers[IrqMask::ADDRESS as usize],
+            self.registers[IrqMask::ADDRESS as usize + 1],
+            self.registers[IrqMask::ADDRESS as usize + 2],
+            self.registers[IrqMask::ADDRESS as usize + 3],
+        ]);
+
+        self.irq_status & mask != 0
+    }
+
+    /// Queue a packet to be received at the next `RX` strobe
+    pub fn inject_rx_packet(&mut self, payload: &[u8]) -> RadioResult<()> {
+        self.pending_rx = Some(MockPacket::from_slice(payload).ok_or(RadioError::ParameterError)?);
+        self.try_receive()
+    }
+
+    /// Take the last transmitted packet
+    pub fn take_transmitted(&mut self) -> Option<MockPacket> {
+        self.transmitted.take()
+    }
+
+    /// Number of bytes of a streamed packet moved between a FIFO and the air per millisecond.
+    /// Above [`FIFO_LENGTH`] the TX FIFO underflows and the RX FIFO overflows
+    pub fn set_stream_rate(&mut self, bytes_per_ms: usize) {
+        self.stream_rate = bytes_per_ms;
+    }
+
+    /// Carrier sense seen by the CSMA/CA engine. On a busy channel the engine raises
+    /// `MAX_BO_CCA_REACH` at once, or in persistent mode holds the transmission until the
+    /// channel is free
+    pub fn set_channel_busy(&mut self, busy: bool) {
+        self.channel_busy = busy;
+    }
+
+    /// Simulated time elapsed through [`Spirit1HalBlocking::delay_ms`]
+    pub fn elapsed_ms(&self) -> u64 {
+        self.elapsed_ms
+    }
+
+    fn load_reset_value<R>(&mut self)
+    where
+        R: WriteableRegister<WORD>,
+        [(); R::LENGTH]: Sized,
+    {
+        if let Ok(bytes) = R::reset_value().into_bytes() {
+            self.store(R::ADDRESS, &bytes);
+        }
+    }
+
+    /// Read a register without the read side effects
+    fn peek<R>(&self) -> RadioResult<R>
+    where
+        R: ReadableRegister<WORD>,
+        [(); R::LENGTH]: Sized,
+    {
+        peek_in(&self.registers)
+    }
+
+    fn raw<const N: usize>(&self, base: u8) -> [u8; N] {
+        raw_in(&self.registers, base)
+    }
+
+    fn store(&mut self, base: u8, value: &[u8]) {
+        for (offset, byte) in value.iter().enumerate() {
+            self.registers[base.wrapping_add(offset as u8) as usize] = *byte;
+        }
+    }
+
+    /// Read a single register applying the read side effects
+    fn load(&mut self, address: u8) -> u8 {
+        match address {
+            0xC0 => {
+                let mut word = MC_STATE_1_RESERVED;
+                word |= ((self.tx_fifo.len == FIFO_LENGTH) as u8) << 2;
+                word |= ((self.rx_fifo.len == 0) as u8) << 1;
+                word
+            }
+            0xC1 => (Self::state_code(self.state) << 1) | 0x01,
+            0xE6 => self.tx_fifo.len as u8,
+            0xE7 => self.rx_fifo.len as u8,
+            0xFA..=0xFD => {
+                // IRQ_STATUS[3] is at 0xFA
+                let shift = 8 * (0xFD - address) as u32;
+                let byte = (self.irq_status >> shift) as u8;
+                self.irq_status &= !(0xFF << shift);
+                byte
+            }
+            _ => self.registers[address as usize],
+        }
+    }
+
+    fn mc_state(&mut self) -> RadioResult<McState> {
+        let status = [self.load(0xC0), self.load(0xC1)];
+        Ok(McState::from_bytes(&status)?)
+    }
+
+    /// `MC_STATE[0]` state code (*see Table 20*)
+    fn state_code(state: SpiritState) -> u8 {
+        match state {
+            SpiritState::STANDBY => 0x40,
+            SpiritState::SLEEP => 0x36,
+            SpiritState::READY => 0x03,
+            SpiritState::LOCK => 0x0F,
+            SpiritState::RX => 0x33,
+            SpiritState::TX => 0x5F,
+            SpiritState::SHUTDOWN | SpiritState::Invalid => 0x00,
+        }
+    }
+
+    fn set_state(&mut self, state: SpiritState) {
+        self.state = state;
+
+        // A transmitter waiting for its ACK does not receive other packets
+        if let Some((air, id)) = self.air {
+            let listening = state == SpiritState::RX && self.ack_wait.is_none();
+            air.set_listener(id, listening.then(|| self.listener()));
+        }
+    }
+
+    /// Snapshot of the configuration used by the medium to acknowledge on behalf of the node
+    fn listener(&self) -> Listener {
+        let piggybacking = self.peek::<Protocol>().is_ok_and(|p| p.piggybacking);
+
+        Listener {
+            registers: self.registers,
+            piggyback: piggybacking
+                .then(|| MockPacket::from_slice(&self.tx_fifo.data[..self.tx_fifo.len]))
+                .flatten(),
+        }
+    }
+
+    fn enter_ready(&mut self) {
+        if self.state != SpiritState::READY {
+            self.set_state(SpiritState::READY);
+            self.raise_irq(InterruptEvent::Ready);
+        }
+    }
+
+    /// Send `PCKTLEN` bytes out of the TX FIFO, streaming them if the FIFO does not hold the
+    /// whole packet.
+    ///
+    /// Returns [`RadioError::PacketTooLong`] if `PCKTLEN` exceeds [`MAX_PACKET_LENGTH`]
+    fn transmit(&mut self) -> RadioResult<()> {
+        let length = u16::from_be_bytes(self.raw(PcktLen::ADDRESS)) as usize;
+        if length > MAX_PACKET_LENGTH {
+            return Err(RadioError::PacketTooLong);
+        }
+
+        let protocol: Protocol = self.peek()?;
+        if protocol.csma_on && self.channel_busy {
+            if protocol.csma_pers_on {
+                self.set_state(SpiritState::TX);
+                self.csma_pending = true;
+            } else {
+                // All the `NBACKOFF_MAX` back-offs found the channel busy
+                self.raise_irq(InterruptEvent::MaxBackoffDuringCCA);
+            }
+            return Ok(());
+        }
+
+        self.set_state(SpiritState::TX);
+
+        let mut packet = MockPacket {
+            len: length,
+            ..MockPacket::EMPTY
+        };
+        if length > self.tx_fifo.len {
+            self.tx_stream = Some(Stream { packet, done: 0 });
+            return Ok(());
+        }
+
+        self.tx_fifo.pop(&mut packet.data[..length]);
+        self.send(packet)
+    }
+
+    /// Move the next bytes of the streamed packets between the FIFOs and the air, raising
+    /// the almost empty/full events on the `FIFO_CONFIG` thresholds
+    fn stream(&mut self) -> RadioResult<()> {
+        let fifo_config: FifoConfig = self.peek()?;
+
+        if let Some(mut stream) = self.tx_stream.take() {
+            let count = (stream.packet.len - stream.done).min(self.stream_rate);
+            let available = count.min(self.tx_fifo.len);
+            let done = stream.done;
+            self.tx_fifo
+                .pop(&mut stream.packet.data[done..done + available]);
+            stream.done += available;
+
+            if available < count {
+                // An underflow ends the transmission with the bytes sent so far
+                self.raise_irq(InterruptEvent::TxFifoError);
+                stream.packet.len = stream.done;
+                self.send(stream.packet)?;
+            } else if stream.done == stream.packet.len {
+                self.send(stream.packet)?;
+            } else {
+                if self.tx_fifo.len <= fifo_config.tx_ae_threshold as usize {
+                    self.raise_irq(InterruptEvent::TxFifoAlmostEmpty);
+                }
+                self.tx_stream = Some(stream);
+            }
+        }
+
+        if let Some(mut stream) = self.rx_stream.take() {
+            let count = (stream.packet.len - stream.done).min(self.stream_rate);
+            let done = stream.done;
+            if !self.rx_fifo.push(&stream.packet.data[done..done + count]) {
+                // The rest of the packet is lost
+                self.raise_irq(InterruptEvent::RxFifoError);
+                self.enter_ready();
+                return Ok(());
+            }
+            stream.done += count;
+
+            if self.rx_fifo.len >= fifo_config.rx_af_threshold as usize {
+                self.raise_irq(InterruptEvent::RxFifoAlmostFull);
+            }
+
+            if stream.done == stream.packet.len {
+                self.raise_irq(InterruptEvent::RxDataReady);
+                self.enter_ready();
+            } else {
+                self.rx_stream = Some(stream);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Put `packet`, taken out of the TX FIFO, on the air
+    fn send(&mut self, packet: MockPacket) -> RadioResult<()> {
+        let frame = self.air_frame(packet)?;
+        if let Some((air, id)) = self.air {
+            air.transmit(id, frame);
+        }
+        self.transmitted = Some(packet);
+
+        // `TX_DATA_SENT` waits for the ACK, see `poll_ack`
+        if frame.requests_ack() {
+            self.ack_wait = Some(AckWait {
+                frame,
+                retransmissions: 0,
+                deadline: self.ack_deadline()?,
+            });
+            self.set_state(SpiritState::RX);
+            return Ok(());
+        }
+
+        self.end_tx(0);
+        self.raise_irq(InterruptEvent::TxDataSent);
+        self.enter_ready();
+
+        Ok(())
+    }
+
+    /// Clock value at which an ACK wait started now times out, `None` if the RX timeout is
+    /// disabled
+    fn ack_deadline(&self) -> RadioResult<Option<u64>> {
+        let timers: Timers = self.peek()?;
+        if timers.rx_timeout_counter == 0 {
+            return Ok(None);
+        }
+
+        let ms = rx_timeout_ms(
+            timers.rx_timeout_counter,
+            timers.rx_timeout_prescaler,
+            self.xtal_frequency,
+        );
+
+        // Rounded up to the next millisecond, `f32::ceil` needs `std`
+        let whole = ms as u64;
+        let ms = whole + ((whole as f32) < ms) as u64;
+
+        Ok(Some(self.elapsed_ms + ms.max(1)))
+    }
+
+    /// Complete a TX sequence: update `TX_PCKT_INFO` and move to the next sequence number
+    fn end_tx(&mut self, retransmissions: u8) {
+        self.store(
+            TxPacketInfo::ADDRESS,
+            &[(self.tx_seq_num << 4) | retransmissions],
+        );
+        self.tx_seq_num = (self.tx_seq_num + 1) & 0b11;
+    }
+
+    /// Automatic retransmission: complete the TX sequence on the ACK, send the packet again
+    /// when the RX timeout expires or raise `MAX_RE_TX_REACH` after `NMAX_RETX` retries
+    fn poll_ack(&mut self) -> RadioResult<()> {
+        let Some(mut wait) = self.ack_wait else {
+            return Ok(());
+        };
+
+        if let Some((air, id)) = self.air {
+            while let Some(frame) = air.receive(id) {
+                if !frame.ack || frame.sequence_number != wait.frame.sequence_number {
+                    continue;
+                }
+
+                self.ack_wait = None;
+                self.end_tx(wait.retransmissions);
+                self.raise_irq(InterruptEvent::TxDataSent);
+                if frame.payload.len > 0 {
+                    self.deliver(frame.payload);
+                } else {
+                    self.enter_ready();
+                }
+
+                return Ok(());
+            }
+        }
+
+        if !wait
+            .deadline
+            .is_some_and(|deadline| self.elapsed_ms >= deadline)
+        {
+            return Ok(());
+        }
+
+        if wait.retransmissions < self.peek::<Protocol>()?.nmax_retx {
+            wait.retransmissions += 1;
+            wait.deadline = self.ack_deadline()?;
+            self.ack_wait = Some(wait);
+
+            if let Some((air, id)) = self.air {
+                air.transmit(id, wait.frame);
+            }
+        } else {
+            self.ack_wait = None;
+            self.end_tx(wait.retransmissions);
+            self.raise_irq(InterruptEvent::MaxReTxReached);
+            self.enter_ready();
+        }
+
+        Ok(())
+    }
+
+    /// Packet handler: wrap `payload` with the address, control and CRC fields
+    fn air_frame(&self, payload: MockPacket) -> RadioResult<AirFrame> {
+        let pckt_ctrl_4: PcktCtrl4 = self.peek()?;
+        let pckt_ctrl_3: PcktCtrl3 = self.peek()?;
+        let pckt_ctrl_1: PcktCtrl1 = self.peek()?;
+        let goals: PcktFltGoals = self.peek()?;
+        let stack = matches!(pckt_ctrl_3.pckt_frmt, PacketFormat::STack);
+
+        Ok(AirFrame {
+            payload,
+            address_len: pckt_ctrl_4.address_len,
+            destination: goals.rx_source_addr,
+            source: goals.tx_source_addr,
+            control_len: pckt_ctrl_4.control_len.min(4),
+            control: self.raw(TxCtrlField::ADDRESS),
+            crc: medium::crc(&pckt_ctrl_1.crc_mode, payload.as_slice()),
+            sequence_number: self.tx_seq_num,
+            no_ack: !stack || self.peek::<Protocol>()?.nack_tx,
+            ack: false,
+            rssi_dbm: 0,
+            deliver_at: 0,
+        })
+    }
+
+    /// Deliver the pending packet, or a frame from the medium, if the receiver is on
+    fn try_receive(&mut self) -> RadioResult<()> {
+        if self.state != SpiritState::RX {
+            return Ok(());
+        }
+
+        if self.ack_wait.is_some() {
+            return self.poll_ack();
+        }
+
+        if self.rx_stream.is_some() {
+            return Ok(());
+        }
+
+        if let Some(packet) = self.pending_rx.take() {
+            self.deliver(packet);
+        } else if let Some((air, id)) = self.air {
+            if let Some(frame) = air.receive(id) {
+                self.receive_frame(frame)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Packet handler: apply the `PCKT_FLT_OPTIONS` filters and the CRC check to `frame`
+    fn receive_frame(&mut self, frame: AirFrame) -> RadioResult<()> {
+        // ACKs only reach a transmitter waiting for them
+        if frame.ack {
+            return Ok(());
+        }
+
+        let rssi_level = ((frame.rssi_dbm as i32 + 130) * 2).clamp(0, 255) as u8;
+        self.store(RssiLevel::ADDRESS, &[rssi_level]);
+        self.store(RxAddrField::ADDRESS, &[frame.source, frame.destination]);
+        self.store(RxCtrlField::ADDRESS, &frame.control);
+        self.store(
+            CrcField::ADDRESS,
+            &frame.crc.unwrap_or(0).to_be_bytes()[1..],
+        );
+        self.store(
+            RxPacketInfo::ADDRESS,
+            &[((frame.no_ack as u8) << 2) | frame.sequence_number],
+        );
+
+        let filtered = filter(&self.registers, &frame)?;
+        if filtered.crc_error {
+            self.raise_irq(InterruptEvent::CrcError);
+        }
+
+        if filtered.accepted {
+            // The medium sent the TX FIFO content as the ACK payload
+            let protocol: Protocol = self.peek()?;
+            if frame.requests_ack() && protocol.auto_ack && protocol.piggybacking {
+                self.tx_fifo.clear();
+            }
+
+            self.deliver(frame.payload);
+        } else {
+            self.raise_irq(InterruptEvent::RxDataDiscarded);
+            self.enter_ready();
+        }
+
+        Ok(())
+    }
+
+    /// AES co-processor, when powered on by `ANA_FUNC_CONF.AES_ON`. The key and data
+    /// registers hold the most significant byte at the lowest address
+    fn run_aes(&mut self, command: SpiritCommand) -> RadioResult<()> {
+        if !self.peek::<AnaFuncConf>()?.aes_on {
+            return Ok(());
+        }
+
+        let mut key: [u8; AES_BLOCK_LENGTH] = self.raw(AesKeyIn::ADDRESS);
+        let mut data: [u8; AES_BLOCK_LENGTH] = self.raw(AesDataIn::ADDRESS);
+        key.reverse();
+        data.reverse();
+
+        let mut out = match command {
+            SpiritCommand::AES_ENCRYPT => SoftAes128::new(&key).encrypt(&data),
+            SpiritCommand::AES_KEY => SoftAes128::new(&key).decryption_key(),
+            SpiritCommand::AES_DECRYPT => SoftAes128::from_decryption_key(&key).decrypt(&data),
+            _ => SoftAes128::new(&key).decrypt(&data),
+        };
+        out.reverse();
+
+        self.store(AesDataOut::ADDRESS, &out);
+        self.raise_irq(InterruptEvent::OthersAesEndOfOperation);
+
+        Ok(())
+    }
+
+    /// Move a received packet to the RX FIFO, streaming it if longer than the FIFO
+    fn deliver(&mut self, packet: MockPacket) {
+        self.rx_fifo.clear();
+        self.store(RxPcktLen::ADDRESS, &(packet.len as u16).to_be_bytes());
+
+        if packet.len > FIFO_LENGTH {
+            self.rx_stream = Some(Stream { packet, done: 0 });
+            return;
+        }
+
+        self.rx_fifo.push(packet.as_slice());
+        self.raise_irq(InterruptEvent::RxDataReady);
+        self.enter_ready();
+    }
+}
+
+/// Read register `R` out of a register file
+fn peek_in<R>(registers: &[u8; 256]) -> RadioResult<R>
+where
+    R: ReadableRegister<WORD>,
+    [(); R::LENGTH]: Sized,
+{
+    Ok(R::from_bytes(&raw_in(registers, R::ADDRESS))?)
+}
+
+fn raw_in<const N: usize>(registers: &[u8; 256], base: u8) -> [u8; N] {
+    let mut bytes = [0; N];
+    for (offset, byte) in bytes.iter_mut().enumerate() {
+        *byte = registers[base.wrapping_add(offset as u8) as usize];
+    }
+
+    bytes
+}
+
+/// Outcome of the packet filters of a receiver
+struct Filtered {
+    accepted: bool,
+    crc_error: bool,
+}
+
+/// Packet handler filters of the receiver with the register file `registers`
+fn filter(registers: &[u8; 256], frame: &AirFrame) -> RadioResult<Filtered> {
+    let options: PcktFltOptions = peek_in(registers)?;
+    let goals: PcktFltGoals = peek_in(registers)?;
+    let pckt_ctrl_1: PcktCtrl1 = peek_in(registers)?;
+
+    let mut accepted = true;
+
+    if frame.address_len > 0 {
+        let filtering = options.dest_vs_source_addr
+            || options.dest_vs_multicast_addr
+            || options.dest_vs_broadcast_addr;
+        let matched = (options.dest_vs_source_addr && frame.destination == goals.tx_source_addr)
+            || (options.dest_vs_multicast_addr && frame.destination == goals.multicast)
+            || (options.dest_vs_broadcast_addr && frame.destination == goals.broadcast);
+
+        accepted &= !filtering || matched;
+    }
+
+    // The source field is only present in STack packets
+    if frame.address_len > 1 && options.source_filtering {
+        let mask = goals.rx_source_mask;
+        accepted &= frame.source & mask == goals.rx_source_addr & mask;
+    }
+
+    if options.control_filtering {
+        let mask: [u8; 4] = raw_in(registers, PcktFltGoals::ADDRESS);
+        let field: [u8; 4] = raw_in(registers, PcktFltGoals::ADDRESS + 4);
+
+        // Control byte n.0 is the last one in register order
+        accepted &= (4 - frame.control_len as usize..4)
+            .all(|i| frame.control[i] & mask[i] == field[i] & mask[i]);
+    }
+
+    let crc_error = medium::crc(&pckt_ctrl_1.crc_mode, frame.payload.as_slice()) != frame.crc;
+    accepted &= !(crc_error && options.crc_check);
+
+    Ok(Filtered {
+        accepted,
+        crc_error,
+    })
+}
+
+/// ACK sent by the node `listener` for `frame`, if it acknowledges it
+fn ack_frame(listener: &Listener, frame: &AirFrame) -> Option<AirFrame> {
+    let registers = &listener.registers;
+    let protocol: Protocol = peek_in(registers).ok()?;
+    if !frame.requests_ack() || !protocol.auto_ack || !filter(registers, frame).ok()?.accepted {
+        return None;
+    }
+
+    let goals: PcktFltGoals = peek_in(registers).ok()?;
+    let pckt_ctrl_1: PcktCtrl1 = peek_in(registers).ok()?;
+    let payload = listener.piggyback.unwrap_or(MockPacket::EMPTY);
+
+    Some(AirFrame {
+        payload,
+        address_len: 2,
+        destination: frame.source,
+        source: goals.tx_source_addr,
+        control_len: 0,
+        control: [0; 4],
+        crc: medium::crc(&pckt_ctrl_1.crc_mode, payload.as_slice()),
+        sequence_number: frame.sequence_number,
+        no_ack: true,
+        ack: true,
+        rssi_dbm: 0,
+        deliver_at: 0,
+    })
+}
+
+impl Spirit1HalBlocking for MockSpirit1<'_> {
+    fn read_register<R>(&mut self) -> RadioResult<R>
+    where
+        R: Register<WORD> + ReadableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        let mut buffer = [0; R::LENGTH];
+        self.read_raw(R::ADDRESS, R::LENGTH, &mut buffer)?;
+
+        Ok(R::from_bytes(&buffer)?)
+    }
+
+    fn read_raw(&mut self, address: u8, length: usize, buffer: &mut [u8]) -> RadioResult<()> {
+        self.try_receive()?;
+
+        if address == LINEAR_FIFO_ADDRESS {
+            if !self.rx_fifo.pop(&mut buffer[..length]) {
+                self.raise_irq(InterruptEvent::RxFifoError);
+            }
+            return Ok(());
+        }
+
+        for (offset, byte) in buffer[..length].iter_mut().enumerate() {
+            *byte = self.load(address.wrapping_add(offset as u8));
+        }
+
+        Ok(())
+    }
+
+    fn write_register<R>(&mut self, value: R) -> RadioResult<()>
+    where
+        R: WriteableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        self.write_raw(R::ADDRESS, &value.into_bytes()?)
+    }
+
+    fn write_raw(&mut self, base: u8, value: &[u8]) -> RadioResult<()> {
+        if base == LINEAR_FIFO_ADDRESS {
+            if !self.tx_fifo.push(value) {
+                self.raise_irq(InterruptEvent::TxFifoError);
+            }
+            return Ok(());
+        }
+
+        self.store(base, value);
+
+        Ok(())
+    }
+
+    fn write_command(&mut self, command: SpiritCommand) -> RadioResult<McState> {
+        // The status is shifted out before the command is executed
+        let status = self.mc_state()?;
+
+        match command {
+            SpiritCommand::TX => {
+                if matches!(self.state, SpiritState::READY | SpiritState::LOCK) {
+                    self.transmit()?;
+                }
+            }
+            SpiritCommand::RX => {
+                if matches!(self.state, SpiritState::READY | SpiritState::LOCK) {
+                    self.set_state(SpiritState::RX);
+                    self.try_receive()?;
+                }
+            }
+            SpiritCommand::READY => self.enter_ready(),
+            SpiritCommand::STANDBY => {
+                if self.state == SpiritState::READY {
+                    self.set_state(SpiritState::STANDBY);
+                    self.raise_irq(InterruptEvent::StandbyStateSwitching);
+                }
+            }
+            SpiritCommand::SLEEP => {
+                if self.state == SpiritState::READY {
+                    self.set_state(SpiritState::SLEEP);
+                }
+            }
+            SpiritCommand::LOCK_RX | SpiritCommand::LOCK_TX => {
+                if self.state == SpiritState::READY {
+                    self.set_state(SpiritState::LOCK);
+                    self.raise_irq(InterruptEvent::Lock);
+                }
+            }
+            SpiritCommand::SABORT => {
+                if matches!(self.state, SpiritState::RX | SpiritState::TX) {
+                    self.ack_wait = None;
+                    self.tx_stream = None;
+                    self.rx_stream = None;
+                    self.csma_pending = false;
+                    self.enter_ready();
+                }
+            }
+            SpiritCommand::S_RES => self.reset(),
+            SpiritCommand::FLUSH_RX_FIFO => self.rx_fifo.clear(),
+            SpiritCommand::FLUSH_TX_FIFO => self.tx_fifo.clear(),
+            SpiritCommand::AES_ENCRYPT
+            | SpiritCommand::AES_KEY
+            | SpiritCommand::AES_DECRYPT
+            | SpiritCommand::AES_KEY_DECRYPT => self.run_aes(command)?,
+            SpiritCommand::SEQUENCE_UPDATE => {
+                self.tx_seq_num = self.peek::<Protocol>()?.tx_seq_num_reload;
+            }
+            SpiritCommand::LDC_RELOAD => {}
+        }
+
+        Ok(status)
+    }
+
+    fn get_xtal_frequency(&self) -> u32 {
+        self.xtal_frequency
+    }
+
+    fn get_base_frequency(&self) -> u32 {
+        self.base_frequency
+    }
+
+    fn get_frequency_band(&self) -> BandSelect {
+        BandSelect::from_hz(self.base_frequency).unwrap_or(BandSelect::High)
+    }
+
+    fn store_base_frequency(&mut self, frequency: u32) {
+        self.base_frequency = frequency;
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.elapsed_ms += ms as u64;
+
+        if let Some((air, id)) = self.air {
+            air.set_clock(id, self.elapsed_ms);
+        }
+
+        if self.csma_pending && !self.channel_busy {
+            self.csma_pending = false;
+            let _ = self.transmit();
+        }
+
+        // Receive errors are reported through IRQ_STATUS
+        for _ in 0..ms {
+            if self.tx_stream.is_none() && self.rx_stream.is_none() {
+                break;
+            }
+            let _ = self.stream();
+        }
+        let _ = self.try_receive();
+    }
+
+    fn irq_latched(&mut self) -> Option<&mut IrqSet> {
+        Some(&mut self.irq_latched)
+    }
+}
+
+/// [`MockSpirit1`] behind the [`Spirit1HalAsync`] interface, every future completing
+/// immediately.
+///
+/// [`Spirit1HalAsync::wait_for_irq`] returns after 1 ms of simulated time whether or not
+/// nIRQ is asserted, the driver checking `IRQ_STATUS` after every wake-up
+#[cfg(feature = "async")]
+pub struct MockSpirit1Async<'a>(pub MockSpirit1<'a>);
+
+#[cfg(feature = "async")]
+impl Spirit1HalAsync for MockSpirit1Async<'_> {
+    async fn read_register<R>(&mut self) -> RadioResult<R>
+    where
+        R: Register<WORD> + ReadableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        Spirit1HalBlocking::read_register(&mut self.0)
+    }
+
+    async fn read_raw(&mut self, address: u8, length: usize, buffer: &mut [u8]) -> RadioResult<()> {
+        Spirit1HalBlocking::read_raw(&mut self.0, address, length, buffer)
+    }
+
+    async fn write_register<R>(&mut self, value: R) -> RadioResult<()>
+    where
+        R: WriteableRegister<WORD> + defmt::Format,
+        [(); R::LENGTH]: Sized,
+    {
+        Spirit1HalBlocking::write_register(&mut self.0, value)
+    }
+
+    async fn write_raw(&mut self, base: u8, value: &[u8]) -> RadioResult<()> {
+        Spirit1HalBlocking::write_raw(&mut self.0, base, value)
+    }
+
+    async fn write_command(&mut self, command: SpiritCommand) -> RadioResult<McState> {
+        Spirit1HalBlocking::write_command(&mut self.0, command)
+    }
+
+    async fn wait_for_irq(&mut self) -> RadioResult<()> {
+        Spirit1HalBlocking::delay_ms(&mut self.0, 1);
+        Ok(())
+    }
+
+    fn get_xtal_frequency(&self) -> u32 {
+        Spirit1HalBlocking::get_xtal_frequency(&self.0)
+    }
+
+    fn get_base_frequency(&self) -> u32 {
+        Spirit1HalBlocking::get_base_frequency(&self.0)
+    }
+
+    fn get_frequency_band(&self) -> BandSelect {
+        Spirit1HalBlocking::get_frequency_band(&self.0)
+    }
+
+    fn store_base_frequency(&mut self, frequency: u32) {
+        Spirit1HalBlocking::store_base_frequency(&mut self.0, frequency)
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        Spirit1HalBlocking::delay_ms(&mut self.0, ms)
+    }
+
+    fn irq_latched(&mut self) -> Option<&mut IrqSet> {
+        Spirit1HalBlocking::irq_latched(&mut self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XTAL: u32 = 50_000_000;
+    const BASE: u32 = 868_000_000;
+
+    fn basic_opts() -> BasicProtocolOpts {
+        BasicProtocolOpts {
+            preamble_length: PreambleLength::Bytes04,
+            sync_length: PacketSyncLength::Bytes04,
+            sync_words: (0x1A, 0x2B, 0x3C, 0x4D),
+            fix_var_length: PacketLengthMode::Variable,
+            packet_length_width: 7,
+            crc_mode: CrcMode::Crc0x1021,
+            control_length: PacketControlLength::Bytes0,
+            address_field: false,
+            fec: false,
+            data_whitening: true,
+        }
+    }
+
+    fn configured(radio: &mut MockSpirit1) {
+        radio.init(RadioInitOpts::default()).unwrap();
+        radio.configure_basic(basic_opts()).unwrap();
+    }
+
+    #[test]
+    fn init_programs_the_radio() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        radio.init(RadioInitOpts::default()).unwrap();
+
+        assert_eq!(radio.state(), SpiritState::READY);
+
+        let base = radio.get_base_frequency_from_device().unwrap();
+        assert!(base.abs_diff(BASE) < 100, "base frequency {}", base);
+
+        let config = radio.read_radio_config().unwrap();
+        assert!(config.data_rate.abs_diff(38_400) < 100);
+        assert!(config.frequency_deviation.abs_diff(20_000) < 1_000);
+        assert_eq!(config.channel_number, 0);
+
+        // The VCO calibration leaves the automatic calibration disabled
+        assert!(!radio.read_register::<Protocol>().unwrap().vco_calibration);
+    }
+
+    #[test]
+    fn init_rejects_invalid_options() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        let opts = RadioInitOpts {
+            data_rate: 600_000,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            radio.init(opts),
+            Err(RadioError::DataRateOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn configure_basic_packet() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        configured(&mut radio);
+
+        let pckt_ctrl_3: PcktCtrl3 = radio.read_register().unwrap();
+        assert!(matches!(pckt_ctrl_3.pckt_frmt, PacketFormat::Basic));
+        assert_eq!(pckt_ctrl_3.len_wid, 7);
+        assert_eq!(radio.read_register::<Sync1>().unwrap().sync1, 0x1A);
+        assert!(radio.read_register::<PcktFltOptions>().unwrap().crc_check);
+    }
+
+    #[test]
+    fn tx_blocking_sends_the_fifo() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        configured(&mut radio);
+
+        assert_eq!(radio.tx_blocking(b"Hello World").unwrap(), 11);
+        assert_eq!(radio.take_transmitted().unwrap().as_slice(), b"Hello World");
+        assert_eq!(radio.state(), SpiritState::READY);
+    }
+
+    #[test]
+    fn rx_blocking_reads_the_injected_packet() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        configured(&mut radio);
+
+        radio.inject_rx_packet(b"ping").unwrap();
+
+        let mut buffer = [0; 16];
+        let packet = radio.rx_blocking(&mut buffer).unwrap();
+        assert_eq!(packet.payload, b"ping");
+    }
+
+    #[test]
+    fn rx_blocking_checks_the_buffer_length() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        configured(&mut radio);
+
+        radio.inject_rx_packet(b"too long for the buffer").unwrap();
+
+        let mut buffer = [0; 8];
+        assert!(matches!(
+            radio.rx_blocking(&mut buffer),
+            Err(RadioError::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn tx_and_rx_over_the_air() {
+        let air = Air::new(LinkProfile::default());
+        let mut tx = MockSpirit1::with_air(XTAL, BASE, &air).unwrap();
+        let mut rx = MockSpirit1::with_air(XTAL, BASE, &air).unwrap();
+        configured(&mut tx);
+        configured(&mut rx);
+
+        rx.strobe_rx().unwrap();
+        tx.tx_blocking(b"Hello World").unwrap();
+
+        let mut buffer = [0; 32];
+        let packet = rx
+            .rx_blocking_timeout(&mut buffer, 10, TimeoutRecovery::Abort)
+            .unwrap();
+        assert_eq!(packet.payload, b"Hello World");
+        assert_eq!(packet.rssi_dbm, LinkProfile::default().rssi_dbm);
+    }
+
+    #[test]
+    fn streamed_packets_raise_the_fifo_thresholds() {
+        let mut radio = MockSpirit1::new(XTAL, BASE);
+        configured(&mut radio);
+
+        // 96 bytes in the TX FIFO, drained to the 48 byte threshold in 3 ms
+        radio
+            .write_raw(LINEAR_FIFO_ADDRESS, &[0xA5; FIFO_LENGTH])
+            .unwrap();
+        radio.write_register(PcktLen::new(200)).unwrap();
+        radio.write_command(SpiritCommand::TX).unwrap();
+        radio.delay_ms(2);
+        assert!(!radio
+            .irq_take()
+            .unwrap()
+            .contains(InterruptEvent::TxFifoAlmostEmpty));
+        radio.delay_ms(1);
+        assert!(radio
+            .irq_take()
+            .unwrap()
+            .contains(InterruptEvent::TxFifoAlmostEmpty));
+        radio.write_command(SpiritCommand::SABORT).unwrap();
+
+        radio.inject_rx_packet(&[0x5A; 200]).unwrap();
+        radio.write_command(SpiritCommand::RX).unwrap();
+        radio.delay_ms(2);
+        assert!(!radio
+            .irq_take()
+            .unwrap()
+            .contains(InterruptEvent::RxFifoAlmostFull));
+        radio.delay_ms(1);
+        assert!(radio
+            .irq_take()
+            .unwrap()
+            .contains(InterruptEvent::RxFifoAlmostFull));
+    }
+}
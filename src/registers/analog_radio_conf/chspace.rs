@@ -8,3 +8,55 @@ pub struct ChSpace {
     #[register(bits = "0..7", reset = 0xFC)]
     pub ch_spacing: u8,
 }
+
+impl ChSpace {
+    /// Channel spacing register for `spacing` Hz, rounded to the nearest `f_XO/2^15` step.
+    ///
+    /// Returns `None` if the spacing does not fit in 8 bits
+    pub fn from_hz(spacing: u32, xtal_frequency: u32) -> Option<Self> {
+        let xtal_frequency = xtal_frequency as u64;
+        let steps = ((spacing as u64) << 15) + xtal_frequency / 2;
+
+        u8::try_from(steps / xtal_frequency).ok().map(Self::new)
+    }
+
+    /// Channel spacing in Hz
+    pub fn spacing_hz(&self, xtal_frequency: u32) -> u32 {
+        ((self.ch_spacing as u64 * xtal_frequency as u64) >> 15) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XTAL: u32 = 50_000_000;
+
+    #[test]
+    fn spacing_round_trip() {
+        for xtal_frequency in [26_000_000, XTAL] {
+            for ch_spacing in 0..=u8::MAX {
+                let spacing = ChSpace::new(ch_spacing).spacing_hz(xtal_frequency);
+                let ch_space = ChSpace::from_hz(spacing, xtal_frequency).unwrap();
+                assert_eq!(ch_space.ch_spacing, ch_spacing);
+            }
+        }
+    }
+
+    #[test]
+    fn spacing_rounds_to_the_nearest_step() {
+        // One step is f_XO/2^15, 20 kHz is 13.1 steps
+        let ch_space = ChSpace::from_hz(20_000, XTAL).unwrap();
+        assert_eq!(ch_space.ch_spacing, 13);
+        assert_eq!(ch_space.spacing_hz(XTAL), 19_836);
+    }
+
+    #[test]
+    fn spacing_out_of_range() {
+        assert_eq!(ChSpace::new(u8::MAX).spacing_hz(XTAL), 389_099);
+        assert!(ChSpace::from_hz(389_099, XTAL).is_some());
+
+        // 255.6 steps rounds to 256
+        assert!(ChSpace::from_hz(390_000, XTAL).is_none());
+    }
+}
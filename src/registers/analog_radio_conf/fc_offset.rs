@@ -31,9 +31,55 @@ impl FcOffset {
         let bytes = fc_offset.to_be_bytes();
 
         Self {
-            fc_offset_msb: bytes[0],
+            fc_offset_msb: bytes[0] & 0x0F,
             fc_offset_lsb: bytes[1],
             ..Self::reset_value()
         }
     }
+
+    /// Carrier offset in steps of `f_XO/2^18`
+    pub fn fc_offset(&self) -> i16 {
+        let word = ((self.fc_offset_msb as u16 & 0x0F) << 8) | self.fc_offset_lsb as u16;
+
+        // Sign extend the 12 bit 2-complement value
+        ((word << 4) as i16) >> 4
+    }
+
+    /// Carrier offset in Hz
+    pub fn offset_hz(&self, xtal_frequency: u32) -> i32 {
+        ((self.fc_offset() as i64 * xtal_frequency as i64) >> 18) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fc_offset_round_trip() {
+        for fc_offset in -2048..=2047 {
+            let bytes = FcOffset::new(fc_offset).into_bytes().unwrap();
+            assert_eq!(FcOffset::from_bytes(&bytes).unwrap().fc_offset(), fc_offset);
+        }
+    }
+
+    #[test]
+    fn fc_offset_sign_extension() {
+        // Bit 11 is the sign of the 12 bit value
+        assert_eq!(FcOffset::new(-1).fc_offset(), -1);
+        assert_eq!(FcOffset::new(-2048).fc_offset(), -2048);
+        assert_eq!(FcOffset::new(2047).fc_offset(), 2047);
+
+        // Bits above the 12 bit value are dropped
+        assert_eq!(FcOffset::new(2048).fc_offset(), -2048);
+        assert_eq!(FcOffset::new(-2049).fc_offset(), 2047);
+    }
+
+    #[test]
+    fn offset_hz() {
+        // One step is f_XO/2^18, ~190.7 Hz for f_XO = 50 MHz
+        assert_eq!(FcOffset::new(0).offset_hz(50_000_000), 0);
+        assert_eq!(FcOffset::new(100).offset_hz(50_000_000), 19_073);
+        assert_eq!(FcOffset::new(-100).offset_hz(50_000_000), -19_074);
+    }
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 use register_rs::*;
 
 /// `SYNTx` register
-#[derive(Register)]
+#[derive(Register, defmt::Format)]
 #[register(address = 0x08, length = 4, endian = "little")]
 pub struct Synt {
     /// Set the charge pump current according to the VCO frequency
@@ -10,7 +10,7 @@ pub struct Synt {
     #[register(bits = "29..31", reset = 0b000)]
     pub wcp: u8,
     /// `SYNT` is a programmable 26-bit integer for configuring base carrier frequency.
-    #[register(bits = "3..28", reset = 0b01010_11101100_10000100_01100)]
+    #[register(bits = "3..28", reset = 0b01100_10000100_11101100_01010)]
     pub synt: u32,
     /// Synthesizer band select.
     ///
@@ -58,6 +58,86 @@ impl BandSelect {
             _ => None,
         }
     }
+
+    /// Band select factor `B`
+    pub fn factor(&self) -> u32 {
+        match self {
+            Self::VeryLow => 32,
+            Self::Low => 16,
+            Self::Middle => 12,
+            Self::High => 6,
+        }
+    }
+
+    /// Lowest base frequency using `VCO_H`, the VCO frequency being `f_base * B`
+    ///
+    /// From `SpiritRadioSetFrequencyBase`
+    pub fn vco_h_threshold(&self) -> u32 {
+        match self {
+            Self::VeryLow => 161_281_250,
+            Self::Low => 322_562_500,
+            Self::Middle => 430_083_334,
+            Self::High => 860_166_667,
+        }
+    }
+
+    /// Charge pump current word (`WCP`) for the base frequency `frequency` in this band
+    ///
+    /// *See Table 26*. From `SpiritRadioSearchWCP`
+    pub fn charge_pump_word(&self, frequency: u32) -> u8 {
+        charge_pump_word((frequency / 1_000_000) * self.factor())
+    }
+}
+
+/// VCO frequency (MHz) for each charge pump current word, *see Table 26*
+const VCO_FREQUENCIES: [u32; 16] = [
+    4644, 4708, 4772, 4836, 4902, 4966, 5030, 5095, 5161, 5232, 5303, 5375, 5448, 5519, 5592, 5663,
+];
+
+/// Charge pump current word for a VCO frequency of `vco_frequency` MHz: the index of the
+/// highest table frequency below `vco_frequency`, modulo 8
+fn charge_pump_word(vco_frequency: u32) -> u8 {
+    let i = if vco_frequency >= VCO_FREQUENCIES[15] {
+        15
+    } else {
+        VCO_FREQUENCIES[..15]
+            .iter()
+            .position(|f| vco_frequency <= *f)
+            .unwrap_or(15)
+            .saturating_sub(1)
+    };
+
+    (i % 8) as u8
+}
+
+impl Synt {
+    /// Compute the `SYNT` register for the base frequency `frequency` (Hz).
+    ///
+    /// `f_base = f_xo / (B * D / 2) * SYNT / 2^18` where `D` is `2` when `refdiv`
+    /// (`SYNTH_CONFIG.REFDIV`) is set. *See Equation 3*
+    ///
+    /// Returns `None` if the frequency is not in a SPIRIT1 band
+    pub fn from_frequency(frequency: u32, xtal_frequency: u32, refdiv: bool) -> Option<Self> {
+        let band_select = BandSelect::from_hz(frequency)?;
+        let d = if refdiv { 2 } else { 1 };
+
+        let synt = (frequency as u64 * (band_select.factor() * d) as u64 * (1 << 17))
+            / xtal_frequency as u64;
+
+        Some(Self {
+            wcp: band_select.charge_pump_word(frequency),
+            synt: synt as u32,
+            band_select,
+        })
+    }
+
+    /// Base frequency (Hz) programmed in this register, see [`Synt::from_frequency`]
+    pub fn frequency(&self, xtal_frequency: u32, refdiv: bool) -> u32 {
+        let d = if refdiv { 2 } else { 1 };
+
+        ((self.synt as u64 * xtal_frequency as u64)
+            / ((self.band_select.factor() * d) as u64 * (1 << 17))) as u32
+    }
 }
 
 impl ReadableRegister<u8> for Synt {
@@ -97,3 +177,48 @@ impl WriteableRegister<u8> for Synt {
         Ok(buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charge_pump_word_below_table() {
+        assert_eq!(charge_pump_word(0), 0);
+        assert_eq!(charge_pump_word(VCO_FREQUENCIES[0] - 1), 0);
+        assert_eq!(charge_pump_word(VCO_FREQUENCIES[0]), 0);
+        assert_eq!(charge_pump_word(4700), 0);
+    }
+
+    #[test]
+    fn charge_pump_word_table_boundaries() {
+        for i in 1..15 {
+            // A frequency of the table selects the word below it
+            assert_eq!(charge_pump_word(VCO_FREQUENCIES[i]), ((i - 1) % 8) as u8);
+            assert_eq!(charge_pump_word(VCO_FREQUENCIES[i] + 1), (i % 8) as u8);
+            assert_eq!(
+                charge_pump_word(VCO_FREQUENCIES[i] - 1),
+                ((i - 1) % 8) as u8
+            );
+        }
+    }
+
+    #[test]
+    fn charge_pump_word_above_table() {
+        assert_eq!(charge_pump_word(VCO_FREQUENCIES[15] - 1), 6);
+        assert_eq!(charge_pump_word(VCO_FREQUENCIES[15]), 7);
+        assert_eq!(charge_pump_word(u32::MAX), 7);
+    }
+
+    #[test]
+    fn charge_pump_word_from_base_frequency() {
+        // 783 MHz * 6 = 4698 MHz
+        assert_eq!(BandSelect::High.charge_pump_word(783_000_000), 0);
+        // 868 MHz * 6 = 5208 MHz, between 5161 and 5232
+        assert_eq!(BandSelect::High.charge_pump_word(868_000_000), 0);
+        // 433 MHz * 12 = 5196 MHz
+        assert_eq!(BandSelect::Middle.charge_pump_word(433_000_000), 0);
+        // 915 MHz * 6 = 5490 MHz, between 5448 and 5519
+        assert_eq!(BandSelect::High.charge_pump_word(915_000_000), 4);
+    }
+}
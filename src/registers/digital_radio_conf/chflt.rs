@@ -53,6 +53,18 @@ impl ChFlt {
         }
     }
 
+    /// Channel filter bandwidth (Hz) programmed in this register, *Table 32* scaled from
+    /// `f_dig = 26 MHz` to the actual digital clock.
+    ///
+    /// `f_dig` is `f_xo` if `pd_clkdiv` (the digital clock divider is disabled), `f_xo / 2`
+    /// otherwise
+    pub fn bandwidth_hz(&self, xtal_frequency: u32, pd_clkdiv: bool) -> u32 {
+        let f_dig = (if pd_clkdiv { xtal_frequency } else { xtal_frequency / 2 }) as u64;
+        let idx = (self.chflt_m as usize + 9 * self.chflt_e as usize).min(89);
+
+        (Self::BANDWIDTH_26M[idx] as u64 * 100 * f_dig / 26_000_000) as u32
+    }
+
     // pub fn calculate_chflt(bandwidth: u32, pd_clk: bool, xtal_frequency: u32) -> Self {
     //     let divider = if pd_clk { 2 } else { 1 };
 
@@ -97,3 +109,38 @@ impl ChFlt {
     //     Self::new(m, e)
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bandwidth_hz() {
+        // Reset value, M = 2 and E = 3
+        let flt = ChFlt::reset_value();
+        assert_eq!(flt.bandwidth_hz(26_000_000, true), 100_500);
+        assert_eq!(flt.bandwidth_hz(52_000_000, false), 100_500);
+        assert_eq!(flt.bandwidth_hz(50_000_000, false), 96_634);
+    }
+
+    #[test]
+    fn bandwidth_hz_edges() {
+        assert_eq!(ChFlt::new(0, 0).bandwidth_hz(26_000_000, true), 800_100);
+        assert_eq!(ChFlt::new(8, 9).bandwidth_hz(26_000_000, true), 1_100);
+
+        // Out of table values select the narrowest filter
+        assert_eq!(ChFlt::new(15, 15).bandwidth_hz(26_000_000, true), 1_100);
+    }
+
+    #[test]
+    fn bandwidth_decreases_with_the_filter_index() {
+        let mut last = u32::MAX;
+        for chflt_e in 0..10 {
+            for chflt_m in 0..9 {
+                let bandwidth = ChFlt::new(chflt_m, chflt_e).bandwidth_hz(26_000_000, true);
+                assert!(bandwidth <= last, "M = {}, E = {}", chflt_m, chflt_e);
+                last = bandwidth;
+            }
+        }
+    }
+}
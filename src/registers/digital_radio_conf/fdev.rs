@@ -28,7 +28,8 @@ impl FreqDev0 {
             a = (xtal_div * (7.5 * (1 << i) as f32)) as u32;
             e = i;
 
-            if frequency_deviation < a {
+            // `a` is the deviation of `M = 7`, still reachable with this exponent
+            if frequency_deviation <= a {
                 break;
             }
         }
@@ -52,4 +53,45 @@ impl FreqDev0 {
 
         (e, m)
     }
+
+    /// Frequency deviation (Hz) programmed in this register:
+    /// `f_dev = f_xo * (8 + M) * 2^E / 2^19` (*see Equation 10*)
+    pub fn deviation_hz(&self, xtal_frequency: u32) -> u32 {
+        ((xtal_frequency as u64 * (8 + self.fdev_m as u64)) << self.fdev_e >> 19) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deviation_hz() {
+        // Reset value, E = 4 and M = 5
+        assert_eq!(FreqDev0::reset_value().deviation_hz(26_000_000), 10_314);
+    }
+
+    #[test]
+    fn deviation_hz_edges() {
+        assert_eq!(FreqDev0::new(0, false, 0).deviation_hz(26_000_000), 396);
+        assert_eq!(FreqDev0::new(9, false, 7).deviation_hz(50_000_000), 732_421);
+    }
+
+    #[test]
+    fn deviation_round_trip() {
+        for xtal_frequency in [26_000_000, 50_000_000] {
+            for fdev_e in 0..10 {
+                for fdev_m in 0..8 {
+                    let deviation =
+                        FreqDev0::new(fdev_e, false, fdev_m).deviation_hz(xtal_frequency);
+                    assert_eq!(
+                        FreqDev0::calculate_fdev(deviation, xtal_frequency),
+                        (fdev_e, fdev_m),
+                        "{} Hz",
+                        deviation
+                    );
+                }
+            }
+        }
+    }
 }
@@ -4136,7 +4136,8 @@ impl Modulation {
     /// Returns the mantissa and exponent
     pub fn calculate_data_rate(data_rate: u32, pd_clkdiv: bool, xtal_frequency: u32) -> (u8, u8) {
         let mut found = false;
-        let divider = pd_clkdiv as i8;
+        // The digital clock is `xtal / 2` unless the divider is powered down
+        let divider = !pd_clkdiv as i8;
 
         let mut i: i8 = 15;
         while !found && i >= 0 {
@@ -4154,8 +4155,10 @@ impl Modulation {
         let mut mantissa_calculation: [i16; 3] = [0; 3];
 
         for j in 0..3 {
-            if mantissa + j - 1 == 1 {
-                mantissa_calculation[j as usize] = (data_rate - ( ( ( 256 + mantissa as u32 + j as u32 - 1 )  * ( xtal_frequency >> (5 + divider) ) ) >> ( 23 - i )) as u32 ) as i16;
+            // Candidate mantissas `M - 1`, `M` and `M + 1`, skipping a zero one
+            let candidate = mantissa as i32 + j - 1;
+            if candidate > 0 {
+                mantissa_calculation[j as usize] = (data_rate as i32 - ( ( ( 256 + candidate as u32 )  * ( xtal_frequency >> (5 + divider) ) ) >> ( 23 - i )) as i32 ) as i16;
             } else {
                 mantissa_calculation[j as usize] = 0x7FFF;
             }
@@ -4167,12 +4170,23 @@ impl Modulation {
         for j in 0..3 {
             if (mantissa_calculation[j].abs() as u16) < mantissa_delta {
                 mantissa_delta = mantissa_calculation[j].abs() as u16;
-                m = mantissa + j as u8 - 1;
+                m = (mantissa as i32 + j as i32 - 1) as u8;
             }
         }
 
         (m, i as u8)
     }
+
+    /// Data rate (bps) programmed in this register: `DR = f_dig * (256 + M) * 2^E / 2^28`
+    /// (*see Equation 14*)
+    ///
+    /// `f_dig` is `f_xo` if `pd_clkdiv` (the digital clock divider is disabled), `f_xo / 2`
+    /// otherwise
+    pub fn data_rate_hz(&self, xtal_frequency: u32, pd_clkdiv: bool) -> u32 {
+        let f_dig = (if pd_clkdiv { xtal_frequency } else { xtal_frequency / 2 }) as u64;
+
+        ((f_dig * (256 + self.data_rate_m as u64)) << self.data_rate_e >> 28) as u32
+    }
 }
 
 #[derive(TryValued, Clone, Debug, defmt::Format)]
@@ -65,3 +65,28 @@ pub struct RssiLevel {
     #[register(bits = "0..7", reset = 0)]
     pub rssi_level: u8,
 }
+
+impl RssiLevel {
+    /// RSSI of the received packet in dBm, with a 0.5 dB resolution rounded down
+    pub fn dbm(&self) -> i16 {
+        (self.rssi_level / 2) as i16 - 130
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rssi_level_dbm() {
+        assert_eq!(RssiLevel { rssi_level: 20 }.dbm(), -120);
+        assert_eq!(RssiLevel { rssi_level: 21 }.dbm(), -120);
+        assert_eq!(RssiLevel { rssi_level: 22 }.dbm(), -119);
+    }
+
+    #[test]
+    fn rssi_level_dbm_edges() {
+        assert_eq!(RssiLevel { rssi_level: 0 }.dbm(), -130);
+        assert_eq!(RssiLevel { rssi_level: u8::MAX }.dbm(), -3);
+    }
+}
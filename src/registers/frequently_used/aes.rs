@@ -6,18 +6,21 @@ const AES_DATA_LENGTH: usize = 16;
 const AES_DATA_OUT_ADDRESS_BASE: u8 = 0xD4;
 
 /// `AES_KEY_IN` register
+#[derive(defmt::Format)]
 pub struct AesKeyIn {
     /// AES engine key input (128 bits)
     pub key: [u8; AES_DATA_LENGTH]
 }
 
 /// `AES_DATA_IN` register
+#[derive(defmt::Format)]
 pub struct AesDataIn {
     /// AES engine data input (128 bits)
     pub data: [u8; AES_DATA_LENGTH]
 }
 
 /// `AES_DATA_OUT` register
+#[derive(defmt::Format)]
 pub struct AesDataOut {
     /// AES engine data output (128 bits)
     pub data: [u8; AES_DATA_LENGTH]
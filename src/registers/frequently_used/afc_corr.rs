@@ -8,3 +8,32 @@ pub struct AfcCorr {
     #[register(bits = "0..7", reset = 0)]
     pub afc_corr: u8,
 }
+
+impl AfcCorr {
+    /// Frequency correction applied by the AFC in Hz, the word being a two's complement
+    /// value in steps of `F_Xo/2^18`
+    pub fn afc_hz(&self, xtal_frequency: u32) -> i32 {
+        ((self.afc_corr as i8 as i64 * xtal_frequency as i64) >> 18) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XTAL: u32 = 50_000_000;
+
+    #[test]
+    fn afc_hz() {
+        // One step is f_XO/2^18, ~190.7 Hz for f_XO = 50 MHz
+        assert_eq!(AfcCorr { afc_corr: 0 }.afc_hz(XTAL), 0);
+        assert_eq!(AfcCorr { afc_corr: 1 }.afc_hz(XTAL), 190);
+        assert_eq!(AfcCorr { afc_corr: 0xFF }.afc_hz(XTAL), -191);
+    }
+
+    #[test]
+    fn afc_hz_edges() {
+        assert_eq!(AfcCorr { afc_corr: 0x7F }.afc_hz(XTAL), 24_223);
+        assert_eq!(AfcCorr { afc_corr: 0x80 }.afc_hz(XTAL), -24_415);
+    }
+}
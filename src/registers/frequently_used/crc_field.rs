@@ -19,3 +19,10 @@ pub struct CrcField {
     #[register(bits = "16..23", reset = 0)]
     pub crc0: u8,
 }
+
+impl CrcField {
+    /// CRC of the received packet, byte 0 being the least significant one
+    pub fn crc(&self) -> u32 {
+        u32::from_be_bytes([0, self.crc2, self.crc1, self.crc0])
+    }
+}
@@ -46,31 +46,31 @@ impl IrqMaskBuilder {
 
     /// Set
     pub fn set(&mut self, event: InterruptEvent) -> &mut Self {
-        // TODO: all TryFrom's are probably able to be Into for `TryValued` enums
-        let mask: u32 = event.try_into().unwrap();
-
-        self.0 |= mask;
+        self.0 |= event.bit();
 
         self
     }
 
-    // fn remove(&mut self, event: InterruptEvent) {
+    /// Remove
+    pub fn remove(&mut self, event: InterruptEvent) -> &mut Self {
+        self.0 &= !event.bit();
 
-    // }
+        self
+    }
 }
 
-// impl Into<IrqMask> for IrqMaskBuilder {
-//     fn into(self) -> IrqMask {
-//         IrqMask::new(self.0)
-//     }
-// }
-
 impl From<IrqMaskBuilder> for IrqMask {
     fn from(value: IrqMaskBuilder) -> Self {
         Self::new(value.0)
     }
 }
 
+impl From<IrqSet> for IrqMask {
+    fn from(value: IrqSet) -> Self {
+        Self::new(value.bits())
+    }
+}
+
 /// `IRQ_STATUS` register. Read & Reset type register
 #[derive(Register, defmt::Format, ReadableRegister)]
 #[register(address = 0xFA, length = 4, endian = "big")]
@@ -100,11 +100,12 @@ pub trait IrqType {
     fn as_u32(&self) -> u32;
 
     fn is_set(&self, event: InterruptEvent) -> bool {
-        let word = self.as_u32();
-        let pos: u32 = event.try_into().unwrap();
+        self.as_u32() & event.bit() != 0
+    }
 
-        // word.bit(pos)
-        word & pos > 1
+    /// Events flagged in the register
+    fn events(&self) -> IrqSet {
+        IrqSet::from_bits(self.as_u32())
     }
 }
 
@@ -141,7 +142,7 @@ impl IrqType for IrqStatus {
 /// for the events that have generated any interrupts. The interrupts are individually maskable
 /// using the IRQ_MASK[3:0] registers: if the mask bit related to a particular event is
 /// programmed at 0, that event does not generate any interrupt request.
-#[derive(TryValued)]
+#[derive(TryValued, Clone, Copy, Debug, PartialEq, defmt::Format)]
 #[valued(type = u32)]
 pub enum InterruptEvent {
     #[valued(0x00000001)]
@@ -194,9 +195,306 @@ pub enum InterruptEvent {
     BrownoutEvent,
     #[valued(0x00200000)]
     Lock,
+    // The following events are for debug purposes
+    #[valued(0x00400000)]
+    PmStartupTimerExpired,
+    #[valued(0x00800000)]
+    XoSettlingTimeout,
+    #[valued(0x01000000)]
+    SynthLockTimeout,
+    #[valued(0x02000000)]
+    SynthLockStartup,
+    #[valued(0x04000000)]
+    SynthCalibrationTimeout,
+    #[valued(0x08000000)]
+    TxCircuitsReady,
+    #[valued(0x10000000)]
+    RxCircuitsReady,
     #[valued(0x20000000)]
     TimerRxTimeout,
     #[valued(0x40000000)]
     OthersAesEndOfOperation,
-    // THERE ARE SOME DEBUG ONES TOO...
+}
+
+impl InterruptEvent {
+    /// Every event, ordered by bit position
+    pub const ALL: [InterruptEvent; 31] = [
+        Self::RxDataReady,
+        Self::RxDataDiscarded,
+        Self::TxDataSent,
+        Self::MaxReTxReached,
+        Self::CrcError,
+        Self::TxFifoError,
+        Self::RxFifoError,
+        Self::TxFifoAlmostFull,
+        Self::TxFifoAlmostEmpty,
+        Self::RxFifoAlmostFull,
+        Self::RxFifoAlmostEmpty,
+        Self::MaxBackoffDuringCCA,
+        Self::ValidPreambleDetected,
+        Self::SyncWordDetected,
+        Self::RssiAboveThreshold,
+        Self::WakeUpTimeout,
+        Self::Ready,
+        Self::StandbyStateSwitching,
+        Self::LowBatteryLevel,
+        Self::PowerOnReset,
+        Self::BrownoutEvent,
+        Self::Lock,
+        Self::PmStartupTimerExpired,
+        Self::XoSettlingTimeout,
+        Self::SynthLockTimeout,
+        Self::SynthLockStartup,
+        Self::SynthCalibrationTimeout,
+        Self::TxCircuitsReady,
+        Self::RxCircuitsReady,
+        Self::TimerRxTimeout,
+        Self::OthersAesEndOfOperation,
+    ];
+
+    /// Bit position of the event in `IRQ_STATUS`/`IRQ_MASK`
+    pub const fn position(self) -> u32 {
+        // Variants are declared in bit order without gaps, checked against the register
+        // values by the tests
+        self as u32
+    }
+
+    /// Single bit mask of the event in `IRQ_STATUS`/`IRQ_MASK`
+    pub const fn bit(self) -> u32 {
+        1 << self.position()
+    }
+}
+
+/// Set of [`InterruptEvent`]s, as stored in `IRQ_STATUS` and `IRQ_MASK`
+///
+/// ```no_run
+/// # use spirit1_rs::prelude::*;
+/// # fn enable(radio: &mut impl SpiritIrq) -> RadioResult<()> {
+/// let events = IrqSet::empty()
+///     .with(InterruptEvent::TxDataSent)
+///     .with(InterruptEvent::RxDataReady);
+///
+/// radio.irq_enable(events)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, defmt::Format)]
+pub struct IrqSet(u32);
+
+impl IrqSet {
+    /// Bits with an [`InterruptEvent`] attached (bit 31 is reserved)
+    const VALID: u32 = 0x7FFF_FFFF;
+
+    /// Set without any event
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Set with every event, including the debug ones
+    pub const fn all() -> Self {
+        Self(Self::VALID)
+    }
+
+    /// Build from a raw register word, reserved bits are dropped
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits & Self::VALID)
+    }
+
+    /// Raw register word
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, event: InterruptEvent) -> bool {
+        self.0 & event.bit() != 0
+    }
+
+    /// Copy of the set with `event` added
+    pub const fn with(self, event: InterruptEvent) -> Self {
+        Self(self.0 | event.bit())
+    }
+
+    /// Copy of the set with `event` removed
+    pub const fn without(self, event: InterruptEvent) -> Self {
+        Self(self.0 & !event.bit())
+    }
+
+    pub fn insert(&mut self, event: InterruptEvent) {
+        self.0 |= event.bit();
+    }
+
+    pub fn remove(&mut self, event: InterruptEvent) {
+        self.0 &= !event.bit();
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Events in the set, ordered by bit position
+    pub fn iter(&self) -> impl Iterator<Item = InterruptEvent> {
+        let bits = self.0;
+        InterruptEvent::ALL
+            .into_iter()
+            .filter(move |event| bits & event.bit() != 0)
+    }
+}
+
+impl From<InterruptEvent> for IrqSet {
+    fn from(value: InterruptEvent) -> Self {
+        Self(value.bit())
+    }
+}
+
+impl<const N: usize> From<[InterruptEvent; N]> for IrqSet {
+    fn from(value: [InterruptEvent; N]) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl FromIterator<InterruptEvent> for IrqSet {
+    fn from_iter<I: IntoIterator<Item = InterruptEvent>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::empty(), Self::with)
+    }
+}
+
+impl core::ops::BitOr for IrqSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl core::ops::BitOr<InterruptEvent> for IrqSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: InterruptEvent) -> Self {
+        self.with(rhs)
+    }
+}
+
+impl core::ops::BitOrAssign for IrqSet {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+impl core::ops::BitAnd for IrqSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.intersection(rhs)
+    }
+}
+
+impl core::ops::Not for IrqSet {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::from_bits(!self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_positions_match_the_register_bits() {
+        for (position, event) in InterruptEvent::ALL.into_iter().enumerate() {
+            assert_eq!(event.position(), position as u32);
+            assert_eq!(1 << event.position(), event.bit());
+            assert_eq!(u32::try_from(event).unwrap(), event.bit());
+        }
+
+        assert_eq!(IrqSet::all().iter().count(), InterruptEvent::ALL.len());
+    }
+
+    #[test]
+    fn irq_set_operations() {
+        let rx = IrqSet::from([InterruptEvent::RxDataReady, InterruptEvent::RxDataDiscarded]);
+        let tx = IrqSet::from(InterruptEvent::TxDataSent);
+
+        let both = rx.union(tx);
+        assert_eq!(both.bits(), 0b111);
+        assert_eq!(both, rx | tx);
+        assert_eq!(both.intersection(tx), tx);
+        assert_eq!(both.difference(tx), rx);
+
+        let without = both.without(InterruptEvent::RxDataDiscarded);
+        assert!(!without.contains(InterruptEvent::RxDataDiscarded));
+        assert!(without.contains(InterruptEvent::RxDataReady));
+        assert!(IrqSet::empty().without(InterruptEvent::Lock).is_empty());
+
+        let mut set = IrqSet::empty();
+        set.insert(InterruptEvent::OthersAesEndOfOperation);
+        set.insert(InterruptEvent::RxDataReady);
+        set.remove(InterruptEvent::RxDataReady);
+        assert_eq!(set, IrqSet::from(InterruptEvent::OthersAesEndOfOperation));
+
+        // Reserved bit 31 is dropped
+        assert_eq!(IrqSet::from_bits(u32::MAX), IrqSet::all());
+        assert_eq!(!IrqSet::all(), IrqSet::empty());
+    }
+
+    #[test]
+    fn irq_set_iterates_in_bit_order() {
+        let set = IrqSet::from([
+            InterruptEvent::TimerRxTimeout,
+            InterruptEvent::RxDataReady,
+            InterruptEvent::Ready,
+        ]);
+
+        let mut events = set.iter();
+        assert_eq!(events.next(), Some(InterruptEvent::RxDataReady));
+        assert_eq!(events.next(), Some(InterruptEvent::Ready));
+        assert_eq!(events.next(), Some(InterruptEvent::TimerRxTimeout));
+        assert_eq!(events.next(), None);
+
+        assert_eq!(set.iter().collect::<IrqSet>(), set);
+    }
+
+    #[test]
+    fn irq_mask_builder_remove() {
+        let mut builder = IrqMaskBuilder::new();
+        builder
+            .set(InterruptEvent::TxDataSent)
+            .set(InterruptEvent::RxDataReady)
+            .remove(InterruptEvent::TxDataSent)
+            .remove(InterruptEvent::Lock);
+
+        let mask = IrqMask::from(builder);
+        assert_eq!(mask.as_u32(), InterruptEvent::RxDataReady.bit());
+        assert_eq!(mask.events(), IrqSet::from(InterruptEvent::RxDataReady));
+    }
+
+    #[test]
+    fn is_set_checks_the_event_bit() {
+        let status = IrqStatus::from_bytes(&[0x40, 0x01, 0x00, 0x04]).unwrap();
+
+        assert!(status.is_set(InterruptEvent::OthersAesEndOfOperation));
+        assert!(status.is_set(InterruptEvent::Ready));
+        assert!(status.is_set(InterruptEvent::TxDataSent));
+        assert!(!status.is_set(InterruptEvent::RxDataReady));
+        assert!(!status.is_set(InterruptEvent::RxDataDiscarded));
+
+        // Every event of the mask is reported, and only those
+        let mask = IrqMask::from(IrqSet::from(InterruptEvent::Lock));
+        for event in InterruptEvent::ALL {
+            assert_eq!(mask.is_set(event), event == InterruptEvent::Lock);
+        }
+    }
 }
@@ -23,4 +23,12 @@ pub struct RxCtrlField {
     /// Control field(s) of the received packet, byte 3
     #[register(bits = "24..31", reset = 0)]
     pub rx_ctrl3: u8,
-}
\ No newline at end of file
+}
+
+impl RxCtrlField {
+    /// Control field of the received packet, with the layout of
+    /// [`set_tx_control_field`](crate::SpiritPacketFormats::set_tx_control_field)
+    pub fn control(&self) -> u32 {
+        u32::from_be_bytes([self.rx_ctrl0, self.rx_ctrl1, self.rx_ctrl2, self.rx_ctrl3])
+    }
+}
@@ -5,9 +5,14 @@ use register_rs::*;
 #[register(address = 0xA7, length = 1)]
 pub struct XoRcoConfig {
     /// Reserved
-    #[register(bits = "4..7", reset = 0b1110)]
+    #[register(bits = "6..7", reset = 0b11)]
     _reserved_0: u8,
 
+    /// Divider of the RCO reference (not documented in the datasheet): with a 25 MHz
+    /// digital clock the RCO runs at 33.3 kHz when set to `0b11`, at 36.1 kHz otherwise
+    #[register(bits = "4..5", reset = 0b10)]
+    pub rco_divider: u8,
+
     /// the 34.7kHz signal must be supplied from a GPIO pin
     #[register(bit = "3", reset = false)]
     pub ext_rcosc: bool,
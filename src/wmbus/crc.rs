@@ -0,0 +1,32 @@
+/// CRC-16 generator polynomial of EN 13757-4:
+/// `x^16 + x^13 + x^12 + x^11 + x^10 + x^8 + x^6 + x^5 + x^2 + 1`
+pub const CRC_POLYNOMIAL: u16 = 0x3D65;
+
+/// CRC-16 of a WM-Bus block (initial value `0x0000`, complemented result). It is
+/// transmitted most significant byte first
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ CRC_POLYNOMIAL
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        // CRC-16/EN-13757 check value
+        assert_eq!(crc16(b"123456789"), 0xC2B7);
+    }
+}
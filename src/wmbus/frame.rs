@@ -0,0 +1,300 @@
+use crate::{RadioError, RadioResult};
+
+use super::crc16;
+
+/// Bytes of the first block covered by the L field: C, M and A fields
+const HEADER_LENGTH: usize = 9;
+
+/// Data bytes of the Format A blocks following the first one
+const FORMAT_A_BLOCK_LENGTH: usize = 16;
+
+/// Bytes (L field and CRC included) of the first two Format B blocks
+const FORMAT_B_BLOCK_LENGTH: usize = 128;
+
+/// Link layer frame format
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub enum FrameFormat {
+    /// First block of 10 bytes followed by blocks of 16 bytes, each with its CRC. The
+    /// L field does not count the CRCs
+    A,
+    /// First and second block (up to 128 bytes) share one CRC, an optional third block has
+    /// its own. The L field counts the CRCs
+    B,
+}
+
+/// Fields of the first block
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub struct FrameHeader {
+    /// C field: frame type and direction
+    pub control: u8,
+    /// M field: manufacturer code
+    pub manufacturer: u16,
+    /// A field: identification number (4 bytes, BCD), version and device type, in
+    /// transmission order
+    pub address: [u8; 6],
+}
+
+/// Link layer frame
+#[derive(Clone, Copy, Debug, PartialEq, defmt::Format)]
+pub struct Frame<'a> {
+    /// First block fields
+    pub header: FrameHeader,
+    /// CI field: application layer type
+    pub ci: u8,
+    /// Bytes following the CI field
+    pub data: &'a [u8],
+}
+
+impl<'a> Frame<'a> {
+    /// Number of bytes of the encoded frame, `None` if it does not fit the L field
+    pub fn encoded_len(&self, format: FrameFormat) -> Option<usize> {
+        // L field, C, M, A and CI fields
+        let plain = 1 + HEADER_LENGTH + 1 + self.data.len();
+        let len = plain + 2 * blocks(format, plain);
+
+        match format {
+            FrameFormat::A if plain - 1 <= u8::MAX as usize => Some(len),
+            FrameFormat::B if len - 1 <= u8::MAX as usize => Some(len),
+            _ => None,
+        }
+    }
+
+    /// Encode the frame into `out`, returning the number of bytes written. The result is the
+    /// payload to transmit in WM-Bus mode
+    pub fn encode(&self, format: FrameFormat, out: &mut [u8]) -> RadioResult<usize> {
+        let len = self.encoded_len(format).ok_or(RadioError::PacketTooLong)?;
+        if out.len() < len {
+            return Err(RadioError::BufferTooSmall);
+        }
+
+        let plain = 1 + HEADER_LENGTH + 1 + self.data.len();
+        let [manufacturer_low, manufacturer_high] = self.header.manufacturer.to_le_bytes();
+        let l_field = match format {
+            FrameFormat::A => plain - 1,
+            FrameFormat::B => len - 1,
+        } as u8;
+
+        let mut head = [0; 1 + HEADER_LENGTH + 1];
+        head[0] = l_field;
+        head[1] = self.header.control;
+        head[2] = manufacturer_low;
+        head[3] = manufacturer_high;
+        head[4..10].copy_from_slice(&self.header.address);
+        head[10] = self.ci;
+
+        let mut written = 0;
+        let mut start = 0;
+        while start < plain {
+            let end = block_end(format, start, plain);
+            for i in start..end {
+                out[written] = if i < head.len() {
+                    head[i]
+                } else {
+                    self.data[i - head.len()]
+                };
+                written += 1;
+            }
+
+            let crc = crc16(&out[written - (end - start)..written]);
+            out[written..written + 2].copy_from_slice(&crc.to_be_bytes());
+            written += 2;
+            start = end;
+        }
+
+        Ok(written)
+    }
+
+    /// Decode the frame at the beginning of `raw`, checking every block CRC. The data
+    /// following the CI field is copied to `buffer`.
+    ///
+    /// Returns [`RadioError::InvalidFrame`] if `raw` is shorter than the L field announces,
+    /// [`RadioError::CrcMismatch`] if a block is corrupted
+    pub fn decode(format: FrameFormat, raw: &[u8], buffer: &'a mut [u8]) -> RadioResult<Self> {
+        let l_field = *raw.first().ok_or(RadioError::InvalidFrame)? as usize;
+        let plain = match format {
+            FrameFormat::A => l_field + 1,
+            FrameFormat::B if l_field < FORMAT_B_BLOCK_LENGTH => (l_field + 1).saturating_sub(2),
+            FrameFormat::B => l_field + 1 - 4,
+        };
+        if plain < 1 + HEADER_LENGTH + 1 {
+            return Err(RadioError::InvalidFrame);
+        }
+
+        // Format B lengths falling between two and three blocks cannot be encoded
+        let len = plain + 2 * blocks(format, plain);
+        if format == FrameFormat::B && len != l_field + 1 {
+            return Err(RadioError::InvalidFrame);
+        }
+        if raw.len() < len {
+            return Err(RadioError::InvalidFrame);
+        }
+
+        let data_len = plain - (1 + HEADER_LENGTH + 1);
+        if buffer.len() < data_len {
+            return Err(RadioError::BufferTooSmall);
+        }
+
+        let mut head = [0; 1 + HEADER_LENGTH + 1];
+        let mut read = 0;
+        let mut start = 0;
+        while start < plain {
+            let end = block_end(format, start, plain);
+            let block = &raw[read..read + end - start];
+            let crc = u16::from_be_bytes([raw[read + end - start], raw[read + end - start + 1]]);
+            if crc16(block) != crc {
+                return Err(RadioError::CrcMismatch);
+            }
+
+            for (i, byte) in (start..end).zip(block) {
+                if i < head.len() {
+                    head[i] = *byte;
+                } else {
+                    buffer[i - head.len()] = *byte;
+                }
+            }
+
+            read += end - start + 2;
+            start = end;
+        }
+
+        Ok(Self {
+            header: FrameHeader {
+                control: head[1],
+                manufacturer: u16::from_le_bytes([head[2], head[3]]),
+                address: [head[4], head[5], head[6], head[7], head[8], head[9]],
+            },
+            ci: head[10],
+            data: &buffer[..data_len],
+        })
+    }
+}
+
+/// End (exclusive) of the block starting at `start` in the frame without CRCs, `plain`
+/// bytes long including the L field
+fn block_end(format: FrameFormat, start: usize, plain: usize) -> usize {
+    match format {
+        FrameFormat::A if start == 0 => 1 + HEADER_LENGTH,
+        FrameFormat::A => (start + FORMAT_A_BLOCK_LENGTH).min(plain),
+        FrameFormat::B if start == 0 => (FORMAT_B_BLOCK_LENGTH - 2).min(plain),
+        FrameFormat::B => plain,
+    }
+}
+
+/// Number of CRCs of a frame `plain` bytes long without CRCs, L field included
+fn blocks(format: FrameFormat, plain: usize) -> usize {
+    let mut blocks = 0;
+    let mut start = 0;
+    while start < plain {
+        start = block_end(format, start, plain);
+        blocks += 1;
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example telegram of the OMS specification (security profile A), Format A with its
+    /// block CRCs
+    const TELEGRAM_A: [u8; 55] = [
+        0x2E, 0x44, 0x93, 0x15, 0x78, 0x56, 0x34, 0x12, 0x33, 0x03, 0x33, 0x63, 0x7A, 0x2A, 0x00,
+        0x20, 0x25, 0x59, 0x23, 0xC9, 0x5A, 0xAA, 0x26, 0xD1, 0xB2, 0xE7, 0x49, 0x3B, 0xC2, 0xAD,
+        0x01, 0x3E, 0xC4, 0xA6, 0xF6, 0xD3, 0x52, 0x9B, 0x52, 0x0E, 0xDF, 0xF0, 0xEA, 0x6D, 0xEF,
+        0xC9, 0x55, 0xB2, 0x9D, 0x6D, 0x69, 0xEB, 0xF3, 0xEC, 0x8A,
+    ];
+
+    /// Encrypted data following the CI field
+    const DATA: [u8; 36] = [
+        0x2A, 0x00, 0x20, 0x25, 0x59, 0x23, 0xC9, 0x5A, 0xAA, 0x26, 0xD1, 0xB2, 0xE7, 0x49, 0x3B,
+        0x01, 0x3E, 0xC4, 0xA6, 0xF6, 0xD3, 0x52, 0x9B, 0x52, 0x0E, 0xDF, 0xF0, 0xEA, 0x6D, 0xEF,
+        0xC9, 0x9D, 0x6D, 0x69, 0xEB, 0xF3,
+    ];
+
+    fn frame() -> Frame<'static> {
+        Frame {
+            header: FrameHeader {
+                control: 0x44,
+                manufacturer: 0x1593,
+                address: [0x78, 0x56, 0x34, 0x12, 0x33, 0x03],
+            },
+            ci: 0x7A,
+            data: &DATA,
+        }
+    }
+
+    #[test]
+    fn decode_format_a() {
+        let mut buffer = [0; 64];
+        let decoded = Frame::decode(FrameFormat::A, &TELEGRAM_A, &mut buffer).unwrap();
+        assert_eq!(decoded, frame());
+    }
+
+    #[test]
+    fn encode_format_a() {
+        let mut raw = [0; 64];
+        assert_eq!(frame().encoded_len(FrameFormat::A), Some(TELEGRAM_A.len()));
+        let len = frame().encode(FrameFormat::A, &mut raw).unwrap();
+        assert_eq!(raw[..len], TELEGRAM_A);
+
+        assert!(matches!(
+            frame().encode(FrameFormat::A, &mut raw[..len - 1]),
+            Err(RadioError::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn format_b() {
+        // Same telegram in Format B: a single block whose CRC is counted by the L field
+        let mut raw = [0; 64];
+        let len = frame().encode(FrameFormat::B, &mut raw).unwrap();
+        assert_eq!(len, 49);
+        assert_eq!(raw[0], 0x30);
+        assert_eq!(raw[1..10], TELEGRAM_A[1..10]);
+        assert_eq!(raw[10], 0x7A);
+        assert_eq!(raw[11..47], DATA);
+        assert_eq!(raw[47..49], [0xB8, 0x53]);
+
+        let mut buffer = [0; 64];
+        let decoded = Frame::decode(FrameFormat::B, &raw[..len], &mut buffer).unwrap();
+        assert_eq!(decoded, frame());
+    }
+
+    #[test]
+    fn format_b_third_block() {
+        let data: [u8; 150] = core::array::from_fn(|i| i as u8);
+        let frame = Frame {
+            data: &data,
+            ..frame()
+        };
+
+        let mut raw = [0; 256];
+        let len = frame.encode(FrameFormat::B, &mut raw).unwrap();
+        assert_eq!(len, 1 + 9 + 1 + data.len() + 4);
+        assert_eq!(raw[0] as usize, len - 1);
+        assert_eq!(raw[126..128], crc16(&raw[..126]).to_be_bytes());
+
+        let mut buffer = [0; 150];
+        let decoded = Frame::decode(FrameFormat::B, &raw[..len], &mut buffer).unwrap();
+        assert_eq!(decoded, frame);
+    }
+
+    #[test]
+    fn decode_checks_every_block() {
+        let mut buffer = [0; 64];
+        for i in [3, 20, 50] {
+            let mut raw = TELEGRAM_A;
+            raw[i] ^= 0x01;
+            assert!(matches!(
+                Frame::decode(FrameFormat::A, &raw, &mut buffer),
+                Err(RadioError::CrcMismatch)
+            ));
+        }
+
+        assert!(matches!(
+            Frame::decode(FrameFormat::A, &TELEGRAM_A[..54], &mut buffer),
+            Err(RadioError::InvalidFrame)
+        ));
+    }
+}
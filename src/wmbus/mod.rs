@@ -0,0 +1,35 @@
+//! # Wireless M-Bus Link Layer
+//! Encoding and decoding of the EN 13757-4 link layer frames carried by the SPIRIT1 in
+//! [`PacketFormat::WMBus`](crate::registers::PacketFormat) mode.
+//!
+//! In WM-Bus mode the packet handler only adds the preamble, the sync word and the
+//! postamble of the submode: the L, C, M, A and CI fields as well as the block CRCs are
+//! part of the payload written to (and read from) the FIFO. The T mode 3 out of 6 coding is
+//! also done by the radio, [`three_of_six`] is provided to work with raw chip captures.
+//!
+//! Everything in this module is independent of the radio and runs on the host.
+//!
+//! ## Example
+//! ```no_run
+//! # use spirit1_rs::{prelude::*, wmbus::*};
+//! # fn send(radio: &mut impl Spirit1Driver) -> RadioResult<()> {
+//! let frame = Frame {
+//!     header: FrameHeader { control: 0x44, manufacturer: 0x2D2C, address: [0x78, 0x56, 0x34, 0x12, 0x01, 0x07] },
+//!     ci: 0x7A,
+//!     data: &[0x01, 0x00, 0x00, 0x00],
+//! };
+//!
+//! let mut raw = [0; 290];
+//! let len = frame.encode(FrameFormat::A, &mut raw)?;
+//! radio.tx_blocking(&raw[..len])?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod crc;
+pub use crc::*;
+
+mod frame;
+pub use frame::*;
+
+pub mod three_of_six;
@@ -0,0 +1,139 @@
+//! # 3 out of 6 Coding
+//! T mode (meter to other) chip coding: every nibble is sent as a 6 chip code word holding
+//! three ones, most significant nibble first. A byte is therefore sent as 12 chips and an
+//! odd number of bytes leaves 4 chips, padded with zeros, in the last encoded byte.
+
+use crate::{RadioError, RadioResult};
+
+/// Code word of each nibble
+const CODES: [u8; 16] = [
+    0x16, 0x0D, 0x0E, 0x0B, 0x1C, 0x19, 0x1A, 0x13, 0x2C, 0x25, 0x26, 0x23, 0x34, 0x31, 0x32, 0x29,
+];
+
+/// Number of bytes taken by `len` bytes once encoded
+pub const fn encoded_len(len: usize) -> usize {
+    (len * 3).div_ceil(2)
+}
+
+/// Number of bytes carried by `len` encoded bytes
+pub const fn decoded_len(len: usize) -> usize {
+    len * 2 / 3
+}
+
+/// Encode `data` into `out`, returning the number of bytes written
+pub fn encode(data: &[u8], out: &mut [u8]) -> RadioResult<usize> {
+    let len = encoded_len(data.len());
+    if out.len() < len {
+        return Err(RadioError::BufferTooSmall);
+    }
+
+    let mut bits: u32 = 0;
+    let mut count = 0;
+    let mut written = 0;
+    for byte in data {
+        bits = (bits << 12)
+            | (CODES[(byte >> 4) as usize] as u32) << 6
+            | CODES[(byte & 0x0F) as usize] as u32;
+        count += 12;
+
+        while count >= 8 {
+            count -= 8;
+            out[written] = (bits >> count) as u8;
+            written += 1;
+        }
+    }
+
+    if count > 0 {
+        out[written] = (bits << (8 - count)) as u8;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// Decode `data` into `out`, returning the number of bytes written. Returns
+/// [`RadioError::InvalidFrame`] on a chip sequence which is not a code word
+pub fn decode(data: &[u8], out: &mut [u8]) -> RadioResult<usize> {
+    let len = decoded_len(data.len());
+    if out.len() < len {
+        return Err(RadioError::BufferTooSmall);
+    }
+
+    let mut bits: u32 = 0;
+    let mut count = 0;
+    let mut written = 0;
+    for byte in data {
+        bits = (bits << 8) | *byte as u32;
+        count += 8;
+
+        if count >= 12 {
+            count -= 12;
+            let high = nibble((bits >> (count + 6)) as u8 & 0x3F)?;
+            let low = nibble((bits >> count) as u8 & 0x3F)?;
+            out[written] = high << 4 | low;
+            written += 1;
+        }
+    }
+
+    Ok(written)
+}
+
+fn nibble(code: u8) -> RadioResult<u8> {
+    CODES
+        .iter()
+        .position(|c| *c == code)
+        .map(|nibble| nibble as u8)
+        .ok_or(RadioError::InvalidFrame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_words_hold_three_ones() {
+        for code in CODES {
+            assert_eq!(code.count_ones(), 3);
+            assert!(code < 0x40);
+        }
+    }
+
+    #[test]
+    fn encode_known_chips() {
+        // L, C and the low byte of the M field of the Format A example telegram
+        let mut out = [0; 5];
+        assert_eq!(encode(&[0x2E, 0x44, 0x93], &mut out).unwrap(), 5);
+        assert_eq!(out, [0x3B, 0x27, 0x1C, 0x94, 0xB0]);
+
+        assert!(matches!(
+            encode(&[0x2E, 0x44], &mut out[..2]),
+            Err(RadioError::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: [u8; 256] = core::array::from_fn(|i| i as u8);
+
+        for len in [0, 1, 2, 3, 255, 256] {
+            let mut encoded = [0; encoded_len(256)];
+            let written = encode(&data[..len], &mut encoded).unwrap();
+            assert_eq!(written, encoded_len(len));
+
+            let mut decoded = [0; 256];
+            let read = decode(&encoded[..written], &mut decoded).unwrap();
+            assert_eq!(read, len);
+            assert_eq!(decoded[..read], data[..len]);
+        }
+    }
+
+    #[test]
+    fn decode_rejects_invalid_chips() {
+        // 0b000000 is not a code word
+        let mut out = [0; 1];
+        assert!(matches!(
+            decode(&[0x00, 0x00], &mut out),
+            Err(RadioError::InvalidFrame)
+        ));
+    }
+}